@@ -26,16 +26,72 @@ impl CompiledTarget {
     }
 }
 
+/// The flags to pass to rustc in order to compile the fuzz test with the given sanitizer
+///
+/// AddressSanitizer and MemorySanitizer are compiled such that they can keep going after reporting
+/// an error, which lets fuzzcheck add the failing test case to its pool of test failures.
+pub fn sanitizer_rustflags(sanitizer: Sanitizer) -> String {
+    let mut rustflags = format!(" -Zsanitizer={}", sanitizer.name());
+    match sanitizer {
+        Sanitizer::Address => rustflags.push_str(" -Zsanitizer-recover=address"),
+        Sanitizer::Memory => rustflags.push_str(" -Zsanitizer-memory-track-origins -Zsanitizer-recover=memory"),
+        Sanitizer::Leak | Sanitizer::Thread => {}
+    }
+    rustflags
+}
+
+/// The arguments to pass to cargo in order to compile the fuzz test with the given sanitizer
+///
+/// MemorySanitizer and ThreadSanitizer require the standard library to be instrumented as well,
+/// otherwise they report false positives. This requires the `rust-src` component.
+pub fn sanitizer_cargo_args(sanitizer: Sanitizer) -> Vec<String> {
+    match sanitizer {
+        Sanitizer::Memory | Sanitizer::Thread => vec!["-Zbuild-std".to_owned()],
+        Sanitizer::Address | Sanitizer::Leak => vec![],
+    }
+}
+
+/// The options to pass to the runtime of the given sanitizer, appended to the existing options
+/// found in the environment variable `existing`.
+///
+/// The sanitizer writes its reports to `log_path.<pid>`, where fuzzcheck reads them. AddressSanitizer
+/// and MemorySanitizer keep going after an error, which fuzzcheck then reports as a test failure.
+/// The errors from which the sanitizer cannot recover abort the process, so that fuzzcheck can
+/// catch the signal and save the failing test case.
+pub fn sanitizer_runtime_options(sanitizer: Sanitizer, existing: Option<String>, log_path: &Path) -> String {
+    let mut options = existing.unwrap_or_default();
+    if !options.is_empty() {
+        options.push(':');
+    }
+    options.push_str("abort_on_error=1");
+    options.push_str(&format!(":log_path={}", log_path.display()));
+    match sanitizer {
+        Sanitizer::Address => {
+            // leaks are only detected at exit, when the failing test case is not known anymore
+            options.push_str(":detect_leaks=0:halt_on_error=0");
+        }
+        Sanitizer::Memory => {
+            options.push_str(":halt_on_error=0");
+        }
+        Sanitizer::Thread => {
+            // by default, ThreadSanitizer keeps going after reporting a data race
+            options.push_str(":halt_on_error=1");
+        }
+        Sanitizer::Leak => {}
+    }
+    options
+}
+
 pub fn launch_executable(
     target_name: &str,
     args: &Arguments,
     compiled_target: &CompiledTarget,
     cargo_args: &[String],
-    address_sanitizer: bool,
     profile: &str,
     instrument_coverage: bool,
     stdio: impl Fn() -> Stdio,
 ) -> std::io::Result<process::Child> {
+    let sanitizer = args.sanitizer;
     let args = string_from_args(args);
     let mut rustflags = std::env::var("RUSTFLAGS").unwrap_or_else(|_| "".to_owned());
     if instrument_coverage {
@@ -43,15 +99,25 @@ pub fn launch_executable(
     }
     rustflags.push_str(" --cfg fuzzing");
 
-    if address_sanitizer {
-        rustflags.push_str(" -Zsanitizer=address");
+    let mut command = Command::new("cargo");
+    if let Some(sanitizer) = sanitizer {
+        rustflags.push_str(&sanitizer_rustflags(sanitizer));
+        let reports_folder = std::env::current_dir()?.join(BUILD_FOLDER).join("sanitizer_reports");
+        std::fs::create_dir_all(&reports_folder)?;
+        let log_path = reports_folder.join(target_name);
+        let env_var = sanitizer.options_env_var();
+        command.env(
+            env_var,
+            sanitizer_runtime_options(sanitizer, std::env::var(env_var).ok(), &log_path),
+        );
     }
-    let child = Command::new("cargo")
+    let child = command
         .env("FUZZCHECK_ARGS", args)
         .env("RUSTFLAGS", &rustflags)
         .arg("test")
         .args(compiled_target.to_args())
         .args(cargo_args)
+        .args(sanitizer.map(sanitizer_cargo_args).unwrap_or_default())
         .args(["--target", TARGET])
         .arg("--profile")
         .arg(profile)
@@ -73,7 +139,6 @@ pub fn input_minify_command(
     args: &Arguments,
     compiled_target: &CompiledTarget,
     cargo_args: &[String],
-    address_sanitizer: bool,
     profile: &str,
     instrument_coverage: bool,
     stdio: &impl Fn() -> Stdio,
@@ -127,7 +192,6 @@ pub fn input_minify_command(
         &config,
         compiled_target,
         cargo_args,
-        address_sanitizer,
         profile,
         instrument_coverage,
         stdio,
@@ -150,7 +214,6 @@ pub fn input_minify_command(
             &config,
             compiled_target,
             cargo_args,
-            address_sanitizer,
             profile,
             instrument_coverage,
             Stdio::inherit,
@@ -181,6 +244,15 @@ pub fn string_from_args(args: &Arguments) -> String {
             s.push(' ');
             Some(input_file.clone())
         }
+        FuzzerCommand::Reproduce { input_file, emit_test } => {
+            s.push_str("--command ");
+            s.push_str(COMMAND_REPRODUCE);
+            s.push(' ');
+            if *emit_test {
+                s.push_str(&format!("--{} ", EMIT_TEST_FLAG));
+            }
+            Some(input_file.clone())
+        }
     };
     if let Some(input_file) = input_file {
         s.push_str(&format!("--{} {} ", INPUT_FILE_FLAG, input_file.display()));
@@ -229,5 +301,17 @@ pub fn string_from_args(args: &Arguments) -> String {
     if args.detect_infinite_loop {
         s.push_str(&format!("--{} ", DETECT_INFINITE_LOOP_FLAG));
     }
+    if let Some(sanitizer) = args.sanitizer {
+        s.push_str(&format!("--{} {} ", SANITIZER_FLAG, sanitizer.name()));
+    }
+    if let Some(interval) = args.leak_check_interval {
+        s.push_str(&format!("--{} {} ", LEAK_CHECK_INTERVAL_FLAG, interval));
+    }
+    if args.strict_corpus {
+        s.push_str(&format!("--{} ", STRICT_CORPUS_FLAG));
+    }
+    if let Some(folder) = &args.quarantine_invalid {
+        s.push_str(&format!("--{} {} ", QUARANTINE_INVALID_FLAG, folder.display()));
+    }
     s
 }
@@ -16,7 +16,11 @@ fn main() -> Result<(), Box<dyn Error>> {
     parser.optflag("", "lib", "Test only this package's library unit tests (default)");
     parser.optopt("", "bin", "Test only the specified binary", "<NAME>");
     parser.optopt("", "test", "Test only the specified test target", "<NAME>");
-    parser.optflag("", "address-sanitizer", "Use AddressSanitizer");
+    parser.optflag(
+        "",
+        "address-sanitizer",
+        &format!("Use AddressSanitizer, same as --{} address", SANITIZER_FLAG),
+    );
     parser.optopt(
         "",
         "profile",
@@ -60,7 +64,17 @@ fn main() -> Result<(), Box<dyn Error>> {
             return Err(Box::new(e));
         }
     };
-    let address_sanitizer = matches.opt_present("address-sanitizer");
+    if matches.opt_present("address-sanitizer") {
+        match args.sanitizer {
+            None | Some(Sanitizer::Address) => args.sanitizer = Some(Sanitizer::Address),
+            Some(_) => {
+                return Err(Box::new(ArgumentsError::Validation(format!(
+                    "--address-sanitizer cannot be used together with another --{}.",
+                    SANITIZER_FLAG
+                ))));
+            }
+        }
+    }
     let profile = matches.opt_str("profile").unwrap_or_else(|| "release".to_owned());
     let no_instrument_coverage = matches.opt_present("no-instrument-coverage");
 
@@ -117,7 +131,6 @@ fn main() -> Result<(), Box<dyn Error>> {
                 &args,
                 &compiled_target,
                 &cargo_args,
-                address_sanitizer,
                 &profile,
                 !no_instrument_coverage,
                 &process::Stdio::inherit,
@@ -130,19 +143,17 @@ fn main() -> Result<(), Box<dyn Error>> {
                 &args,
                 &compiled_target,
                 &cargo_args,
-                address_sanitizer,
                 &profile,
                 !no_instrument_coverage,
                 &process::Stdio::inherit,
             )?;
         }
-        FuzzerCommand::Read { .. } => {
+        FuzzerCommand::Read { .. } | FuzzerCommand::Reproduce { .. } => {
             let exec = launch_executable(
                 target_name,
                 &args,
                 &compiled_target,
                 &cargo_args,
-                address_sanitizer,
                 &profile,
                 !no_instrument_coverage,
                 &process::Stdio::inherit,
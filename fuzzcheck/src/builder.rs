@@ -59,17 +59,26 @@ is to use the [`SensorAndPoolBuilder`], although it only offers a couple limited
 */
 
 use std::borrow::Borrow;
+use std::cell::RefCell;
 use std::marker::PhantomData;
 use std::path::Path;
+use std::rc::Rc;
 use std::result::Result;
 use std::time::Duration;
 
 use fuzzcheck_common::arg::{
-    options_parser, Arguments, ArgumentsError, FuzzerCommand, COMMAND_FUZZ, COMMAND_MINIFY_INPUT, INPUT_FILE_FLAG,
+    options_parser, Arguments, ArgumentsError, FuzzerCommand, COMMAND_FUZZ, COMMAND_MINIFY_INPUT, COMMAND_REPRODUCE,
+    EMIT_TEST_FLAG, INPUT_FILE_FLAG,
 };
 
 use crate::code_coverage_sensor::CodeCoverageSensor;
+#[doc(inline)]
+pub use crate::fuzz_test_arguments::{
+    FuzzTestArguments2, FuzzTestArguments3, FuzzTestArguments4, FuzzTestArguments5, FuzzTestArguments6,
+    MultipleArguments,
+};
 use crate::fuzzer::{Fuzzer, FuzzingResult};
+use crate::regression_test::FuzzTestLocation;
 use crate::sensors_and_pools::{
     AndPool, DifferentObservations, MaximiseEachCounterPool, MaximiseObservationPool, MostNDiversePool,
     SameObservations, SimplestToActivateCounterPool, WrapperSensor,
@@ -78,7 +87,7 @@ use crate::sensors_and_pools::{
 use crate::SerdeSerializer;
 use crate::{
     split_string_by_whitespace, CompatibleWithObservations, DefaultMutator, Mutator, PoolExt, Sensor, SensorExt,
-    Serializer,
+    Serializer, ToRustExpr,
 };
 
 /** A function that can be fuzz-tested.
@@ -140,6 +149,57 @@ where
     }
 }
 
+/** A function that can be fuzz-tested and that needs access to some mutable state.
+
+This is the equivalent of [`FuzzTestFunction`] for the functions given to [`fuzz_test_with_state`].
+A function of type `Fn(&mut S, &T) -> bool` (or returning `()` or `Result<_,_>`)
+is converted to `Fn(&T) -> bool` by giving it a shared reference to the state.
+*/
+pub trait FuzzTestFunctionWithState<S, T, FT: ?Sized, ImplId> {
+    type NormalizedFunction: for<'a> Fn(&'a T) -> bool;
+    fn test_function(self, state: Rc<RefCell<S>>) -> Self::NormalizedFunction;
+}
+
+impl<S, T, FT: ?Sized, F> FuzzTestFunctionWithState<S, T, FT, ReturnBool> for F
+where
+    T: Borrow<FT>,
+    F: Fn(&mut S, &FT) -> bool,
+{
+    type NormalizedFunction = impl Fn(&T) -> bool;
+    #[no_coverage]
+    fn test_function(self, state: Rc<RefCell<S>>) -> Self::NormalizedFunction {
+        #[no_coverage]
+        move |x| (self)(&mut state.borrow_mut(), x.borrow())
+    }
+}
+impl<S, T, FT: ?Sized, F> FuzzTestFunctionWithState<S, T, FT, ReturnVoid> for F
+where
+    T: Borrow<FT>,
+    F: Fn(&mut S, &FT),
+{
+    type NormalizedFunction = impl Fn(&T) -> bool;
+    #[no_coverage]
+    fn test_function(self, state: Rc<RefCell<S>>) -> Self::NormalizedFunction {
+        #[no_coverage]
+        move |x| {
+            self(&mut state.borrow_mut(), x.borrow());
+            true
+        }
+    }
+}
+impl<S, T, FT: ?Sized, F, V, E> FuzzTestFunctionWithState<S, T, FT, ReturnResult> for F
+where
+    T: Borrow<FT>,
+    F: Fn(&mut S, &FT) -> Result<V, E>,
+{
+    type NormalizedFunction = impl Fn(&T) -> bool;
+    #[no_coverage]
+    fn test_function(self, state: Rc<RefCell<S>>) -> Self::NormalizedFunction {
+        #[no_coverage]
+        move |x| self(&mut state.borrow_mut(), x.borrow()).is_ok()
+    }
+}
+
 /// A fuzz-test builder that knows the function to fuzz-test. It is created by calling [`fuzz_test(..)`](fuzz_test).
 ///
 /// Use [`self.mutator(..)`](FuzzerBuilder1::mutator) to specify the [mutator](Mutator)
@@ -150,12 +210,19 @@ where
 /// to use the default mutator, serializer, sensor, pool, and arguments, and obtain a [`FuzzerBuilder5`].
 /// This method is only available if the argument of the test function implements [`DefaultMutator`]
 /// and is serializable with serde.
-pub struct FuzzerBuilder1<T, F>
+///
+/// If it was created by [`fuzz_test_with_state(..)`](fuzz_test_with_state), the type parameter `S` is the
+/// type of the state given to the test function, and [`self.reset_state(..)`](FuzzerBuilder1::reset_state) can
+/// be used to specify how to reset it between each test case.
+pub struct FuzzerBuilder1<T, F, S = ()>
 where
     T: ?Sized,
     F: Fn(&T) -> bool + 'static,
 {
     test_function: F,
+    state: Rc<RefCell<S>>,
+    reset_hook: Option<Box<dyn Fn()>>,
+    location: FuzzTestLocation,
     _phantom: PhantomData<*const T>,
 }
 
@@ -169,6 +236,8 @@ where
     M: Mutator<V>,
 {
     test_function: F,
+    reset_hook: Option<Box<dyn Fn()>>,
+    location: FuzzTestLocation,
     mutator: M,
     _phantom: PhantomData<*const V>,
 }
@@ -186,6 +255,8 @@ where
     M: Mutator<V>,
 {
     test_function: F,
+    reset_hook: Option<Box<dyn Fn()>>,
+    location: FuzzTestLocation,
     mutator: M,
     serializer: Box<dyn Serializer<Value = V>>,
     _phantom: PhantomData<*const V>,
@@ -207,6 +278,8 @@ where
     P: CompatibleWithObservations<Sens::Observations>,
 {
     test_function: F,
+    reset_hook: Option<Box<dyn Fn()>>,
+    location: FuzzTestLocation,
     mutator: M,
     serializer: Box<dyn Serializer<Value = V>>,
     sensor: Sens,
@@ -227,6 +300,11 @@ where
 /// * [`self.stop_after_iterations(..)`](FuzzerBuilder5::stop_after_iterations)
 /// * [`self.stop_after_duration(..)`](FuzzerBuilder5::stop_after_duration)
 /// * [`self.stop_after_first_test_failure(..)`](FuzzerBuilder5::stop_after_first_test_failure)
+///
+/// And add test cases to run at the start of the fuzz test with [`self.seed_inputs(..)`](FuzzerBuilder5::seed_inputs).
+///
+/// If the test cases implement [`ToRustExpr`], use [`self.with_to_rust_expr()`](FuzzerBuilder5::with_to_rust_expr)
+/// to be able to write regression tests for the test failures found by the fuzzer.
 pub struct FuzzerBuilder5<F, M, V, Sens, P>
 where
     F: Fn(&V) -> bool + 'static,
@@ -236,11 +314,15 @@ where
     P: CompatibleWithObservations<Sens::Observations>,
 {
     test_function: F,
+    reset_hook: Option<Box<dyn Fn()>>,
+    location: FuzzTestLocation,
     mutator: M,
     serializer: Box<dyn Serializer<Value = V>>,
     sensor: Sens,
     pool: P,
     arguments: Arguments,
+    seed_inputs: Vec<V>,
+    to_rust_expr: Option<fn(&V) -> String>,
     _phantom: PhantomData<*const V>,
 }
 
@@ -255,7 +337,22 @@ where
     1. `Fn(&T)` : the fuzzer will only report a failure when the given function crashes
     2. `Fn(&T) -> Bool` : the fuzzer will report a failure when the output is `false`
     3. `Fn(&T) -> Result<_,_>` : the fuzzer will report a failure when the output is `Err(..)`
+
+    The function can also take up to six arguments, e.g. `Fn(&A, &B) -> Bool`. In that case, the
+    test cases have the type [`FuzzTestArguments2<A::Owned, B::Owned>`](FuzzTestArguments2), which
+    contains each argument in a separate field. Its default mutator is built from the mutators of
+    each argument, and the serde serializer saves it as a record `{ "arg0": .., "arg1": .. }`.
+    ```no_run
+    fn parse(input: &str, strict: &bool) -> bool {
+        // ..
+        # true
+    }
+    let _ = fuzzcheck::fuzz_test(parse)
+        .default_options() // the test cases have the type FuzzTestArguments2<String, bool>
+        .launch();
+    ```
 */
+#[track_caller]
 #[no_coverage]
 pub fn fuzz_test<T, F, TestFunctionKind>(test_function: F) -> FuzzerBuilder1<T::Owned, F::NormalizedFunction>
 where
@@ -265,12 +362,112 @@ where
 {
     FuzzerBuilder1 {
         test_function: test_function.test_function(),
+        state: Rc::new(RefCell::new(())),
+        reset_hook: None,
+        location: FuzzTestLocation::new::<F>(true),
+        _phantom: PhantomData,
+    }
+}
+
+/**
+    Build a fuzz test for a function that needs access to some state that is expensive to create,
+    such as a database connection or a JIT compiler.
+
+    The `setup` closure is called only once, immediately. The state that it returns is then given
+    to the test function every time it is called. Because the setup happens before the fuzzer starts
+    recording observations, the code coverage caused by the setup is not observed by the sensor.
+
+    Use [`.reset_state(..)`](FuzzerBuilder1::reset_state) to specify how to restore the state before
+    each test case. The code coverage caused by the reset is also ignored.
+
+    The test function can have the same kinds of return types as the ones accepted by [`fuzz_test`]:
+    1. `Fn(&mut S, &T)` : the fuzzer will only report a failure when the given function crashes
+    2. `Fn(&mut S, &T) -> Bool` : the fuzzer will report a failure when the output is `false`
+    3. `Fn(&mut S, &T) -> Result<_,_>` : the fuzzer will report a failure when the output is `Err(..)`
+
+    ```no_run
+    struct Interpreter { globals: Vec<u8> }
+    impl Interpreter {
+        fn load() -> Self { Interpreter { globals: vec![] } }
+        fn run(&mut self, program: &[u8]) { self.globals.extend(program) }
+    }
+    let _ = fuzzcheck::fuzz_test_with_state(Interpreter::load, Interpreter::run)
+        .reset_state(|interpreter: &mut Interpreter| interpreter.globals.clear())
+        .default_options()
+        .launch();
+    ```
+*/
+#[track_caller]
+#[no_coverage]
+pub fn fuzz_test_with_state<T, S, F, TestFunctionKind>(
+    setup: impl FnOnce() -> S,
+    test_function: F,
+) -> FuzzerBuilder1<T::Owned, F::NormalizedFunction, S>
+where
+    T: ?Sized + ToOwned + 'static,
+    T::Owned: Clone,
+    S: 'static,
+    F: FuzzTestFunctionWithState<S, T::Owned, T, TestFunctionKind>,
+{
+    let state = Rc::new(RefCell::new(setup()));
+    FuzzerBuilder1 {
+        test_function: test_function.test_function(state.clone()),
+        state,
+        reset_hook: None,
+        location: FuzzTestLocation::new::<F>(false),
         _phantom: PhantomData,
     }
 }
 
+/**
+    Run the test function on the given test case, and panic if the test fails.
+
+    The test case has the same type as the one given to the test function by the fuzzer. It is used
+    by the regression tests written by `cargo fuzzcheck --command reproduce --emit-test`, which can be
+    written the same way regardless of the return type of the test function.
+    ```
+    fn parse(input: &str) -> Result<u8, std::num::ParseIntError> {
+        input.parse()
+    }
+    fuzzcheck::reproduce(parse, "42".to_owned());
+    ```
+*/
+#[no_coverage]
+pub fn reproduce<T, F, TestFunctionKind>(test_function: F, value: T::Owned)
+where
+    T: ?Sized + ToOwned,
+    F: FuzzTestFunction<T::Owned, T, TestFunctionKind>,
+{
+    let test_function = test_function.test_function();
+    assert!(test_function(&value), "the test function failed on the given test case");
+}
+
+impl<T, F, S> FuzzerBuilder1<T, F, S>
+where
+    T: ?Sized,
+    F: Fn(&T) -> bool,
+    S: 'static,
+{
+    /// Specify how to reset the state of the test function before each test case.
+    ///
+    /// The reset hook is called outside of the sensor’s recording, so the code coverage
+    /// that it causes is not observed.
+    #[must_use]
+    #[no_coverage]
+    pub fn reset_state(self, reset: impl Fn(&mut S) + 'static) -> Self {
+        let state = self.state.clone();
+        FuzzerBuilder1 {
+            reset_hook: Some(Box::new(
+                #[no_coverage]
+                move || reset(&mut state.borrow_mut()),
+            )),
+            ..self
+        }
+    }
+}
+
 #[cfg(feature = "serde_json_serializer")]
-impl<T, F> FuzzerBuilder1<T, F>
+impl<T, F, S> FuzzerBuilder1<T, F, S>
 where
     T: ?Sized + ToOwned + 'static,
     T::Owned: Clone + serde::Serialize + for<'e> serde::Deserialize<'e> + DefaultMutator,
@@ -297,7 +494,7 @@ where
     }
 }
 
-impl<T, F> FuzzerBuilder1<T, F>
+impl<T, F, S> FuzzerBuilder1<T, F, S>
 where
     T: ?Sized + ToOwned + 'static,
     T::Owned: Clone + DefaultMutator,
@@ -313,7 +510,7 @@ where
         self.mutator(<T::Owned as DefaultMutator>::default_mutator())
     }
 }
-impl<T, F> FuzzerBuilder1<T, F>
+impl<T, F, S> FuzzerBuilder1<T, F, S>
 where
     T: ?Sized,
     F: Fn(&T) -> bool,
@@ -366,6 +563,8 @@ where
     {
         FuzzerBuilder2 {
             test_function: self.test_function.test_function(),
+            reset_hook: self.reset_hook,
+            location: self.location,
             mutator,
             _phantom: PhantomData,
         }
@@ -402,6 +601,8 @@ where
     {
         FuzzerBuilder3 {
             test_function: self.test_function,
+            reset_hook: self.reset_hook,
+            location: self.location,
             mutator: self.mutator,
             serializer: Box::new(serializer),
             _phantom: PhantomData,
@@ -421,6 +622,8 @@ where
     pub fn serde_serializer(self) -> FuzzerBuilder3<F, M, V> {
         FuzzerBuilder3 {
             test_function: self.test_function,
+            reset_hook: self.reset_hook,
+            location: self.location,
             mutator: self.mutator,
             serializer: Box::new(SerdeSerializer::<V>::default()),
             _phantom: PhantomData,
@@ -441,6 +644,8 @@ where
         let (sensor, pool) = default_sensor_and_pool_with_custom_filter(keep).finish();
         FuzzerBuilder4 {
             test_function: self.test_function,
+            reset_hook: self.reset_hook,
+            location: self.location,
             mutator: self.mutator,
             serializer: self.serializer,
             sensor,
@@ -456,6 +661,8 @@ where
         let (sensor, pool) = default_sensor_and_pool().finish();
         FuzzerBuilder4 {
             test_function: self.test_function,
+            reset_hook: self.reset_hook,
+            location: self.location,
             mutator: self.mutator,
             serializer: self.serializer,
             sensor,
@@ -471,6 +678,8 @@ where
     ) -> FuzzerBuilder4<F, M, V, Sens, P> {
         FuzzerBuilder4 {
             test_function: self.test_function,
+            reset_hook: self.reset_hook,
+            location: self.location,
             mutator: self.mutator,
             serializer: self.serializer,
             sensor,
@@ -492,11 +701,15 @@ where
     pub fn arguments(self, arguments: Arguments) -> FuzzerBuilder5<F, M, V, Sens, P> {
         FuzzerBuilder5 {
             test_function: self.test_function,
+            reset_hook: self.reset_hook,
+            location: self.location,
             mutator: self.mutator,
             serializer: self.serializer,
             sensor: self.sensor,
             pool: self.pool,
             arguments,
+            seed_inputs: vec![],
+            to_rust_expr: None,
             _phantom: self._phantom,
         }
     }
@@ -510,9 +723,11 @@ fuzzcheck <SUBCOMMAND> [OPTIONS]
 SUBCOMMANDS:
     {fuzz}    Run the fuzz test
     {minify}    Minify a crashing test input, requires --{input_file}
+    {reproduce}    Run the test function on a test input, requires --{input_file}
 "#,
             fuzz = COMMAND_FUZZ,
             minify = COMMAND_MINIFY_INPUT,
+            reproduce = COMMAND_REPRODUCE,
             input_file = INPUT_FILE_FLAG,
         );
         help += parser.usage("").as_str();
@@ -530,9 +745,17 @@ fuzzcheck {minify} --{input_file} "artifacts/crash.json"
     and name them {{complexity}}-{{hash}}.json. 
     For example, artifacts/crash.minified/4213--8cd7777109b57b8c.json
     is a minified input of complexity 42.13.
+
+fuzzcheck {reproduce} --{input_file} "artifacts/crash.json" --{emit_test}
+
+    Run the test function on the test input defined in the file "artifacts/crash.json"
+    and write a regression test constructing it in the file fuzzcheck_regressions.rs,
+    next to the fuzz test. The fuzz test must be built with `.with_to_rust_expr()`.
 "#,
             fuzz = COMMAND_FUZZ,
             minify = COMMAND_MINIFY_INPUT,
+            reproduce = COMMAND_REPRODUCE,
+            emit_test = EMIT_TEST_FLAG,
             input_file = INPUT_FILE_FLAG,
         )
         .as_str();
@@ -552,11 +775,15 @@ fuzzcheck {minify} --{input_file} "artifacts/crash.json"
         };
         FuzzerBuilder5 {
             test_function: self.test_function,
+            reset_hook: self.reset_hook,
+            location: self.location,
             mutator: self.mutator,
             serializer: self.serializer,
             sensor: self.sensor,
             pool: self.pool,
             arguments,
+            seed_inputs: vec![],
+            to_rust_expr: None,
             _phantom: PhantomData,
         }
     }
@@ -627,25 +854,91 @@ where
         x.arguments.stop_after_first_failure = stop_after_first_test_failure;
         x
     }
+    /**
+        Add test cases that are run at the start of the fuzz test, in addition to the ones
+        read from the input corpus.
+
+        This is useful to seed the fuzzer with interesting values without having to save them
+        in the input corpus folder first, for example in a fresh checkout or on CI.
+        ```no_run
+        # fn parse(s: &str) -> bool { true }
+        let _ = fuzzcheck::fuzz_test(parse)
+            .default_options()
+            .seed_inputs(["hello world".to_owned(), "[1, 2, 3]".to_owned()])
+            .launch();
+        ```
+        ## Panics
+        The fuzzer panics when it starts if one of the seed inputs is rejected by the mutator,
+        i.e. if [`mutator.validate_value(..)`](Mutator::validate_value) returns `None`.
+    */
+    #[must_use]
+    #[no_coverage]
+    pub fn seed_inputs(self, inputs: impl IntoIterator<Item = V>) -> Self {
+        let mut x = self;
+        x.seed_inputs.extend(inputs);
+        x
+    }
+    /**
+        Use [`ToRustExpr`] to write a regression test when the fuzz test is launched with
+        `cargo fuzzcheck <target> --command reproduce --input-file <file> --emit-test`.
+
+        The regression test constructs the test case saved in the input file and calls the tested
+        function on it with [`reproduce`]. It is appended to the file `fuzzcheck_regressions.rs`,
+        located in the same folder as the source file of the fuzz test.
+        ```no_run
+        # #![feature(no_coverage)]
+        use fuzzcheck::ToRustExpr;
+        # use fuzzcheck::DefaultMutator;
+        # use serde::{Serialize, Deserialize};
+
+        #[derive(Clone, DefaultMutator, ToRustExpr, Serialize, Deserialize)]
+        struct Command { id: u8, payload: Vec<u8> }
+
+        fn run(command: &Command) { /* .. */ }
+
+        let _ = fuzzcheck::fuzz_test(run)
+            .default_options()
+            .with_to_rust_expr()
+            .launch();
+        ```
+    */
+    #[must_use]
+    #[no_coverage]
+    pub fn with_to_rust_expr(self) -> Self
+    where
+        V: ToRustExpr,
+    {
+        let mut x = self;
+        x.to_rust_expr = Some(V::to_rust_expr);
+        x
+    }
     /// Launch the fuzz test!
     #[no_coverage]
     pub fn launch(self) -> FuzzingResult<V> {
         let FuzzerBuilder5 {
             test_function,
+            reset_hook,
             mutator,
             serializer,
             pool,
             sensor,
+            location,
             arguments,
+            seed_inputs,
+            to_rust_expr,
             _phantom,
         } = self;
 
         crate::fuzzer::launch(
             Box::new(test_function),
+            reset_hook,
             mutator,
             serializer,
             Box::new((sensor, pool)),
             arguments,
+            seed_inputs,
+            location,
+            to_rust_expr,
         )
     }
 }
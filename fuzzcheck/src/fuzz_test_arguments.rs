@@ -0,0 +1,96 @@
+//! Support for fuzz test functions taking more than one argument.
+//!
+//! A function of type `Fn(&A, &B) -> R` is tested with values of type
+//! [`FuzzTestArguments2<A::Owned, B::Owned>`](FuzzTestArguments2), a record containing each argument
+//! in a named field. Its default mutator is derived from the tuple mutators, and it is serialized
+//! by the [`SerdeSerializer`](crate::SerdeSerializer) as `{ "arg0": .., "arg1": .. }`.
+extern crate self as fuzzcheck;
+
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+use fuzzcheck_mutators_derive::DefaultMutator;
+#[cfg(feature = "serde_json_serializer")]
+use serde::{Deserialize, Serialize};
+
+use crate::builder::{FuzzTestFunction, ReturnBool, ReturnResult, ReturnVoid};
+use crate::ToRustExpr;
+
+/// Marker type for a function taking multiple arguments, such as `Fn(&A, &B) -> bool`
+///
+/// `ReturnKind` is [`ReturnBool`], [`ReturnVoid`], or [`ReturnResult`], and `Signature` is
+/// the type of a function pointer with the same arguments, such as `fn(&A, &B)`.
+pub struct MultipleArguments<ReturnKind, Signature: ?Sized> {
+    _phantom: PhantomData<(ReturnKind, Signature)>,
+}
+
+macro_rules! fuzz_test_arguments {
+    ($name:ident, $nbr_args:literal, $($T:ident $arg:ident),+) => {
+        #[doc = concat!("The arguments given to a fuzz test function taking ", $nbr_args, " arguments.")]
+        ///
+        /// Read the [`fuzz_test`](crate::fuzz_test) documentation for more information.
+        #[cfg_attr(feature = "serde_json_serializer", derive(Serialize, Deserialize))]
+        #[derive(Clone, Debug, PartialEq, DefaultMutator)]
+        pub struct $name<$($T),+> {
+            $(pub $arg: $T),+
+        }
+
+        // not derived, because the type is only accessible through its re-export in the builder module
+        impl<$($T),+> ToRustExpr for $name<$($T),+>
+        where
+            $($T: ToRustExpr),+
+        {
+            #[no_coverage]
+            fn to_rust_expr(&self) -> String {
+                let fields = [$(format!(concat!(stringify!($arg), ": {}"), self.$arg.to_rust_expr())),+];
+                format!(concat!("fuzzcheck::builder::", stringify!($name), " {{ {} }}"), fields.join(", "))
+            }
+        }
+
+        impl<$($T),+, F> FuzzTestFunction<$name<$(<$T as ToOwned>::Owned),+>, $name<$(<$T as ToOwned>::Owned),+>, MultipleArguments<ReturnBool, fn($(&$T),+)>> for F
+        where
+            $($T: ?Sized + ToOwned),+,
+            F: Fn($(&$T),+) -> bool,
+        {
+            type NormalizedFunction = impl Fn(&$name<$(<$T as ToOwned>::Owned),+>) -> bool;
+            #[no_coverage]
+            fn test_function(self) -> Self::NormalizedFunction {
+                #[no_coverage]
+                move |x| (self)($(x.$arg.borrow()),+)
+            }
+        }
+        impl<$($T),+, F> FuzzTestFunction<$name<$(<$T as ToOwned>::Owned),+>, $name<$(<$T as ToOwned>::Owned),+>, MultipleArguments<ReturnVoid, fn($(&$T),+)>> for F
+        where
+            $($T: ?Sized + ToOwned),+,
+            F: Fn($(&$T),+),
+        {
+            type NormalizedFunction = impl Fn(&$name<$(<$T as ToOwned>::Owned),+>) -> bool;
+            #[no_coverage]
+            fn test_function(self) -> Self::NormalizedFunction {
+                #[no_coverage]
+                move |x| {
+                    (self)($(x.$arg.borrow()),+);
+                    true
+                }
+            }
+        }
+        impl<$($T),+, F, S, E> FuzzTestFunction<$name<$(<$T as ToOwned>::Owned),+>, $name<$(<$T as ToOwned>::Owned),+>, MultipleArguments<ReturnResult, fn($(&$T),+)>> for F
+        where
+            $($T: ?Sized + ToOwned),+,
+            F: Fn($(&$T),+) -> Result<S, E>,
+        {
+            type NormalizedFunction = impl Fn(&$name<$(<$T as ToOwned>::Owned),+>) -> bool;
+            #[no_coverage]
+            fn test_function(self) -> Self::NormalizedFunction {
+                #[no_coverage]
+                move |x| (self)($(x.$arg.borrow()),+).is_ok()
+            }
+        }
+    };
+}
+
+fuzz_test_arguments!(FuzzTestArguments2, 2, T0 arg0, T1 arg1);
+fuzz_test_arguments!(FuzzTestArguments3, 3, T0 arg0, T1 arg1, T2 arg2);
+fuzz_test_arguments!(FuzzTestArguments4, 4, T0 arg0, T1 arg1, T2 arg2, T3 arg3);
+fuzz_test_arguments!(FuzzTestArguments5, 5, T0 arg0, T1 arg1, T2 arg2, T3 arg3, T4 arg4);
+fuzz_test_arguments!(FuzzTestArguments6, 6, T0 arg0, T1 arg1, T2 arg2, T3 arg3, T4 arg4, T5 arg5);
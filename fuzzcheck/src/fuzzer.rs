@@ -2,16 +2,19 @@ use std::any::{Any, TypeId};
 use std::borrow::Borrow;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::hash::{Hash, Hasher};
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::process::exit;
 use std::result::Result;
 
-use fuzzcheck_common::arg::{Arguments, FuzzerCommand};
+use fuzzcheck_common::arg::{Arguments, FuzzerCommand, MAX_INPUT_CPLX_FLAG, STRICT_CORPUS_FLAG};
 use fuzzcheck_common::{FuzzerEvent, FuzzerStats};
 use libc::{SIGABRT, SIGALRM, SIGBUS, SIGFPE, SIGINT, SIGSEGV, SIGTERM, SIGTRAP};
 
 use crate::data_structures::RcSlab;
+use crate::regression_test::{regression_test_source, write_regression_test, FuzzTestLocation};
+use crate::sanitizer_report::SanitizerReports;
 use crate::sensors_and_pools::{
     AndSensorAndPool, NoopSensor, TestFailure, TestFailurePool, TestFailureSensor, UnitPool, TEST_FAILURE,
 };
@@ -27,6 +30,7 @@ static SERIALIZER_FROM_DATA_ERROR: &str = "the file could not be decoded into a
 static READ_INPUT_FILE_ERROR: &str = "the input file could not be read";
 static SAVE_ARTIFACTS_ERROR: &str = "the artifact could not be saved";
 static UPDATE_CORPUS_ERROR: &str = "the corpus could not be updated on the file system";
+static QUARANTINE_INPUT_ERROR: &str = "an invalid file of the input corpus could not be moved to the quarantine folder";
 
 static mut DID_FIND_ANY_TEST_FAILURE: bool = false;
 
@@ -81,6 +85,40 @@ where
     subvalues: CrossoverSubValueProvider<T, M>,
 }
 
+/// The reason why the content of a file cannot be used as a test case
+#[derive(Clone, Copy)]
+enum InvalidInput {
+    /// The serializer could not decode the file
+    Unparsable,
+    /// The decoded value was rejected by [`Mutator::validate_value`]
+    RejectedByMutator,
+}
+impl Display for InvalidInput {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidInput::Unparsable => write!(f, "it could not be deserialized"),
+            InvalidInput::RejectedByMutator => write!(f, "it was rejected by the mutator"),
+        }
+    }
+}
+
+/// Decode the content of a file into a value and its cache
+#[no_coverage]
+fn decode_input<T, M>(
+    mutator: &M,
+    serializer: &dyn Serializer<Value = T>,
+    data: &[u8],
+) -> Result<(T, M::Cache), InvalidInput>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    let value = serializer.from_data(data).ok_or(InvalidInput::Unparsable)?;
+    let cache = mutator.validate_value(&value).ok_or(InvalidInput::RejectedByMutator)?;
+    Ok((value, cache))
+}
+
 /**
  * A struct that stores the value, cache, and mutation step of an input.
  * It is used for convenience.
@@ -163,6 +201,10 @@ struct FuzzerState<T: Clone + 'static, M: Mutator<T>> {
     /// The world handles effects
     world: World,
     rng: fastrand::Rng,
+    /// Values given by the user to test at the start of the fuzz test
+    seed_inputs: Vec<T>,
+    /// The reports written by the sanitizer runtime, if the fuzz test was compiled with a sanitizer
+    sanitizer_reports: Option<SanitizerReports>,
 
     signal_handler_alt_stack: Option<(*mut u8, std::alloc::Layout)>,
 }
@@ -237,11 +279,26 @@ where
 
         match signal {
             SIGABRT | SIGBUS | SIGSEGV | SIGFPE | SIGALRM | SIGTRAP => {
+                let failure = self.sanitizer_reports.as_mut().and_then(
+                    #[no_coverage]
+                    |reports| {
+                        let (text, report) = reports.read_new_report()?;
+                        // the report was written to the log file instead of stderr, so we print it here
+                        eprintln!("{}", text);
+                        Some(report?.into_test_failure())
+                    },
+                );
                 if let Some(input) = Self::get_input(&self.input_idx, &self.pool_storage) {
                     let input = input.new_source(&self.mutator, Generation(0));
                     let cplx = input.complexity(&self.mutator);
                     let content = self.serializer.to_data(&input.value);
-                    let _ = self.world.save_artifact(content, cplx, self.serializer.extension());
+                    let _ = if let Some(failure) = failure {
+                        println!("{}", failure.display);
+                        self.world
+                            .save_artifact_for_failure(content, cplx, self.serializer.extension(), failure.id)
+                    } else {
+                        self.world.save_artifact(content, cplx, self.serializer.extension())
+                    };
                     self.write_stats().expect(WRITE_STATS_ERROR);
                     exit(TerminationStatus::Crash as i32);
                 } else {
@@ -291,6 +348,8 @@ where
 {
     state: FuzzerState<T, M>,
     test: Box<dyn Fn(&T) -> bool>,
+    /// Called before each test case, outside of the sensor’s recording
+    reset_hook: Option<Box<dyn Fn()>>,
 }
 
 impl<T, M> Fuzzer<T, M>
@@ -302,11 +361,13 @@ where
     #[no_coverage]
     fn new(
         test: Box<dyn Fn(&T) -> bool>,
+        reset_hook: Option<Box<dyn Fn()>>,
         mutator: M,
         serializer: Box<dyn Serializer<Value = T>>,
         sensor_and_pool: Box<dyn SensorAndPool>,
         settings: Arguments,
         world: World,
+        seed_inputs: Vec<T>,
     ) -> Self {
         let arbitrary_step = mutator.default_arbitrary_step();
         Fuzzer {
@@ -317,13 +378,19 @@ where
                 arbitrary_step,
                 input_idx: FuzzerInputIndex::None,
                 fuzzer_stats: FuzzerStats::default(),
-                settings,
                 serializer,
                 world,
                 rng: fastrand::Rng::new(),
+                seed_inputs,
+                sanitizer_reports: settings.sanitizer.map(
+                    #[no_coverage]
+                    |sanitizer| SanitizerReports::new(sanitizer, settings.leak_check_interval),
+                ),
+                settings,
                 signal_handler_alt_stack: None,
             },
             test,
+            reset_hook,
         }
     }
 
@@ -340,15 +407,21 @@ where
                     serializer,
                     world,
                     settings,
+                    sanitizer_reports,
                     ..
                 },
             test,
-            ..
+            reset_hook,
         } = self;
 
         // we have verified in the caller function that there is an input
         let input = FuzzerState::<T, M>::get_input(input_idx, pool_storage).unwrap();
 
+        // the reset hook runs before the sensor starts recording, so its code coverage is not observed
+        if let Some(reset_hook) = reset_hook {
+            reset_hook();
+        }
+
         std::panic::set_hook(Box::new(
             #[no_coverage]
             move |panic_info| {
@@ -358,6 +431,7 @@ where
                     TEST_FAILURE = Some(TestFailure {
                         display: format!("{}", panic_info),
                         id: hasher.finish(),
+                        sanitizer_report: None,
                     });
                 }
             },
@@ -389,15 +463,29 @@ where
                 TEST_FAILURE = Some(TestFailure {
                     display: "test function returned false".to_string(),
                     id: 0,
+                    sanitizer_report: None,
                 });
                 true
             },
+            Ok(true) => {
+                let test_failure = sanitizer_reports.as_mut().and_then(
+                    #[no_coverage]
+                    |reports| reports.test_failure(fuzzer_stats.total_number_of_runs),
+                );
+                if let Some(test_failure) = test_failure {
+                    unsafe {
+                        TEST_FAILURE = Some(test_failure);
+                    }
+                    true
+                } else {
+                    false
+                }
+            }
             Err(_) => {
                 // the panic handler already changed the value of TEST_FAILURE
                 // so we don't need to do anything
                 true
             }
-            Ok(true) => false,
         };
         if test_failure {
             unsafe {
@@ -560,24 +648,75 @@ where
         }
     }
 
+    /// Test the seed inputs, the input corpus, and a few arbitrary inputs
+    ///
+    /// Returns the number of files of the input corpus that could not be deserialized and that were
+    /// rejected by the mutator, respectively.
     #[no_coverage]
-    fn process_initial_inputs(&mut self) -> Result<(), ReasonForStopping<T>> {
-        let mut inputs: Vec<FuzzedInput<T, M>> = self
-            .state
-            .world
-            .read_input_corpus()
-            .expect(READ_INPUT_FILE_ERROR)
-            .into_iter()
-            .filter_map(
-                #[no_coverage]
-                |value| {
-                    let value = self.state.serializer.from_data(&value)?;
-                    let cache = self.state.mutator.validate_value(&value)?;
+    fn process_initial_inputs(&mut self) -> Result<(usize, usize), ReasonForStopping<T>> {
+        let mut inputs: Vec<FuzzedInput<T, M>> = vec![];
+        for (i, value) in std::mem::take(&mut self.state.seed_inputs).into_iter().enumerate() {
+            if let Some(cache) = self.state.mutator.validate_value(&value) {
+                let cplx = self.state.mutator.complexity(&value, &cache);
+                if cplx > self.state.settings.max_input_cplx {
+                    let serialized = self.state.serializer.to_data(&value);
+                    panic!(
+                        "The seed input #{} is more complex than the maximum input complexity ({:.2} > {:.2}), which can be increased with --{}: {}",
+                        i,
+                        cplx,
+                        self.state.settings.max_input_cplx,
+                        MAX_INPUT_CPLX_FLAG,
+                        String::from_utf8_lossy(&serialized)
+                    );
+                }
+                let mutation_step = self.state.mutator.default_mutation_step(&value, &cache);
+                inputs.push(FuzzedInput::new(value, cache, mutation_step, Generation(0)));
+            } else {
+                let serialized = self.state.serializer.to_data(&value);
+                panic!(
+                    "The seed input #{} is rejected by the mutator: {}",
+                    i,
+                    String::from_utf8_lossy(&serialized)
+                );
+            }
+        }
+        let mut rejected_files = vec![];
+        for (path, data) in self.state.world.read_input_corpus().expect(READ_INPUT_FILE_ERROR) {
+            match decode_input(&self.state.mutator, self.state.serializer.as_ref(), &data) {
+                Ok((value, cache)) => {
                     let mutation_step = self.state.mutator.default_mutation_step(&value, &cache);
-                    Some(FuzzedInput::new(value, cache, mutation_step, Generation(0)))
-                },
+                    inputs.push(FuzzedInput::new(value, cache, mutation_step, Generation(0)));
+                }
+                Err(reason) => rejected_files.push((path, reason)),
+            }
+        }
+        let nbr_unparsable = rejected_files
+            .iter()
+            .filter(
+                #[no_coverage]
+                |(_, reason)| matches!(reason, InvalidInput::Unparsable),
             )
-            .collect();
+            .count();
+        let nbr_invalid = rejected_files.len() - nbr_unparsable;
+        if let Some(folder) = &self.state.settings.quarantine_invalid {
+            for (path, _) in rejected_files.iter() {
+                self.state
+                    .world
+                    .quarantine_input(path, folder)
+                    .expect(QUARANTINE_INPUT_ERROR);
+            }
+        }
+        if self.state.settings.strict_corpus && !rejected_files.is_empty() {
+            let mut message = format!("{} files of the input corpus are invalid:\n", rejected_files.len());
+            for (path, reason) in rejected_files.iter() {
+                message.push_str(&format!("    {}: {}\n", path.display(), reason));
+            }
+            message.push_str(&format!(
+                "Remove them, or run the fuzzer without --{} to ignore them.",
+                STRICT_CORPUS_FLAG
+            ));
+            panic!("{}", message);
+        }
 
         for _ in 0..100 {
             if let Some((input, _)) = self.state.arbitrary_input() {
@@ -599,7 +738,7 @@ where
             self.test_and_process_input(cplx)?;
         }
 
-        Ok(())
+        Ok((nbr_unparsable, nbr_invalid))
     }
 
     #[no_coverage]
@@ -609,9 +748,12 @@ where
             Some((&self.state.fuzzer_stats, self.state.sensor_and_pool.stats().as_ref())),
         );
         if !minify {
-            self.process_initial_inputs()?;
+            let (nbr_unparsable, nbr_invalid) = self.process_initial_inputs()?;
             self.state.world.report_event(
-                FuzzerEvent::DidReadCorpus,
+                FuzzerEvent::DidReadCorpus {
+                    nbr_unparsable,
+                    nbr_invalid,
+                },
                 Some((&self.state.fuzzer_stats, self.state.sensor_and_pool.stats().as_ref())),
             );
         }
@@ -649,10 +791,14 @@ pub enum TerminationStatus {
 #[no_coverage]
 pub fn launch<T, M>(
     test: Box<dyn Fn(&T) -> bool>,
+    reset_hook: Option<Box<dyn Fn()>>,
     mutator: M,
     serializer: Box<dyn Serializer<Value = T>>,
     sensor_and_pool: Box<dyn SensorAndPool>,
     mut args: Arguments,
+    seed_inputs: Vec<T>,
+    location: FuzzTestLocation,
+    to_rust_expr: Option<fn(&T) -> String>,
 ) -> FuzzingResult<T>
 where
     T: Clone + 'static,
@@ -673,11 +819,13 @@ where
                 );
                 let mut fuzzer = Fuzzer::new(
                     test,
+                    reset_hook,
                     mutator,
                     serializer,
                     Box::new(sensor_and_pool),
                     args.clone(),
                     World::new(args.clone()).expect(WORLD_NEW_ERROR),
+                    seed_inputs,
                 );
 
                 let mut stats_headers = vec![CSVField::String("time".to_string())];
@@ -697,11 +845,13 @@ where
             } else {
                 let mut fuzzer = Fuzzer::new(
                     test,
+                    reset_hook,
                     mutator,
                     serializer,
                     sensor_and_pool,
                     args.clone(),
                     World::new(args.clone()).expect(WORLD_NEW_ERROR),
+                    seed_inputs,
                 );
                 unsafe { fuzzer.state.set_up_signal_handler() };
 
@@ -722,7 +872,16 @@ where
         FuzzerCommand::MinifyInput { input_file } => {
             let world = World::new(args.clone()).expect(WORLD_NEW_ERROR);
             let value = world.read_input_file(input_file).expect(READ_INPUT_FILE_ERROR);
-            let value = serializer.from_data(&value).expect(SERIALIZER_FROM_DATA_ERROR);
+            let value = serializer.from_data(&value).unwrap_or_else(
+                #[no_coverage]
+                || {
+                    panic!(
+                        "The input file {} is invalid: {}.",
+                        input_file.display(),
+                        InvalidInput::Unparsable
+                    )
+                },
+            );
             if let Some(cache) = mutator.validate_value(&value) {
                 let mutation_step = mutator.default_mutation_step(&value, &cache);
                 args.max_input_cplx = mutator.complexity(&value, &cache) - 0.01;
@@ -737,11 +896,13 @@ where
                     AndSensorAndPool::new(sensor_and_pool, Box::new((noop_sensor, unit_pool)), 1.0, 100.0);
                 let mut fuzzer = Fuzzer::new(
                     test,
+                    reset_hook,
                     mutator,
                     serializer,
                     Box::new(sensor_and_pool),
                     args.clone(),
                     world,
+                    vec![],
                 );
 
                 let mut subvalues: HashMap<TypeId, Vec<(*const dyn Any, f64)>> = HashMap::default();
@@ -781,16 +942,27 @@ where
 
                 fuzzer.main_loop(true).unwrap_err()
             } else {
-                // TODO: send a better error message saying some inputs in the corpus cannot be read
-                // TODO: there should be an option to ignore invalid values
-                panic!("A value in the input corpus is invalid.");
+                panic!(
+                    "The input file {} is invalid: {}.",
+                    input_file.display(),
+                    InvalidInput::RejectedByMutator
+                );
             }
         }
         FuzzerCommand::Read { input_file } => {
             // no signal handlers are installed, but that should be ok as the exit code won't be 0
             let mut world = World::new(args.clone()).expect(WORLD_NEW_ERROR);
             let value = world.read_input_file(input_file).expect(READ_INPUT_FILE_ERROR);
-            let value = serializer.from_data(&value).expect(SERIALIZER_FROM_DATA_ERROR);
+            let value = serializer.from_data(&value).unwrap_or_else(
+                #[no_coverage]
+                || {
+                    panic!(
+                        "The input file {} is invalid: {}.",
+                        input_file.display(),
+                        InvalidInput::Unparsable
+                    )
+                },
+            );
             if let Some(cache) = mutator.validate_value(&value) {
                 let mutation_step = mutator.default_mutation_step(&value, &cache);
                 let input = FuzzedInput::new(value, cache, mutation_step, Generation(0));
@@ -832,8 +1004,70 @@ where
                     exit(TerminationStatus::Success as i32);
                 }
             } else {
-                // TODO: send a better error message saying some inputs in the corpus cannot be read
-                panic!("A value in the input corpus is invalid.");
+                panic!(
+                    "The input file {} is invalid: {}.",
+                    input_file.display(),
+                    InvalidInput::RejectedByMutator
+                );
+            }
+        }
+        FuzzerCommand::Reproduce { input_file, emit_test } => {
+            let world = World::new(args.clone()).expect(WORLD_NEW_ERROR);
+            let content = world.read_input_file(input_file).expect(READ_INPUT_FILE_ERROR);
+            let value = serializer.from_data(&content).expect(SERIALIZER_FROM_DATA_ERROR);
+
+            if let Some(reset_hook) = &reset_hook {
+                reset_hook();
+            }
+            let result = catch_unwind(AssertUnwindSafe(
+                #[no_coverage]
+                || (test)(&value),
+            ));
+            let failed = !matches!(result, Ok(true));
+            if failed {
+                world.report_event(FuzzerEvent::TestFailure, None);
+            } else {
+                println!("The test case does not cause a test failure.");
+            }
+
+            if *emit_test {
+                let to_rust_expr = to_rust_expr.unwrap_or_else(
+                    #[no_coverage]
+                    || {
+                        panic!(
+                            "The regression test cannot be written because the fuzz test was not built with `.with_to_rust_expr()`."
+                        )
+                    },
+                );
+                if let Some(function) = location.function {
+                    let mut hasher = DefaultHasher::new();
+                    content.hash(&mut hasher);
+                    let name = format!("fuzzcheck_regression_{:x}", hasher.finish());
+                    let source = regression_test_source(function, &name, input_file, &to_rust_expr(&value));
+                    match write_regression_test(&location, &name, &source) {
+                        Ok((path, true)) => println!(
+                            "The regression test {} was written to {}. Add `#[cfg(test)] mod fuzzcheck_regressions;` to the module of the fuzz test to run it.",
+                            name,
+                            path.display()
+                        ),
+                        Ok((path, false)) => println!(
+                            "The regression test {} was not written because {} already contains it.",
+                            name,
+                            path.display()
+                        ),
+                        Err(e) => println!("The regression test could not be written: {}\n{}", e, source),
+                    }
+                } else {
+                    println!(
+                        "The regression test was not written because the tested function cannot be named, e.g. because it is a closure. Use a named function in the fuzz test to write regression tests. The test case is:\n{}",
+                        to_rust_expr(&value)
+                    );
+                }
+            }
+            if failed {
+                exit(TerminationStatus::TestFailure as i32);
+            } else {
+                exit(TerminationStatus::Success as i32);
             }
         }
     };
@@ -28,6 +28,9 @@
 #![feature(stmt_expr_attributes)]
 // very very nice to use, but I guess not essential?
 #![feature(type_alias_impl_trait)]
+// used to check for memory leaks only when compiled with a sanitizer,
+// could be replaced by a --cfg flag passed by cargo-fuzzcheck
+#![feature(cfg_sanitize)]
 // essential for tuple mutators, but there may be a (more complicated) way
 // to do without them
 #![feature(generic_associated_types)]
@@ -57,18 +60,26 @@ pub mod builder;
 mod code_coverage_sensor;
 mod data_structures;
 mod fenwick_tree;
+mod fuzz_test_arguments;
 mod fuzzer;
 pub mod mutators;
+mod regression_test;
+mod sanitizer_report;
 pub mod sensors_and_pools;
 pub mod serializers;
 mod signals_handler;
 mod split_string;
 pub mod subvalue_provider;
+mod to_rust_expr;
 mod traits;
 mod world;
 
 #[doc(inline)]
 pub use builder::fuzz_test;
+#[doc(inline)]
+pub use builder::fuzz_test_with_state;
+#[doc(inline)]
+pub use builder::reproduce;
 pub use fuzzcheck_common::arg::Arguments;
 /**
     Make a mutator for a custom type, optionally making it the type’s default mutator.
@@ -181,6 +192,8 @@ pub use fuzzcheck_common::arg::Arguments;
             }
     }
     ```
+    The fields can also be given the shorthand `#[fuzz(..)]` attributes described in the documentation
+    of [`DefaultMutator`](crate::DefaultMutator).
 */
 pub use fuzzcheck_mutators_derive::make_mutator;
 /// Implement a mutator for the type and make it the type’s `DefaultMutator`.
@@ -214,6 +227,80 @@ pub use fuzzcheck_mutators_derive::make_mutator;
 /// ```
 /// Similarly to [`make_mutator!`](crate::make_mutator), you can use the attributes `#[field_mutator]` and `#[ignore_variant]`
 /// to customise the generated mutator.
+///
+/// The `#[fuzz]` attribute is a shorthand for the most common field mutators:
+/// * `#[fuzz(range = 0 ..= 10)]` for integers, floats, and `char`s within a range
+/// * `#[fuzz(len = 1 .. 8)]` for a `Vec`, `VecDeque`, or `String` whose length is within a range
+/// * `#[fuzz(regex = "[a-z]+")]` for a `String` matching a regular expression (on crate feature `regex_grammar`)
+/// * `#[fuzz(value = expr)]` for a field whose value is always `expr`
+/// ```
+/// # #![feature(no_coverage)]
+/// use fuzzcheck::DefaultMutator;
+///
+/// #[derive(Clone, DefaultMutator)]
+/// struct Request {
+///     #[fuzz(range = 1 ..= 3)]
+///     version: u8,
+///     #[fuzz(len = ..16)]
+///     body: Vec<u8>,
+///     #[fuzz(value = true)]
+///     keep_alive: bool,
+/// }
+/// let mutator = Request::default_mutator();
+/// ```
+#[cfg_attr(
+    feature = "regex_grammar",
+    doc = r###"
+```
+# #![feature(no_coverage)]
+use fuzzcheck::DefaultMutator;
+
+#[derive(Clone, DefaultMutator)]
+struct Route {
+    #[fuzz(regex = "/[a-z]{1,8}(/[a-z]{1,8})*")]
+    path: String,
+}
+let mutator = Route::default_mutator();
+```
+"###
+)]
+/// On the variants of an enum, `#[fuzz(weight = N)]` makes the mutator generate the variant with a probability
+/// proportional to `N`, both for new random values and when a random mutation switches the variant of an existing
+/// value. The variants without a weight have a weight of 1.
+/// ```
+/// # #![feature(no_coverage)]
+/// use fuzzcheck::DefaultMutator;
+///
+/// #[derive(Clone, DefaultMutator)]
+/// enum Command {
+///     Read(u8),
+///     Write(u8, Vec<u8>),
+///     // generate `Shutdown` five times more often than `Read` or `Write`
+///     #[fuzz(weight = 5)]
+///     Shutdown,
+/// }
+/// ```
+/// On the type itself, `#[fuzz(invariant = repair_fn)]` makes the mutator call `repair_fn` on each value it generates
+/// or mutates, such that the value satisfies an invariant. This is usually much more efficient than discarding the values
+/// that do not satisfy it with [`MutatorExt::filter`](crate::MutatorExt::filter). The type must implement `PartialEq`.
+/// See [`RepairMutator`](crate::mutators::repair::RepairMutator) for the requirements on the repair function.
+/// ```
+/// # #![feature(no_coverage)]
+/// use fuzzcheck::DefaultMutator;
+///
+/// fn repair_span(span: &mut Span) {
+///     if span.start > span.end {
+///         std::mem::swap(&mut span.start, &mut span.end);
+///     }
+/// }
+///
+/// #[derive(Clone, PartialEq, DefaultMutator)]
+/// #[fuzz(invariant = repair_span)]
+/// struct Span {
+///     start: usize,
+///     end: usize,
+/// }
+/// ```
 pub use fuzzcheck_mutators_derive::DefaultMutator;
 #[doc(inline)]
 pub use fuzzer::FuzzingResult;
@@ -230,6 +317,9 @@ pub(crate) use mutators::CROSSOVER_RATE;
 pub use sensors_and_pools::PoolExt;
 #[doc(inline)]
 pub use sensors_and_pools::SensorExt;
+#[cfg(feature = "bincode_serializer")]
+#[doc(inline)]
+pub use serializers::BincodeSerializer;
 #[doc(inline)]
 pub use serializers::ByteSerializer;
 #[cfg(feature = "serde_json_serializer")]
@@ -242,6 +332,13 @@ pub(crate) use split_string::split_string_by_whitespace;
 pub use subvalue_provider::SubValueProvider;
 #[doc(inline)]
 pub use subvalue_provider::SubValueProviderId;
+/// Implement [`ToRustExpr`](trait@ToRustExpr) for a struct or enum whose fields implement it.
+///
+/// The generated expressions refer to the type through the path of the module where it is
+/// defined, e.g. `my_crate::ast::Expr::Lit(1u8)`.
+pub use fuzzcheck_mutators_derive::ToRustExpr;
+#[doc(inline)]
+pub use to_rust_expr::ToRustExpr;
 #[doc(inline)]
 pub use traits::CompatibleWithObservations;
 #[doc(inline)]
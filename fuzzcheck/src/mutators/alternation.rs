@@ -2,6 +2,7 @@ use std::any::Any;
 use std::cmp::Ordering;
 use std::marker::PhantomData;
 
+use super::vose_alias::VoseAlias;
 use crate::Mutator;
 
 /**
@@ -17,6 +18,10 @@ let m2 = U8WithinRangeMutator::new(78 ..= 200);
 let m = AlternationMutator::new(vec![m1, m2], 0.0);
 
 // m will produce values either in 3..=10 or in 78..=200
+
+// m will produce values in 3..=10 three times more often than values in 78..=200
+let m = AlternationMutator::new(vec![U8WithinRangeMutator::new(3 ..= 10), U8WithinRangeMutator::new(78 ..= 200)], 0.0)
+    .with_weights(vec![3.0, 1.0]);
 ```
 */
 pub struct AlternationMutator<T, M>
@@ -25,6 +30,7 @@ where
     M: Mutator<T>,
 {
     mutators: Vec<M>,
+    weights: Option<VoseAlias>,
     rng: fastrand::Rng,
     search_space_complexity: f64,
     added_complexity: f64,
@@ -87,6 +93,7 @@ where
 
         Self {
             mutators,
+            weights: None,
             search_space_complexity,
             rng: fastrand::Rng::default(),
             added_complexity,
@@ -95,6 +102,24 @@ where
             _phantom: PhantomData,
         }
     }
+
+    /// Choose the mutator used to generate a new random value with a probability proportional to its weight,
+    /// instead of uniformly.
+    ///
+    /// There must be one weight per mutator. The weights do not need to add up to 1. They are also used to
+    /// choose the mutator of the random values that replace the current one during a mutation. The ordered
+    /// generation of new values is not affected: it still goes through each mutator in turn.
+    #[no_coverage]
+    pub fn with_weights(mut self, weights: Vec<f64>) -> Self {
+        assert_eq!(weights.len(), self.mutators.len());
+        assert!(weights.iter().all(
+            #[no_coverage]
+            |w| w.is_finite() && *w >= 0.0
+        ));
+        assert!(weights.iter().sum::<f64>() > 0.0);
+        self.weights = Some(VoseAlias::new(weights));
+        self
+    }
 }
 
 #[doc(hidden)]
@@ -273,7 +298,11 @@ where
     #[doc(hidden)]
     #[no_coverage]
     fn random_arbitrary(&self, max_cplx: f64) -> (T, f64) {
-        let idx = self.rng.usize(..self.mutators.len());
+        let idx = if let Some(weights) = &self.weights {
+            weights.sample()
+        } else {
+            self.rng.usize(..self.mutators.len())
+        };
         let mutator = &self.mutators[idx];
 
         let (v, c) = mutator.random_arbitrary(max_cplx);
@@ -0,0 +1,286 @@
+/*!
+A benchmark measuring the throughput of a mutator and the diversity of the values it produces.
+
+The fuzzer calls the mutator once per iteration, so a slow mutator can become the bottleneck of a
+fuzz test whose test function is fast. [`MutatorBenchmark`] measures the time taken by each method
+of the [`Mutator`] trait, the complexities of the values they produce, and how many of these values
+are distinct.
+```
+use fuzzcheck::mutators::benchmark::MutatorBenchmark;
+use fuzzcheck::DefaultMutator;
+
+let mutator = <Vec<(u8, bool)>>::default_mutator();
+let report = MutatorBenchmark::new(256.0).with_iterations(100, 10).run(&mutator);
+println!("{report}");
+```
+The report is printed as a table with one row per method of the mutator, such that the results of
+different versions of a mutator can be compared. Each call is timed individually, so the fixed cost of
+reading the clock is estimated before the benchmark and subtracted from the measured durations. Its columns are the number of values produced, the
+average time in nanoseconds taken by one call to the method (including `unmutate` for the mutations),
+the percentage of distinct values, and the minimum, median, 90th percentile, and maximum complexities
+of the values.
+*/
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use crate::subvalue_provider::EmptySubValueProvider;
+use crate::Mutator;
+
+/// The measurements of one method of the [`Mutator`] trait during a [`MutatorBenchmark`]
+#[derive(Debug, Clone, Default)]
+pub struct OperationStats {
+    /// The number of values produced
+    pub nbr_values: usize,
+    /// The number of distinct values produced, according to their `Debug` representation
+    pub nbr_distinct_values: usize,
+    /// The total time spent in the method, and in `unmutate` for the mutations, excluding the cost of measuring it
+    pub duration: Duration,
+    /// The complexities of the values produced, sorted in increasing order
+    pub complexities: Vec<f64>,
+}
+
+impl OperationStats {
+    /// The average time taken by one call to the method, in nanoseconds
+    #[no_coverage]
+    pub fn nanos_per_operation(&self) -> f64 {
+        if self.nbr_values == 0 {
+            0.0
+        } else {
+            self.duration.as_nanos() as f64 / self.nbr_values as f64
+        }
+    }
+
+    /// The fraction of the values produced that are distinct, between 0 and 1
+    #[no_coverage]
+    pub fn diversity(&self) -> f64 {
+        if self.nbr_values == 0 {
+            0.0
+        } else {
+            self.nbr_distinct_values as f64 / self.nbr_values as f64
+        }
+    }
+
+    /// The complexity below which a fraction `q` of the values produced lie, e.g. `q = 0.5` for the median
+    #[no_coverage]
+    pub fn complexity_quantile(&self, q: f64) -> f64 {
+        if self.complexities.is_empty() {
+            0.0
+        } else {
+            let idx = ((self.complexities.len() - 1) as f64 * q.clamp(0.0, 1.0)).round() as usize;
+            self.complexities[idx]
+        }
+    }
+}
+
+/// The result of a [`MutatorBenchmark`]
+#[derive(Debug, Clone, Default)]
+pub struct BenchmarkReport {
+    pub ordered_arbitrary: OperationStats,
+    pub random_arbitrary: OperationStats,
+    pub ordered_mutate: OperationStats,
+    pub random_mutate: OperationStats,
+}
+
+impl Display for BenchmarkReport {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<17} {:>9} {:>8} {:>9} {:>10} {:>12} {:>9} {:>9}",
+            "operation", "values", "ns/op", "distinct", "min cplx", "median cplx", "p90 cplx", "max cplx"
+        )?;
+        for (name, stats) in [
+            ("ordered_arbitrary", &self.ordered_arbitrary),
+            ("random_arbitrary", &self.random_arbitrary),
+            ("ordered_mutate", &self.ordered_mutate),
+            ("random_mutate", &self.random_mutate),
+        ] {
+            writeln!(
+                f,
+                "{:<17} {:>9} {:>8.1} {:>8.2}% {:>10.2} {:>12.2} {:>9.2} {:>9.2}",
+                name,
+                stats.nbr_values,
+                stats.nanos_per_operation(),
+                stats.diversity() * 100.0,
+                stats.complexity_quantile(0.0),
+                stats.complexity_quantile(0.5),
+                stats.complexity_quantile(0.9),
+                stats.complexity_quantile(1.0),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Measures the throughput of a mutator and the diversity of the values it produces.
+///
+/// The benchmark generates values with `ordered_arbitrary` and `random_arbitrary`. Then, it mutates each
+/// value generated by `random_arbitrary` with `ordered_mutate` and `random_mutate`, calling `unmutate`
+/// after each mutation, as the fuzzer does. Only the time spent inside the mutator is measured.
+#[derive(Debug, Clone)]
+pub struct MutatorBenchmark {
+    max_cplx: f64,
+    nbr_arbitraries: usize,
+    nbr_mutations: usize,
+}
+
+impl MutatorBenchmark {
+    /// Create a benchmark generating values up to the given maximum complexity.
+    ///
+    /// By default, the benchmark generates 1000 values with each of `ordered_arbitrary` and `random_arbitrary`,
+    /// and mutates each value generated by `random_arbitrary` 100 times with each of `ordered_mutate` and
+    /// `random_mutate`.
+    #[no_coverage]
+    pub fn new(max_cplx: f64) -> Self {
+        Self {
+            max_cplx,
+            nbr_arbitraries: 1000,
+            nbr_mutations: 100,
+        }
+    }
+
+    /// Set the number of values generated by each of `ordered_arbitrary` and `random_arbitrary`,
+    /// and the number of times each value generated by `random_arbitrary` is mutated
+    #[no_coverage]
+    pub fn with_iterations(mut self, nbr_arbitraries: usize, nbr_mutations: usize) -> Self {
+        self.nbr_arbitraries = nbr_arbitraries;
+        self.nbr_mutations = nbr_mutations;
+        self
+    }
+
+    /// Run the benchmark on the mutator
+    #[no_coverage]
+    pub fn run<T, M>(&self, mutator: &M) -> BenchmarkReport
+    where
+        T: Clone + Debug + 'static,
+        M: Mutator<T>,
+    {
+        let mut ordered_arbitrary = Recorder::default();
+        let mut step = mutator.default_arbitrary_step();
+        for _ in 0..self.nbr_arbitraries {
+            let x = ordered_arbitrary.time(
+                #[no_coverage]
+                || mutator.ordered_arbitrary(&mut step, self.max_cplx),
+            );
+            if let Some((value, cplx)) = x {
+                ordered_arbitrary.record(&value, cplx);
+            } else {
+                break;
+            }
+        }
+
+        let mut random_arbitrary = Recorder::default();
+        let mut ordered_mutate = Recorder::default();
+        let mut random_mutate = Recorder::default();
+        for _ in 0..self.nbr_arbitraries {
+            let (mut value, cplx) = random_arbitrary.time(
+                #[no_coverage]
+                || mutator.random_arbitrary(self.max_cplx),
+            );
+            random_arbitrary.record(&value, cplx);
+
+            let mut cache = if let Some(cache) = mutator.validate_value(&value) {
+                cache
+            } else {
+                continue;
+            };
+            let mut step = mutator.default_mutation_step(&value, &cache);
+            for _ in 0..self.nbr_mutations {
+                let x = ordered_mutate.time(
+                    #[no_coverage]
+                    || mutator.ordered_mutate(&mut value, &mut cache, &mut step, &EmptySubValueProvider, self.max_cplx),
+                );
+                if let Some((token, cplx)) = x {
+                    ordered_mutate.record(&value, cplx);
+                    ordered_mutate.time(
+                        #[no_coverage]
+                        || mutator.unmutate(&mut value, &mut cache, token),
+                    );
+                } else {
+                    break;
+                }
+            }
+            for _ in 0..self.nbr_mutations {
+                let (token, cplx) = random_mutate.time(
+                    #[no_coverage]
+                    || mutator.random_mutate(&mut value, &mut cache, self.max_cplx),
+                );
+                random_mutate.record(&value, cplx);
+                random_mutate.time(
+                    #[no_coverage]
+                    || mutator.unmutate(&mut value, &mut cache, token),
+                );
+            }
+        }
+
+        let overhead = clock_overhead();
+        BenchmarkReport {
+            ordered_arbitrary: ordered_arbitrary.finish(overhead),
+            random_arbitrary: random_arbitrary.finish(overhead),
+            ordered_mutate: ordered_mutate.finish(overhead),
+            random_mutate: random_mutate.finish(overhead),
+        }
+    }
+}
+
+/// The time measured by timing a call that does nothing, estimated from the median of many such calls
+#[no_coverage]
+fn clock_overhead() -> Duration {
+    let mut durations = (0..1001)
+        .map(
+            #[no_coverage]
+            |_| {
+                let start = Instant::now();
+                start.elapsed()
+            },
+        )
+        .collect::<Vec<_>>();
+    durations.sort();
+    durations[durations.len() / 2]
+}
+
+/// Collects the measurements of one method of the [`Mutator`] trait
+#[derive(Default)]
+struct Recorder {
+    duration: Duration,
+    nbr_timed_calls: u32,
+    complexities: Vec<f64>,
+    hashes: HashSet<u64>,
+}
+
+impl Recorder {
+    #[no_coverage]
+    fn time<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.duration += start.elapsed();
+        self.nbr_timed_calls += 1;
+        result
+    }
+
+    #[no_coverage]
+    fn record<T: Debug>(&mut self, value: &T, cplx: f64) {
+        let mut hasher = DefaultHasher::new();
+        format!("{value:?}").hash(&mut hasher);
+        self.hashes.insert(hasher.finish());
+        self.complexities.push(cplx);
+    }
+
+    #[no_coverage]
+    fn finish(mut self, clock_overhead: Duration) -> OperationStats {
+        self.complexities.sort_by(
+            #[no_coverage]
+            |x, y| x.total_cmp(y),
+        );
+        OperationStats {
+            nbr_values: self.complexities.len(),
+            nbr_distinct_values: self.hashes.len(),
+            duration: self.duration.saturating_sub(clock_overhead * self.nbr_timed_calls),
+            complexities: self.complexities,
+        }
+    }
+}
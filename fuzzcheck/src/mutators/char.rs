@@ -2,6 +2,7 @@ use std::any::Any;
 use std::ops::{Bound, RangeBounds};
 
 use crate::mutators::integer::binary_search_arbitrary_u32;
+use crate::mutators::DefaultMutatorWithinRange;
 use crate::{DefaultMutator, Mutator, MutatorExt};
 
 const INITIAL_MUTATION_STEP: u64 = 0;
@@ -73,6 +74,14 @@ impl CharWithinRangeMutator {
     }
 }
 
+impl DefaultMutatorWithinRange for char {
+    type Mutator = CharWithinRangeMutator;
+    #[no_coverage]
+    fn default_mutator_within_range<RB: RangeBounds<char>>(range: RB) -> Self::Mutator {
+        CharWithinRangeMutator::new(range)
+    }
+}
+
 impl Mutator<char> for CharWithinRangeMutator {
     #[doc(hidden)]
     type Cache = f64; // complexity of the character
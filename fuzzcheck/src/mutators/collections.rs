@@ -0,0 +1,1011 @@
+//! Mutators for maps and sets: `HashMap`, `BTreeMap`, `HashSet`, and `BTreeSet`.
+//!
+//! They are all instances of the [`KeyedCollectionMutator`], which mutates a collection of
+//! entries indexed by unique keys. It can insert and remove entries and mutate their keys and values,
+//! but never creates two entries with the same key. Sets are collections whose values are `()`.
+//!
+//! ```
+//! use std::collections::{BTreeSet, HashMap};
+//! use fuzzcheck::DefaultMutator;
+//! use fuzzcheck::mutators::collections::KeyedCollectionMutator;
+//! use fuzzcheck::mutators::integer_within_range::U8WithinRangeMutator;
+//!
+//! let m = <HashMap<u8, Vec<bool>>>::default_mutator();
+//! // a set containing numbers between 0 and 9
+//! let m = KeyedCollectionMutator::<BTreeSet<u8>, _, _, _, _>::new_set(U8WithinRangeMutator::new(0..10));
+//! ```
+use std::any::Any;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::mutators::unit::VoidMutator;
+use crate::mutators::vose_alias::VoseAlias;
+use crate::{DefaultMutator, Mutator};
+
+/// The maximum number of operations tried by a single mutation before giving up
+const MAX_NBR_TRIES: usize = 16;
+
+/// A collection of values indexed by unique keys, such as `HashMap<K, V>` or `BTreeSet<K>` (whose values are `()`)
+pub trait KeyedCollection<K, V>: Clone + Default + 'static {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn get(&self, key: &K) -> Option<&V>;
+    fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+    /// Insert the entry in the collection, which does not already contain the key
+    fn insert(&mut self, key: K, value: V);
+    fn remove(&mut self, key: &K) -> Option<V>;
+    fn entries<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>;
+}
+
+impl<K, V> KeyedCollection<K, V> for HashMap<K, V>
+where
+    K: Clone + Hash + Eq + 'static,
+    V: Clone + 'static,
+{
+    #[no_coverage]
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+    #[no_coverage]
+    fn get(&self, key: &K) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+    #[no_coverage]
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        HashMap::get_mut(self, key)
+    }
+    #[no_coverage]
+    fn insert(&mut self, key: K, value: V) {
+        HashMap::insert(self, key, value);
+    }
+    #[no_coverage]
+    fn remove(&mut self, key: &K) -> Option<V> {
+        HashMap::remove(self, key)
+    }
+    #[no_coverage]
+    fn entries<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a> {
+        Box::new(self.iter())
+    }
+}
+
+impl<K, V> KeyedCollection<K, V> for BTreeMap<K, V>
+where
+    K: Clone + Ord + 'static,
+    V: Clone + 'static,
+{
+    #[no_coverage]
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+    #[no_coverage]
+    fn get(&self, key: &K) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+    #[no_coverage]
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        BTreeMap::get_mut(self, key)
+    }
+    #[no_coverage]
+    fn insert(&mut self, key: K, value: V) {
+        BTreeMap::insert(self, key, value);
+    }
+    #[no_coverage]
+    fn remove(&mut self, key: &K) -> Option<V> {
+        BTreeMap::remove(self, key)
+    }
+    #[no_coverage]
+    fn entries<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a> {
+        Box::new(self.iter())
+    }
+}
+
+impl<K> KeyedCollection<K, ()> for HashSet<K>
+where
+    K: Clone + Hash + Eq + 'static,
+{
+    #[no_coverage]
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+    #[no_coverage]
+    fn get(&self, key: &K) -> Option<&()> {
+        self.contains(key).then_some(&())
+    }
+    #[no_coverage]
+    fn get_mut(&mut self, key: &K) -> Option<&mut ()> {
+        // boxing a zero-sized value does not allocate
+        self.contains(key).then_some(Box::leak(Box::new(())))
+    }
+    #[no_coverage]
+    fn insert(&mut self, key: K, _value: ()) {
+        HashSet::insert(self, key);
+    }
+    #[no_coverage]
+    fn remove(&mut self, key: &K) -> Option<()> {
+        HashSet::remove(self, key).then_some(())
+    }
+    #[no_coverage]
+    fn entries<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a ())> + 'a> {
+        Box::new(self.iter().map(
+            #[no_coverage]
+            |k| (k, &()),
+        ))
+    }
+}
+
+impl<K> KeyedCollection<K, ()> for BTreeSet<K>
+where
+    K: Clone + Ord + 'static,
+{
+    #[no_coverage]
+    fn len(&self) -> usize {
+        BTreeSet::len(self)
+    }
+    #[no_coverage]
+    fn get(&self, key: &K) -> Option<&()> {
+        self.contains(key).then_some(&())
+    }
+    #[no_coverage]
+    fn get_mut(&mut self, key: &K) -> Option<&mut ()> {
+        // boxing a zero-sized value does not allocate
+        self.contains(key).then_some(Box::leak(Box::new(())))
+    }
+    #[no_coverage]
+    fn insert(&mut self, key: K, _value: ()) {
+        BTreeSet::insert(self, key);
+    }
+    #[no_coverage]
+    fn remove(&mut self, key: &K) -> Option<()> {
+        BTreeSet::remove(self, key).then_some(())
+    }
+    #[no_coverage]
+    fn entries<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a ())> + 'a> {
+        Box::new(self.iter().map(
+            #[no_coverage]
+            |k| (k, &()),
+        ))
+    }
+}
+
+/// A mutator for collections of entries indexed by unique keys, such as maps and sets
+///
+/// The complexity of a collection is `1.0` plus the sum of the complexities of its keys and values.
+pub struct KeyedCollectionMutator<C, K, V, KM, VM>
+where
+    C: KeyedCollection<K, V>,
+    K: Clone + 'static,
+    V: Clone + 'static,
+    KM: Mutator<K>,
+    VM: Mutator<V>,
+{
+    key_mutator: KM,
+    value_mutator: VM,
+    rng: fastrand::Rng,
+    _phantom: PhantomData<(C, K, V)>,
+}
+
+impl<C, K, V, KM, VM> KeyedCollectionMutator<C, K, V, KM, VM>
+where
+    C: KeyedCollection<K, V>,
+    K: Clone + 'static,
+    V: Clone + 'static,
+    KM: Mutator<K>,
+    VM: Mutator<V>,
+{
+    #[no_coverage]
+    pub fn new(key_mutator: KM, value_mutator: VM) -> Self {
+        Self {
+            key_mutator,
+            value_mutator,
+            rng: fastrand::Rng::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<C, K, KM> KeyedCollectionMutator<C, K, (), KM, VoidMutator>
+where
+    C: KeyedCollection<K, ()>,
+    K: Clone + 'static,
+    KM: Mutator<K>,
+{
+    /// Create a mutator for a set whose elements are mutated by `key_mutator`
+    #[no_coverage]
+    pub fn new_set(key_mutator: KM) -> Self {
+        Self::new(key_mutator, <()>::default_mutator())
+    }
+}
+
+/// The mutator of `HashMap<K, V>`
+pub type HashMapMutator<K, V, KM, VM> = KeyedCollectionMutator<HashMap<K, V>, K, V, KM, VM>;
+/// The mutator of `BTreeMap<K, V>`
+pub type BTreeMapMutator<K, V, KM, VM> = KeyedCollectionMutator<BTreeMap<K, V>, K, V, KM, VM>;
+/// The mutator of `HashSet<K>`
+pub type HashSetMutator<K, KM> = KeyedCollectionMutator<HashSet<K>, K, (), KM, VoidMutator>;
+/// The mutator of `BTreeSet<K>`
+pub type BTreeSetMutator<K, KM> = KeyedCollectionMutator<BTreeSet<K>, K, (), KM, VoidMutator>;
+
+/// The cache of an entry of the collection.
+///
+/// It contains a copy of the key, which identifies the entry in the collection.
+pub struct EntryCache<K, KC, VC> {
+    key: K,
+    key_cache: KC,
+    value_cache: VC,
+    cplx: f64,
+}
+impl<K: Clone, KC: Clone, VC: Clone> Clone for EntryCache<K, KC, VC> {
+    #[no_coverage]
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            key_cache: self.key_cache.clone(),
+            value_cache: self.value_cache.clone(),
+            cplx: self.cplx,
+        }
+    }
+}
+
+pub struct Cache<K, KC, VC> {
+    entries: Vec<EntryCache<K, KC, VC>>,
+    sum_cplx: f64,
+}
+impl<K: Clone, KC: Clone, VC: Clone> Clone for Cache<K, KC, VC> {
+    #[no_coverage]
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            sum_cplx: self.sum_cplx,
+        }
+    }
+}
+
+/// The mutation step of the collection.
+///
+/// The steps of the entries are in the same order as the entries of the cache. The operations whose
+/// ordered mutations are exhausted are removed, such that the ordered mutations of the collection end.
+pub struct MutationStep<KS, VS, KA> {
+    entries: Vec<(KS, VS)>,
+    key_arbitrary_step: KA,
+    operations: Vec<Operation>,
+    weights: Vec<f64>,
+    sampling: VoseAlias,
+}
+impl<KS: Clone, VS: Clone, KA: Clone> Clone for MutationStep<KS, VS, KA> {
+    #[no_coverage]
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            key_arbitrary_step: self.key_arbitrary_step.clone(),
+            operations: self.operations.clone(),
+            weights: self.weights.clone(),
+            sampling: self.sampling.clone(),
+        }
+    }
+}
+
+/// An operation performed by the ordered mutations of the collection
+#[derive(Clone)]
+enum Operation {
+    /// Insert an entry whose key is given by the key arbitrary step
+    Insert,
+    /// Remove each entry, in order
+    Remove { next_idx: usize },
+    /// Mutate the keys of the entries at the given indices, in turn
+    MutateKey { indices: Vec<usize>, next: usize },
+    /// Mutate the values of the entries at the given indices, in turn
+    MutateValue { indices: Vec<usize>, next: usize },
+}
+
+/// Why an operation on the collection failed
+enum OperationError {
+    /// The ordered mutations of the operation, or of the entry it was applied to, are exhausted
+    Exhausted,
+    /// The operation was not applied, e.g. because it would create a duplicate key
+    Rejected,
+}
+
+/// The arbitrary step of the collection.
+///
+/// The first collection is empty, and the next ones contain a single entry whose key is given by
+/// the ordered arbitrary keys, such that they are all different.
+pub struct ArbitraryStep<KA> {
+    make_empty: bool,
+    key_arbitrary_step: KA,
+}
+impl<KA: Clone> Clone for ArbitraryStep<KA> {
+    #[no_coverage]
+    fn clone(&self) -> Self {
+        Self {
+            make_empty: self.make_empty,
+            key_arbitrary_step: self.key_arbitrary_step.clone(),
+        }
+    }
+}
+
+pub enum UnmutateToken<C, K, V, KC, VC, KT, VT> {
+    Insert,
+    Remove {
+        idx: usize,
+        entry: EntryCache<K, KC, VC>,
+        value: V,
+    },
+    MutateKey {
+        idx: usize,
+        token: KT,
+        cplx: f64,
+    },
+    MutateValue {
+        idx: usize,
+        token: VT,
+        cplx: f64,
+    },
+    Replace {
+        value: C,
+        cache: Cache<K, KC, VC>,
+    },
+}
+
+type Token<C, K, V, KM, VM> = UnmutateToken<
+    C,
+    K,
+    V,
+    <KM as Mutator<K>>::Cache,
+    <VM as Mutator<V>>::Cache,
+    <KM as Mutator<K>>::UnmutateToken,
+    <VM as Mutator<V>>::UnmutateToken,
+>;
+
+impl<C, K, V, KM, VM> KeyedCollectionMutator<C, K, V, KM, VM>
+where
+    C: KeyedCollection<K, V>,
+    K: Clone + 'static,
+    V: Clone + 'static,
+    KM: Mutator<K>,
+    VM: Mutator<V>,
+{
+    #[no_coverage]
+    fn min_entry_complexity(&self) -> f64 {
+        self.key_mutator.min_complexity() + self.value_mutator.min_complexity()
+    }
+
+    #[no_coverage]
+    fn complexity_from_inner(&self, sum_cplx: f64, len: usize) -> f64 {
+        1.0 + if len == 0 || self.min_entry_complexity() > 0.0 {
+            sum_cplx
+        } else {
+            len as f64 + sum_cplx
+        }
+    }
+
+    /// Insert a new entry, whose key is given by the key arbitrary step if possible
+    #[no_coverage]
+    fn insert(
+        &self,
+        value: &mut C,
+        cache: &mut <Self as Mutator<C>>::Cache,
+        key_arbitrary_step: Option<&mut KM::ArbitraryStep>,
+        max_cplx: f64,
+    ) -> Result<(Token<C, K, V, KM, VM>, f64), OperationError> {
+        let max_entry_cplx = max_cplx - self.complexity_from_inner(cache.sum_cplx, value.len() + 1);
+        if max_entry_cplx < self.min_entry_complexity() {
+            return Err(OperationError::Rejected);
+        }
+        let max_key_cplx = max_entry_cplx - self.value_mutator.min_complexity();
+        let (key, key_cplx) = if let Some(step) = key_arbitrary_step {
+            self.key_mutator
+                .ordered_arbitrary(step, max_key_cplx)
+                .ok_or(OperationError::Exhausted)?
+        } else {
+            self.key_mutator.random_arbitrary(max_key_cplx)
+        };
+        if value.get(&key).is_some() {
+            return Err(OperationError::Rejected);
+        }
+        let (element, value_cplx) = self.value_mutator.random_arbitrary(max_entry_cplx - key_cplx);
+        let entry_cplx = key_cplx + value_cplx;
+        let cplx = self.complexity_from_inner(cache.sum_cplx + entry_cplx, value.len() + 1);
+        if cplx > max_cplx {
+            return Err(OperationError::Rejected);
+        }
+        let key_cache = self.key_mutator.validate_value(&key).ok_or(OperationError::Rejected)?;
+        let value_cache = self
+            .value_mutator
+            .validate_value(&element)
+            .ok_or(OperationError::Rejected)?;
+        value.insert(key.clone(), element);
+        cache.entries.push(EntryCache {
+            key,
+            key_cache,
+            value_cache,
+            cplx: entry_cplx,
+        });
+        cache.sum_cplx += entry_cplx;
+        Ok((UnmutateToken::Insert, cplx))
+    }
+
+    #[no_coverage]
+    fn remove(
+        &self,
+        value: &mut C,
+        cache: &mut <Self as Mutator<C>>::Cache,
+        idx: usize,
+    ) -> (Token<C, K, V, KM, VM>, f64) {
+        // the entries are not ordered, and the removed entry is put back at the same index by `unmutate`
+        let entry = cache.entries.swap_remove(idx);
+        let element = value.remove(&entry.key).unwrap();
+        cache.sum_cplx -= entry.cplx;
+        let cplx = self.complexity_from_inner(cache.sum_cplx, value.len());
+        (
+            UnmutateToken::Remove {
+                idx,
+                entry,
+                value: element,
+            },
+            cplx,
+        )
+    }
+
+    /// Mutate the key of the entry at index `idx`, with an ordered mutation if a step is given
+    #[no_coverage]
+    fn mutate_key(
+        &self,
+        value: &mut C,
+        cache: &mut <Self as Mutator<C>>::Cache,
+        idx: usize,
+        step: Option<(&mut KM::MutationStep, &dyn crate::SubValueProvider)>,
+        max_cplx: f64,
+    ) -> Result<(Token<C, K, V, KM, VM>, f64), OperationError> {
+        let len = value.len();
+        let entry = &mut cache.entries[idx];
+        let old_entry_cplx = entry.cplx;
+        let old_key_cplx = self.key_mutator.complexity(&entry.key, &entry.key_cache);
+        let max_key_cplx = max_cplx - self.complexity_from_inner(cache.sum_cplx - old_key_cplx, len);
+
+        let element = value.remove(&entry.key).unwrap();
+        let (token, key_cplx) = if let Some((step, subvalue_provider)) = step {
+            if let Some(x) = self.key_mutator.ordered_mutate(
+                &mut entry.key,
+                &mut entry.key_cache,
+                step,
+                subvalue_provider,
+                max_key_cplx,
+            ) {
+                x
+            } else {
+                value.insert(entry.key.clone(), element);
+                return Err(OperationError::Exhausted);
+            }
+        } else {
+            self.key_mutator
+                .random_mutate(&mut entry.key, &mut entry.key_cache, max_key_cplx)
+        };
+        if value.get(&entry.key).is_some() {
+            // another entry has the same key
+            self.key_mutator.unmutate(&mut entry.key, &mut entry.key_cache, token);
+            value.insert(entry.key.clone(), element);
+            return Err(OperationError::Rejected);
+        }
+        value.insert(entry.key.clone(), element);
+        entry.cplx = old_entry_cplx - old_key_cplx + key_cplx;
+        cache.sum_cplx += entry.cplx - old_entry_cplx;
+        let cplx = self.complexity_from_inner(cache.sum_cplx, len);
+        Ok((
+            UnmutateToken::MutateKey {
+                idx,
+                token,
+                cplx: old_entry_cplx,
+            },
+            cplx,
+        ))
+    }
+
+    /// Mutate the value of the entry at index `idx`, with an ordered mutation if a step is given
+    #[no_coverage]
+    fn mutate_value(
+        &self,
+        value: &mut C,
+        cache: &mut <Self as Mutator<C>>::Cache,
+        idx: usize,
+        step: Option<(&mut VM::MutationStep, &dyn crate::SubValueProvider)>,
+        max_cplx: f64,
+    ) -> Result<(Token<C, K, V, KM, VM>, f64), OperationError> {
+        let len = value.len();
+        let entry = &mut cache.entries[idx];
+        let old_entry_cplx = entry.cplx;
+        let element = value.get_mut(&entry.key).unwrap();
+        let old_value_cplx = self.value_mutator.complexity(element, &entry.value_cache);
+        let max_value_cplx = max_cplx - self.complexity_from_inner(cache.sum_cplx - old_value_cplx, len);
+
+        let (token, value_cplx) = if let Some((step, subvalue_provider)) = step {
+            self.value_mutator
+                .ordered_mutate(element, &mut entry.value_cache, step, subvalue_provider, max_value_cplx)
+                .ok_or(OperationError::Exhausted)?
+        } else {
+            self.value_mutator
+                .random_mutate(element, &mut entry.value_cache, max_value_cplx)
+        };
+        entry.cplx = old_entry_cplx - old_value_cplx + value_cplx;
+        cache.sum_cplx += entry.cplx - old_entry_cplx;
+        let cplx = self.complexity_from_inner(cache.sum_cplx, len);
+        Ok((
+            UnmutateToken::MutateValue {
+                idx,
+                token,
+                cplx: old_entry_cplx,
+            },
+            cplx,
+        ))
+    }
+}
+
+impl<C, K, V, KM, VM> Mutator<C> for KeyedCollectionMutator<C, K, V, KM, VM>
+where
+    C: KeyedCollection<K, V>,
+    K: Clone + 'static,
+    V: Clone + 'static,
+    KM: Mutator<K>,
+    VM: Mutator<V>,
+{
+    #[doc(hidden)]
+    type Cache = Cache<K, KM::Cache, VM::Cache>;
+    #[doc(hidden)]
+    type MutationStep = MutationStep<KM::MutationStep, VM::MutationStep, KM::ArbitraryStep>;
+    #[doc(hidden)]
+    type ArbitraryStep = ArbitraryStep<KM::ArbitraryStep>;
+    #[doc(hidden)]
+    type UnmutateToken = Token<C, K, V, KM, VM>;
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+        ArbitraryStep {
+            make_empty: true,
+            key_arbitrary_step: self.key_mutator.default_arbitrary_step(),
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn is_valid(&self, value: &C) -> bool {
+        value.entries().all(
+            #[no_coverage]
+            |(k, v)| self.key_mutator.is_valid(k) && self.value_mutator.is_valid(v),
+        )
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn validate_value(&self, value: &C) -> Option<Self::Cache> {
+        let mut entries = Vec::with_capacity(value.len());
+        let mut sum_cplx = 0.0;
+        for (key, element) in value.entries() {
+            let key_cache = self.key_mutator.validate_value(key)?;
+            let value_cache = self.value_mutator.validate_value(element)?;
+            let cplx =
+                self.key_mutator.complexity(key, &key_cache) + self.value_mutator.complexity(element, &value_cache);
+            sum_cplx += cplx;
+            entries.push(EntryCache {
+                key: key.clone(),
+                key_cache,
+                value_cache,
+                cplx,
+            });
+        }
+        Some(Cache { entries, sum_cplx })
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_mutation_step(&self, value: &C, cache: &Self::Cache) -> Self::MutationStep {
+        let entries = cache
+            .entries
+            .iter()
+            .map(
+                #[no_coverage]
+                |entry| {
+                    let element = value.get(&entry.key).unwrap();
+                    (
+                        self.key_mutator.default_mutation_step(&entry.key, &entry.key_cache),
+                        self.value_mutator.default_mutation_step(element, &entry.value_cache),
+                    )
+                },
+            )
+            .collect();
+        // the weights of the operations are the same as the probabilities of the random mutations
+        let mut operations = vec![(Operation::Insert, 2.0)];
+        if !value.is_empty() {
+            operations.push((Operation::Remove { next_idx: 0 }, 1.0));
+            if self.key_mutator.max_complexity() > 0.0 {
+                let indices = (0..value.len()).collect();
+                operations.push((Operation::MutateKey { indices, next: 0 }, 2.0));
+            }
+            if self.value_mutator.max_complexity() > 0.0 {
+                let indices = (0..value.len()).collect();
+                operations.push((Operation::MutateValue { indices, next: 0 }, 5.0));
+            }
+        }
+        let (operations, weights): (Vec<_>, Vec<_>) = operations.into_iter().unzip();
+        let sampling = VoseAlias::new(weights.clone());
+        MutationStep {
+            entries,
+            key_arbitrary_step: self.key_mutator.default_arbitrary_step(),
+            operations,
+            weights,
+            sampling,
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        let key_cplx = self.key_mutator.global_search_space_complexity();
+        if key_cplx == 0.0 {
+            // there is at most one entry
+            1.0 + self.value_mutator.global_search_space_complexity()
+        } else {
+            (key_cplx + self.value_mutator.global_search_space_complexity()) * (usize::MAX as f64)
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        f64::INFINITY
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        self.complexity_from_inner(0.0, 0)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn complexity(&self, value: &C, cache: &Self::Cache) -> f64 {
+        self.complexity_from_inner(cache.sum_cplx, value.len())
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(C, f64)> {
+        if max_cplx < self.min_complexity() {
+            return None;
+        }
+        if step.make_empty {
+            step.make_empty = false;
+            return Some((C::default(), self.min_complexity()));
+        }
+        let mut value = C::default();
+        let mut cache = Cache {
+            entries: vec![],
+            sum_cplx: 0.0,
+        };
+        for _ in 0..MAX_NBR_TRIES {
+            match self.insert(&mut value, &mut cache, Some(&mut step.key_arbitrary_step), max_cplx) {
+                Ok((_, cplx)) => return Some((value, cplx)),
+                Err(OperationError::Exhausted) => return None,
+                Err(OperationError::Rejected) => {}
+            }
+        }
+        None
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_arbitrary(&self, max_cplx: f64) -> (C, f64) {
+        let mut value = C::default();
+        let min_cplx = self.min_complexity();
+        if max_cplx <= min_cplx || self.rng.u8(..) == 0 {
+            return (value, min_cplx);
+        }
+        let target_cplx = crate::mutators::gen_f64(&self.rng, min_cplx..max_cplx);
+        let mut cache = Cache {
+            entries: vec![],
+            sum_cplx: 0.0,
+        };
+        let mut nbr_failures = 0;
+        while nbr_failures < MAX_NBR_TRIES {
+            if self.insert(&mut value, &mut cache, None, target_cplx).is_err() {
+                nbr_failures += 1;
+            }
+        }
+        let cplx = self.complexity(&value, &cache);
+        (value, cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut C,
+        cache: &mut Self::Cache,
+        step: &mut Self::MutationStep,
+        subvalue_provider: &dyn crate::SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        if max_cplx < self.min_complexity() {
+            return None;
+        }
+        for _ in 0..MAX_NBR_TRIES {
+            if step.operations.is_empty() {
+                return None;
+            }
+            let op_idx = step.sampling.sample();
+            let result = match &mut step.operations[op_idx] {
+                Operation::Insert => self.insert(value, cache, Some(&mut step.key_arbitrary_step), max_cplx),
+                Operation::Remove { next_idx } => {
+                    if *next_idx < value.len() {
+                        *next_idx += 1;
+                        Ok(self.remove(value, cache, *next_idx - 1))
+                    } else {
+                        Err(OperationError::Exhausted)
+                    }
+                }
+                Operation::MutateKey { indices, next } => {
+                    let pos = *next % indices.len();
+                    let idx = indices[pos];
+                    let key_step = &mut step.entries[idx].0;
+                    let result = self.mutate_key(value, cache, idx, Some((key_step, subvalue_provider)), max_cplx);
+                    next_entry(indices, next, pos, &result);
+                    result
+                }
+                Operation::MutateValue { indices, next } => {
+                    let pos = *next % indices.len();
+                    let idx = indices[pos];
+                    let value_step = &mut step.entries[idx].1;
+                    let result = self.mutate_value(value, cache, idx, Some((value_step, subvalue_provider)), max_cplx);
+                    next_entry(indices, next, pos, &result);
+                    result
+                }
+            };
+            match result {
+                Ok(result) => return Some(result),
+                Err(OperationError::Rejected) => {}
+                Err(OperationError::Exhausted) => {
+                    let is_exhausted = match &step.operations[op_idx] {
+                        Operation::Insert | Operation::Remove { .. } => true,
+                        Operation::MutateKey { indices, .. } | Operation::MutateValue { indices, .. } => {
+                            indices.is_empty()
+                        }
+                    };
+                    if is_exhausted {
+                        step.operations.remove(op_idx);
+                        step.weights.remove(op_idx);
+                        if !step.weights.is_empty() {
+                            step.sampling = VoseAlias::new(step.weights.clone());
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_mutate(&self, value: &mut C, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
+        for _ in 0..MAX_NBR_TRIES {
+            let len = value.len();
+            let choice = if len == 0 { 0 } else { self.rng.u8(..10) };
+            let result = match choice {
+                0 | 1 => self.insert(value, cache, None, max_cplx),
+                2 => Ok(self.remove(value, cache, self.rng.usize(..len))),
+                3 | 4 => self.mutate_key(value, cache, self.rng.usize(..len), None, max_cplx),
+                _ => self.mutate_value(value, cache, self.rng.usize(..len), None, max_cplx),
+            };
+            if let Ok(result) = result {
+                return result;
+            }
+        }
+        let (new_value, cplx) = self.random_arbitrary(max_cplx);
+        let new_cache = self.validate_value(&new_value).unwrap();
+        (
+            UnmutateToken::Replace {
+                value: std::mem::replace(value, new_value),
+                cache: std::mem::replace(cache, new_cache),
+            },
+            cplx,
+        )
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn unmutate(&self, value: &mut C, cache: &mut Self::Cache, t: Self::UnmutateToken) {
+        match t {
+            UnmutateToken::Insert => {
+                let entry = cache.entries.pop().unwrap();
+                value.remove(&entry.key);
+                cache.sum_cplx -= entry.cplx;
+            }
+            UnmutateToken::Remove {
+                idx,
+                entry,
+                value: element,
+            } => {
+                value.insert(entry.key.clone(), element);
+                cache.sum_cplx += entry.cplx;
+                cache.entries.push(entry);
+                let last = cache.entries.len() - 1;
+                cache.entries.swap(idx, last);
+            }
+            UnmutateToken::MutateKey { idx, token, cplx } => {
+                let entry = &mut cache.entries[idx];
+                let element = value.remove(&entry.key).unwrap();
+                self.key_mutator.unmutate(&mut entry.key, &mut entry.key_cache, token);
+                value.insert(entry.key.clone(), element);
+                cache.sum_cplx += cplx - entry.cplx;
+                entry.cplx = cplx;
+            }
+            UnmutateToken::MutateValue { idx, token, cplx } => {
+                let entry = &mut cache.entries[idx];
+                let element = value.get_mut(&entry.key).unwrap();
+                self.value_mutator.unmutate(element, &mut entry.value_cache, token);
+                cache.sum_cplx += cplx - entry.cplx;
+                entry.cplx = cplx;
+            }
+            UnmutateToken::Replace {
+                value: old_value,
+                cache: old_cache,
+            } => {
+                *value = old_value;
+                *cache = old_cache;
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn visit_subvalues<'a>(&self, value: &'a C, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
+        for entry in cache.entries.iter() {
+            let element = value.get(&entry.key).unwrap();
+            let key_cplx = self.key_mutator.complexity(&entry.key, &entry.key_cache);
+            visit(&entry.key, key_cplx);
+            visit(element, entry.cplx - key_cplx);
+            self.key_mutator.visit_subvalues(&entry.key, &entry.key_cache, visit);
+            self.value_mutator.visit_subvalues(element, &entry.value_cache, visit);
+        }
+    }
+}
+
+/// Update the position of the next entry to mutate, given the result of the mutation of the entry at `pos`.
+///
+/// The entries whose ordered mutations are exhausted are removed from `indices`, whose order does not matter.
+#[no_coverage]
+fn next_entry<T>(indices: &mut Vec<usize>, next: &mut usize, pos: usize, result: &Result<T, OperationError>) {
+    if let Err(OperationError::Exhausted) = result {
+        indices.swap_remove(pos);
+        *next = pos;
+    } else {
+        *next = pos + 1;
+    }
+}
+
+impl<K, V> DefaultMutator for HashMap<K, V>
+where
+    K: DefaultMutator + Hash + Eq,
+    V: DefaultMutator,
+{
+    type Mutator = HashMapMutator<K, V, K::Mutator, V::Mutator>;
+    #[no_coverage]
+    fn default_mutator() -> Self::Mutator {
+        KeyedCollectionMutator::new(K::default_mutator(), V::default_mutator())
+    }
+}
+
+impl<K, V> DefaultMutator for BTreeMap<K, V>
+where
+    K: DefaultMutator + Ord,
+    V: DefaultMutator,
+{
+    type Mutator = BTreeMapMutator<K, V, K::Mutator, V::Mutator>;
+    #[no_coverage]
+    fn default_mutator() -> Self::Mutator {
+        KeyedCollectionMutator::new(K::default_mutator(), V::default_mutator())
+    }
+}
+
+impl<K> DefaultMutator for HashSet<K>
+where
+    K: DefaultMutator + Hash + Eq,
+{
+    type Mutator = HashSetMutator<K, K::Mutator>;
+    #[no_coverage]
+    fn default_mutator() -> Self::Mutator {
+        KeyedCollectionMutator::new_set(K::default_mutator())
+    }
+}
+
+impl<K> DefaultMutator for BTreeSet<K>
+where
+    K: DefaultMutator + Ord,
+{
+    type Mutator = BTreeSetMutator<K, K::Mutator>;
+    #[no_coverage]
+    fn default_mutator() -> Self::Mutator {
+        KeyedCollectionMutator::new_set(K::default_mutator())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashMap};
+
+    use super::KeyedCollectionMutator;
+    use crate::mutators::integer_within_range::U8WithinRangeMutator;
+    use crate::mutators::testing_utilities::test_mutator;
+    use crate::subvalue_provider::EmptySubValueProvider;
+    use crate::{DefaultMutator, Mutator};
+
+    #[test]
+    fn test_btree_set_mutator() {
+        let m = <BTreeSet<u16>>::default_mutator();
+        test_mutator(m, 500., 500., false, true, 100, 100);
+    }
+
+    #[test]
+    fn test_mutations_preserve_unique_keys() {
+        let m = <HashMap<u8, u16>>::default_mutator();
+        let mut value = HashMap::from([(1, 10), (2, 20), (3, 30)]);
+        let mut cache = m.validate_value(&value).unwrap();
+        let mut step = m.default_mutation_step(&value, &cache);
+        let original = value.clone();
+        let original_keys = cache.entries.iter().map(|e| e.key).collect::<Vec<_>>();
+        for i in 0..1000 {
+            let (token, cplx) = if i % 2 == 0 {
+                m.ordered_mutate(&mut value, &mut cache, &mut step, &EmptySubValueProvider, 1000.0)
+                    .unwrap()
+            } else {
+                m.random_mutate(&mut value, &mut cache, 1000.0)
+            };
+            let fresh_cache = m.validate_value(&value).unwrap();
+            assert_eq!(cache.entries.len(), value.len());
+            assert!((cplx - m.complexity(&value, &fresh_cache)).abs() < 0.01);
+            m.unmutate(&mut value, &mut cache, token);
+            assert_eq!(value, original);
+            assert!(cache.entries.iter().map(|e| e.key).eq(original_keys.iter().copied()));
+        }
+    }
+
+    #[test]
+    fn test_ordered_arbitrary_sets_are_different() {
+        let m = KeyedCollectionMutator::<BTreeSet<u8>, _, _, _, _>::new_set(U8WithinRangeMutator::new(0..4));
+        let mut step = m.default_arbitrary_step();
+        let mut sets = vec![];
+        while let Some((x, _)) = m.ordered_arbitrary(&mut step, 1000.0) {
+            assert!(!sets.contains(&x));
+            sets.push(x);
+        }
+        // the empty set and the four sets with a single element
+        assert_eq!(sets.len(), 5);
+    }
+
+    #[test]
+    fn test_ordered_mutations_are_exhausted() {
+        let m = KeyedCollectionMutator::<BTreeSet<u8>, _, _, _, _>::new_set(U8WithinRangeMutator::new(0..4));
+        let original = BTreeSet::from([1]);
+        let mut value = original.clone();
+        let mut cache = m.validate_value(&value).unwrap();
+        let mut step = m.default_mutation_step(&value, &cache);
+        let mut nbr_mutations = 0;
+        while let Some((token, _)) = m.ordered_mutate(&mut value, &mut cache, &mut step, &EmptySubValueProvider, 1000.0)
+        {
+            nbr_mutations += 1;
+            assert!(nbr_mutations < 100);
+            m.unmutate(&mut value, &mut cache, token);
+            assert_eq!(value, original);
+        }
+    }
+
+    #[test]
+    fn test_set_with_small_key_space() {
+        let m = KeyedCollectionMutator::<BTreeSet<u8>, _, _, _, _>::new_set(U8WithinRangeMutator::new(0..4));
+        for _ in 0..100 {
+            let (x, _) = m.random_arbitrary(1000.0);
+            assert!(x.len() <= 4);
+        }
+    }
+}
@@ -0,0 +1,500 @@
+/*!
+A checker for the contract of the [`Mutator`] trait.
+
+Implementing [`Mutator`] by hand is error-prone: the complexity of a value must be the same whether it
+is returned by a mutation or computed from a fresh cache, [`unmutate`](Mutator::unmutate) must revert
+every change made by a mutation, etc. When these rules are broken, the fuzzer does not crash. Instead, it
+silently explores the input space poorly.
+
+[`ConformanceTest`] exercises a mutator and reports every broken rule it detects. The mutators draw their
+random numbers from generators seeded by `fastrand`'s thread-local generator, so seeding it before creating
+the mutator makes the test reproducible:
+```
+use fuzzcheck::mutators::conformance::ConformanceTest;
+use fuzzcheck::DefaultMutator;
+
+fastrand::seed(0);
+let mutator = <Vec<Option<u8>>>::default_mutator();
+let report = ConformanceTest::new(256.0).run(&mutator);
+// panics with a readable report if the mutator violated the contract of `Mutator`
+report.assert_ok();
+```
+*/
+
+use std::any::Any;
+use std::collections::HashSet;
+use std::fmt::{Debug, Display};
+
+use crate::subvalue_provider::EmptySubValueProvider;
+use crate::Mutator;
+
+/// The maximum difference between two complexities that are considered equal
+const COMPLEXITY_TOLERANCE: f64 = 0.01;
+/// The maximum number of violations printed by the [`Display`] implementation of [`ConformanceReport`]
+const MAX_DISPLAYED_VIOLATIONS: usize = 20;
+
+/// The method of the [`Mutator`] trait that produced a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Origin {
+    OrderedArbitrary,
+    RandomArbitrary,
+    OrderedMutate,
+    RandomMutate,
+}
+impl Display for Origin {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::OrderedArbitrary => write!(f, "ordered_arbitrary"),
+            Origin::RandomArbitrary => write!(f, "random_arbitrary"),
+            Origin::OrderedMutate => write!(f, "ordered_mutate"),
+            Origin::RandomMutate => write!(f, "random_mutate"),
+        }
+    }
+}
+
+/// A rule of the [`Mutator`] contract that was broken during a [`ConformanceTest`]
+///
+/// The values are stored as their `Debug` representation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConformanceViolation {
+    /// `min_complexity()` is greater than `max_complexity()`, or one of them is not a number
+    InvalidComplexityBounds { min: f64, max: f64 },
+    /// A value produced by the mutator is rejected by `validate_value` or `is_valid`
+    InvalidValue { origin: Origin, value: String },
+    /// The complexity returned along with a value is different from `complexity(value, validate_value(value))`
+    InconsistentComplexity {
+        origin: Origin,
+        value: String,
+        returned: f64,
+        recomputed: f64,
+    },
+    /// The complexity of a value produced by the mutator is outside `min_complexity() ..= max_complexity()`
+    ComplexityOutOfBounds {
+        origin: Origin,
+        value: String,
+        cplx: f64,
+        min: f64,
+        max: f64,
+    },
+    /// `unmutate` did not restore the value that was mutated
+    UnmutateMismatch {
+        origin: Origin,
+        original: String,
+        mutated: String,
+        unmutated: String,
+    },
+    /// `unmutate` restored the value, but not its cache, such that its complexity changed
+    UnmutateCacheMismatch {
+        origin: Origin,
+        value: String,
+        before: f64,
+        after: f64,
+    },
+    /// `ordered_arbitrary` produced the same value twice
+    DuplicateArbitrary { value: String },
+    /// `visit_subvalues` gave a complexity that is negative, not a number, or greater than the complexity of the value
+    InvalidSubvalueComplexity {
+        value: String,
+        cplx: f64,
+        subvalue_cplx: f64,
+    },
+}
+impl Display for ConformanceViolation {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConformanceViolation::InvalidComplexityBounds { min, max } => {
+                write!(f, "invalid complexity bounds: min_complexity() = {min:.3}, max_complexity() = {max:.3}")
+            }
+            ConformanceViolation::InvalidValue { origin, value } => {
+                write!(f, "{origin} produced a value rejected by validate_value: {value}")
+            }
+            ConformanceViolation::InconsistentComplexity {
+                origin,
+                value,
+                returned,
+                recomputed,
+            } => write!(
+                f,
+                "{origin} returned a complexity of {returned:.3}, but complexity(..) computes {recomputed:.3} for {value}"
+            ),
+            ConformanceViolation::ComplexityOutOfBounds {
+                origin,
+                value,
+                cplx,
+                min,
+                max,
+            } => write!(
+                f,
+                "{origin} produced a value of complexity {cplx:.3}, outside of {min:.3} ..= {max:.3}: {value}"
+            ),
+            ConformanceViolation::UnmutateMismatch {
+                origin,
+                original,
+                mutated,
+                unmutated,
+            } => write!(
+                f,
+                "unmutate did not revert {origin}: {original} was mutated to {mutated}, then unmutated to {unmutated}"
+            ),
+            ConformanceViolation::UnmutateCacheMismatch {
+                origin,
+                value,
+                before,
+                after,
+            } => write!(
+                f,
+                "unmutate did not revert the cache after {origin}: the complexity of {value} went from {before:.3} to {after:.3}"
+            ),
+            ConformanceViolation::DuplicateArbitrary { value } => {
+                write!(f, "ordered_arbitrary produced the same value twice: {value}")
+            }
+            ConformanceViolation::InvalidSubvalueComplexity {
+                value,
+                cplx,
+                subvalue_cplx,
+            } => write!(
+                f,
+                "visit_subvalues gave a complexity of {subvalue_cplx:.3} to a subvalue of {value}, whose complexity is {cplx:.3}"
+            ),
+        }
+    }
+}
+
+/// The result of a [`ConformanceTest`]
+#[derive(Debug, Clone, Default)]
+pub struct ConformanceReport {
+    /// The number of values produced by the mutator during the test
+    pub nbr_values: usize,
+    /// The violations of the [`Mutator`] contract, in the order they were detected
+    pub violations: Vec<ConformanceViolation>,
+}
+impl ConformanceReport {
+    /// Returns `true` if no violation was detected
+    #[no_coverage]
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+    /// Panics with the content of the report if any violation was detected
+    #[no_coverage]
+    #[track_caller]
+    pub fn assert_ok(&self) {
+        if !self.is_ok() {
+            panic!("{self}");
+        }
+    }
+}
+impl Display for ConformanceReport {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_ok() {
+            return write!(
+                f,
+                "the mutator produced {} values without any violation",
+                self.nbr_values
+            );
+        }
+        writeln!(
+            f,
+            "the mutator produced {} values and violated its contract {} times:",
+            self.nbr_values,
+            self.violations.len()
+        )?;
+        for violation in self.violations.iter().take(MAX_DISPLAYED_VIOLATIONS) {
+            writeln!(f, "  - {violation}")?;
+        }
+        if self.violations.len() > MAX_DISPLAYED_VIOLATIONS {
+            writeln!(f, "  ... and {} more", self.violations.len() - MAX_DISPLAYED_VIOLATIONS)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a mutator follows the contract of the [`Mutator`] trait.
+///
+/// The test generates values with `ordered_arbitrary` and `random_arbitrary`, and mutates each of them
+/// with `ordered_mutate` and `random_mutate`, followed by `unmutate`. It checks that:
+/// * `min_complexity() <= max_complexity()`
+/// * every value produced is accepted by `validate_value` and `is_valid`
+/// * the complexity returned with each value is equal to the one given by `complexity`, and lies within
+///   `min_complexity() ..= max_complexity()`
+/// * `unmutate` restores both the value and its cache
+/// * the complexities given by `visit_subvalues` are not greater than the complexity of the whole value
+///
+/// Two values are considered equal if they are equal according to `PartialEq` or if they have the same
+/// `Debug` representation, such that a float mutator producing `NaN` is not reported as broken.
+///
+/// It can also check that `ordered_arbitrary` does not produce the same value twice, with
+/// [`check_duplicates`](ConformanceTest::check_duplicates). That check is disabled by default, because many
+/// mutators, including the default mutators of vectors and tuples, fall back to random generation in
+/// `ordered_arbitrary` once they have produced their simplest values.
+#[derive(Debug, Clone)]
+pub struct ConformanceTest {
+    max_cplx: f64,
+    nbr_arbitraries: usize,
+    nbr_mutations: usize,
+    check_duplicates: bool,
+}
+
+impl ConformanceTest {
+    /// Create a conformance test generating values up to the given maximum complexity.
+    ///
+    /// By default, the test generates 100 values with each of `ordered_arbitrary` and `random_arbitrary`,
+    /// and mutates each of them 100 times.
+    #[no_coverage]
+    pub fn new(max_cplx: f64) -> Self {
+        Self {
+            max_cplx,
+            nbr_arbitraries: 100,
+            nbr_mutations: 100,
+            check_duplicates: false,
+        }
+    }
+
+    /// Set the number of values generated by each of `ordered_arbitrary` and `random_arbitrary`,
+    /// and the number of times each of them is mutated
+    #[no_coverage]
+    pub fn with_iterations(mut self, nbr_arbitraries: usize, nbr_mutations: usize) -> Self {
+        self.nbr_arbitraries = nbr_arbitraries;
+        self.nbr_mutations = nbr_mutations;
+        self
+    }
+
+    /// Report the values produced more than once by `ordered_arbitrary`
+    ///
+    /// Only use it for mutators that keep track of the values they have already produced.
+    #[no_coverage]
+    pub fn check_duplicates(mut self) -> Self {
+        self.check_duplicates = true;
+        self
+    }
+
+    /// Run the conformance test on the mutator and return the violations of the [`Mutator`] contract
+    #[no_coverage]
+    pub fn run<T, M>(&self, mutator: &M) -> ConformanceReport
+    where
+        T: Clone + Debug + PartialEq + 'static,
+        M: Mutator<T>,
+    {
+        let mut checker = Checker {
+            mutator,
+            min: mutator.min_complexity(),
+            max: mutator.max_complexity(),
+            report: ConformanceReport::default(),
+        };
+        if !is_within(checker.min, f64::NEG_INFINITY, checker.max) {
+            checker
+                .report
+                .violations
+                .push(ConformanceViolation::InvalidComplexityBounds {
+                    min: checker.min,
+                    max: checker.max,
+                });
+        }
+
+        let mut arbitraries = HashSet::new();
+        let mut step = mutator.default_arbitrary_step();
+        for _ in 0..self.nbr_arbitraries {
+            let (value, cplx) = if let Some(x) = mutator.ordered_arbitrary(&mut step, self.max_cplx) {
+                x
+            } else {
+                break;
+            };
+            if self.check_duplicates && !arbitraries.insert(format!("{value:?}")) {
+                checker
+                    .report
+                    .violations
+                    .push(ConformanceViolation::DuplicateArbitrary {
+                        value: format!("{value:?}"),
+                    });
+            }
+            self.check_value_and_mutations(&mut checker, Origin::OrderedArbitrary, value, cplx);
+        }
+        for _ in 0..self.nbr_arbitraries {
+            let (value, cplx) = mutator.random_arbitrary(self.max_cplx);
+            self.check_value_and_mutations(&mut checker, Origin::RandomArbitrary, value, cplx);
+        }
+        checker.report
+    }
+
+    #[no_coverage]
+    fn check_value_and_mutations<T, M>(&self, checker: &mut Checker<M>, origin: Origin, value: T, cplx: f64)
+    where
+        T: Clone + Debug + PartialEq + 'static,
+        M: Mutator<T>,
+    {
+        let mutator = checker.mutator;
+        let cache = if let Some(cache) = checker.check_value(origin, &value, cplx) {
+            cache
+        } else {
+            return;
+        };
+        let mut step = mutator.default_mutation_step(&value, &cache);
+        let mut mutated = value.clone();
+        let mut mutated_cache = cache.clone();
+
+        for _ in 0..self.nbr_mutations {
+            let (token, cplx) = if let Some(x) = mutator.ordered_mutate(
+                &mut mutated,
+                &mut mutated_cache,
+                &mut step,
+                &EmptySubValueProvider,
+                self.max_cplx,
+            ) {
+                x
+            } else {
+                break;
+            };
+            checker.check_value(Origin::OrderedMutate, &mutated, cplx);
+            let mutated_value = format!("{mutated:?}");
+            mutator.unmutate(&mut mutated, &mut mutated_cache, token);
+            checker.check_unmutate(
+                Origin::OrderedMutate,
+                &value,
+                &cache,
+                mutated_value,
+                &mutated,
+                &mutated_cache,
+            );
+            if !equal(&value, &mutated) {
+                // the following mutations would start from the wrong value, so move on to random mutations
+                mutated = value.clone();
+                mutated_cache = cache.clone();
+                break;
+            }
+        }
+        for _ in 0..self.nbr_mutations {
+            let (token, cplx) = mutator.random_mutate(&mut mutated, &mut mutated_cache, self.max_cplx);
+            checker.check_value(Origin::RandomMutate, &mutated, cplx);
+            let mutated_value = format!("{mutated:?}");
+            mutator.unmutate(&mut mutated, &mut mutated_cache, token);
+            checker.check_unmutate(
+                Origin::RandomMutate,
+                &value,
+                &cache,
+                mutated_value,
+                &mutated,
+                &mutated_cache,
+            );
+            if !equal(&value, &mutated) {
+                break;
+            }
+        }
+    }
+}
+
+/// The state of a running [`ConformanceTest`]
+struct Checker<'a, M> {
+    mutator: &'a M,
+    min: f64,
+    max: f64,
+    report: ConformanceReport,
+}
+
+impl<'a, M> Checker<'a, M> {
+    /// Check the rules that apply to every value produced by the mutator, and return its cache
+    #[no_coverage]
+    fn check_value<T>(&mut self, origin: Origin, value: &T, cplx: f64) -> Option<M::Cache>
+    where
+        T: Clone + Debug + PartialEq + 'static,
+        M: Mutator<T>,
+    {
+        self.report.nbr_values += 1;
+        let violations = &mut self.report.violations;
+        let cache = match self.mutator.validate_value(value) {
+            Some(cache) if self.mutator.is_valid(value) => cache,
+            _ => {
+                violations.push(ConformanceViolation::InvalidValue {
+                    origin,
+                    value: format!("{value:?}"),
+                });
+                return None;
+            }
+        };
+        let recomputed = self.mutator.complexity(value, &cache);
+        if !is_within(cplx, recomputed, recomputed) {
+            violations.push(ConformanceViolation::InconsistentComplexity {
+                origin,
+                value: format!("{value:?}"),
+                returned: cplx,
+                recomputed,
+            });
+        }
+        if !is_within(recomputed, self.min, self.max) {
+            violations.push(ConformanceViolation::ComplexityOutOfBounds {
+                origin,
+                value: format!("{value:?}"),
+                cplx: recomputed,
+                min: self.min,
+                max: self.max,
+            });
+        }
+        let mut subvalue_complexities = vec![];
+        self.mutator.visit_subvalues(
+            value,
+            &cache,
+            &mut #[no_coverage]
+            |_: &dyn Any, subvalue_cplx| subvalue_complexities.push(subvalue_cplx),
+        );
+        for subvalue_cplx in subvalue_complexities {
+            if !is_within(subvalue_cplx, 0.0, recomputed) {
+                violations.push(ConformanceViolation::InvalidSubvalueComplexity {
+                    value: format!("{value:?}"),
+                    cplx: recomputed,
+                    subvalue_cplx,
+                });
+            }
+        }
+        Some(cache)
+    }
+
+    /// Check that `unmutated` and its cache are the same as `original` and its cache
+    #[no_coverage]
+    fn check_unmutate<T>(
+        &mut self,
+        origin: Origin,
+        original: &T,
+        original_cache: &M::Cache,
+        mutated: String,
+        unmutated: &T,
+        unmutated_cache: &M::Cache,
+    ) where
+        T: Clone + Debug + PartialEq + 'static,
+        M: Mutator<T>,
+    {
+        if !equal(original, unmutated) {
+            self.report.violations.push(ConformanceViolation::UnmutateMismatch {
+                origin,
+                original: format!("{original:?}"),
+                mutated,
+                unmutated: format!("{unmutated:?}"),
+            });
+            return;
+        }
+        let before = self.mutator.complexity(original, original_cache);
+        let after = self.mutator.complexity(unmutated, unmutated_cache);
+        if !is_within(after, before, before) {
+            self.report
+                .violations
+                .push(ConformanceViolation::UnmutateCacheMismatch {
+                    origin,
+                    value: format!("{original:?}"),
+                    before,
+                    after,
+                });
+        }
+    }
+}
+
+/// Returns `true` if the complexity is within `min ..= max`, with a small tolerance, and is not `NaN`
+#[no_coverage]
+fn is_within(cplx: f64, min: f64, max: f64) -> bool {
+    min - COMPLEXITY_TOLERANCE <= cplx && cplx <= max + COMPLEXITY_TOLERANCE
+}
+
+/// Returns `true` if the two values are equal or have the same `Debug` representation
+#[no_coverage]
+fn equal<T: Debug + PartialEq>(x: &T, y: &T) -> bool {
+    x == y || format!("{x:?}") == format!("{y:?}")
+}
@@ -1,5 +1,6 @@
 use std::any::Any;
 
+use super::vose_alias::VoseAlias;
 use crate::Mutator;
 
 /// Trait used by the [DefaultMutator derive macro](fuzzcheck_mutators_derive::DefaultMutator)
@@ -12,6 +13,8 @@ pub trait BasicEnumStructure {
 /// A mutator used for enums implementing [BasicEnumStructure]
 pub struct BasicEnumMutator {
     non_ignored_variant_count: usize,
+    weights: Option<VoseAlias>,
+    variants_by_weight: Vec<usize>,
     rng: fastrand::Rng,
     cplx: f64,
 }
@@ -23,10 +26,43 @@ impl BasicEnumMutator {
     {
         Self {
             non_ignored_variant_count,
+            weights: None,
+            variants_by_weight: vec![],
             rng: <_>::default(),
             cplx: crate::mutators::size_to_cplxity(non_ignored_variant_count),
         }
     }
+
+    /// Choose the variants with a probability proportional to their weight, instead of uniformly.
+    ///
+    /// There must be one weight per non-ignored variant. The weights do not need to add up to 1.
+    /// The ordered mutations also try the variants in order of decreasing weight.
+    #[no_coverage]
+    pub fn with_weights(mut self, weights: Vec<f64>) -> Self {
+        assert_eq!(weights.len(), self.non_ignored_variant_count);
+        assert!(weights.iter().all(
+            #[no_coverage]
+            |w| w.is_finite() && *w >= 0.0
+        ));
+        assert!(weights.iter().sum::<f64>() > 0.0);
+        let mut variants_by_weight = (0..weights.len()).collect::<Vec<_>>();
+        variants_by_weight.sort_by(
+            #[no_coverage]
+            |&x, &y| weights[y].partial_cmp(&weights[x]).unwrap_or(std::cmp::Ordering::Equal),
+        );
+        self.variants_by_weight = variants_by_weight;
+        self.weights = Some(VoseAlias::new(weights));
+        self
+    }
+
+    #[no_coverage]
+    fn random_variant_index(&self) -> usize {
+        if let Some(weights) = &self.weights {
+            weights.sample()
+        } else {
+            self.rng.usize(..self.non_ignored_variant_count)
+        }
+    }
 }
 
 const INITIAL_MUTATION_STEP: usize = 1;
@@ -101,7 +137,12 @@ where
         if *step < self.non_ignored_variant_count {
             let old_step = *step;
             *step += 1;
-            Some((T::from_variant_index(old_step), self.cplx))
+            let item_idx = if self.weights.is_some() {
+                self.variants_by_weight[old_step]
+            } else {
+                old_step
+            };
+            Some((T::from_variant_index(item_idx), self.cplx))
         } else {
             None
         }
@@ -110,7 +151,7 @@ where
     #[doc(hidden)]
     #[no_coverage]
     fn random_arbitrary(&self, _max_cplx: f64) -> (T, f64) {
-        let item_idx = self.rng.usize(..self.non_ignored_variant_count);
+        let item_idx = self.random_variant_index();
         (T::from_variant_index(item_idx), self.cplx)
     }
 
@@ -127,6 +168,20 @@ where
         if max_cplx < <Self as Mutator<T>>::min_complexity(self) {
             return None;
         }
+        if self.weights.is_some() {
+            // starts at step = 1
+            // try the other variants in order of decreasing weight
+            let old_index = value.get_variant_index();
+            while *step <= self.non_ignored_variant_count {
+                let item_idx = self.variants_by_weight[*step - 1];
+                *step += 1;
+                if item_idx != old_index {
+                    *value = T::from_variant_index(item_idx);
+                    return Some((old_index, self.cplx));
+                }
+            }
+            return None;
+        }
         // starts at step = 1
         // create new from (get_item_index + step) % nbr_of_items
         if *step < self.non_ignored_variant_count {
@@ -144,7 +199,7 @@ where
     #[no_coverage]
     fn random_mutate(&self, value: &mut T, _cache: &mut Self::Cache, _max_cplx: f64) -> (Self::UnmutateToken, f64) {
         let old_index = value.get_variant_index();
-        let item_idx = self.rng.usize(..self.non_ignored_variant_count);
+        let item_idx = self.random_variant_index();
         *value = T::from_variant_index(item_idx);
         (old_index, self.cplx)
     }
@@ -0,0 +1,440 @@
+use std::any::Any;
+
+use crate::mutators::integer::{U32Mutator, U64Mutator};
+use crate::{DefaultMutator, Mutator};
+
+/*
+    The float mutators mutate the values in the following order:
+    1. small perturbations of the original value: sign flip, next/previous representable float,
+       addition/subtraction of 1, multiplication/division by 2, rounding
+    2. special values that often trigger edge cases: ±0, ±1, NaN, ±∞, MIN/MAX, EPSILON, subnormals, etc.
+    3. flipping each bit of the exponent, and then each bit of the mantissa
+    4. arbitrary bit patterns, which are generated by the integer mutator of the same size
+       and therefore do not repeat themselves
+
+    The arbitrary floats start with the special values, followed by the other arbitrary bit patterns.
+*/
+
+const INITIAL_MUTATION_STEP: u64 = 0;
+pub(crate) const NBR_PERTURBATIONS: u64 = 8;
+
+macro_rules! impl_float_helpers {
+    ($name:ident, $bits:ident, $special_values:ident, $nth_mutation:ident, $next_up:ident, $next_down:ident, $to_ordered_bits:ident, $from_ordered_bits:ident) => {
+        /// Special values that are often the source of edge cases
+        #[no_coverage]
+        pub(crate) fn $special_values() -> [$name; 17] {
+            let min_positive_subnormal = <$name>::from_bits(1);
+            let max_subnormal = <$name>::from_bits(<$name>::MIN_POSITIVE.to_bits() - 1);
+            [
+                0.0,
+                -0.0,
+                1.0,
+                -1.0,
+                0.5,
+                2.0,
+                <$name>::NAN,
+                <$name>::INFINITY,
+                <$name>::NEG_INFINITY,
+                <$name>::MAX,
+                <$name>::MIN,
+                <$name>::EPSILON,
+                <$name>::MIN_POSITIVE,
+                -<$name>::MIN_POSITIVE,
+                min_positive_subnormal,
+                -min_positive_subnormal,
+                max_subnormal,
+            ]
+        }
+
+        /// The smallest float that is greater than `x`
+        #[no_coverage]
+        pub(crate) fn $next_up(x: $name) -> $name {
+            if x.is_nan() || x == <$name>::INFINITY {
+                x
+            } else if x == 0.0 {
+                <$name>::from_bits(1)
+            } else if x > 0.0 {
+                <$name>::from_bits(x.to_bits() + 1)
+            } else {
+                <$name>::from_bits(x.to_bits() - 1)
+            }
+        }
+
+        /// The largest float that is smaller than `x`
+        #[no_coverage]
+        pub(crate) fn $next_down(x: $name) -> $name {
+            -$next_up(-x)
+        }
+
+        /// Maps the non-NaN floats to integers, preserving their order, with -0.0 placed just before 0.0
+        #[no_coverage]
+        pub(crate) fn $to_ordered_bits(x: $name) -> $bits {
+            let bits = x.to_bits();
+            if bits >> (<$bits>::BITS - 1) == 1 {
+                !bits
+            } else {
+                bits | (1 << (<$bits>::BITS - 1))
+            }
+        }
+
+        /// The inverse of the function above
+        #[no_coverage]
+        pub(crate) fn $from_ordered_bits(bits: $bits) -> $name {
+            if bits >> (<$bits>::BITS - 1) == 1 {
+                <$name>::from_bits(bits & !(1 << (<$bits>::BITS - 1)))
+            } else {
+                <$name>::from_bits(!bits)
+            }
+        }
+
+        /// The result of the structured mutation of `value` at the given step, or `None` if all of them were tried.
+        ///
+        /// The mutations are the perturbations of the value, followed by the special values, and then
+        /// the flip of each bit of the exponent and of the mantissa. The result may be equal to `value`,
+        /// for example when rounding an integer, so the callers must skip these mutations.
+        #[no_coverage]
+        pub(crate) fn $nth_mutation(value: $name, step: u64) -> Option<$name> {
+            if step < NBR_PERTURBATIONS {
+                return Some(match step {
+                    0 => -value,
+                    1 => $next_up(value),
+                    2 => $next_down(value),
+                    3 => value + 1.0,
+                    4 => value - 1.0,
+                    5 => value * 2.0,
+                    6 => value / 2.0,
+                    _ => value.round(),
+                });
+            }
+            let step = step - NBR_PERTURBATIONS;
+            let special_values = $special_values();
+            if (step as usize) < special_values.len() {
+                return Some(special_values[step as usize]);
+            }
+            let step = step - special_values.len() as u64;
+            // the sign bit is not included, since flipping it is the first perturbation
+            if step < (<$bits>::BITS - 1) as u64 {
+                let bit = <$bits>::BITS - 2 - step as u32;
+                Some(<$name>::from_bits(value.to_bits() ^ (1 << bit)))
+            } else {
+                None
+            }
+        }
+    };
+}
+
+impl_float_helpers!(
+    f32,
+    u32,
+    special_values_f32,
+    nth_mutation_f32,
+    next_up_f32,
+    next_down_f32,
+    to_ordered_bits_f32,
+    from_ordered_bits_f32
+);
+impl_float_helpers!(
+    f64,
+    u64,
+    special_values_f64,
+    nth_mutation_f64,
+    next_up_f64,
+    next_down_f64,
+    to_ordered_bits_f64,
+    from_ordered_bits_f64
+);
+
+macro_rules! impl_float_mutator {
+    ($name:ident, $bits:ident, $name_mutator:ident, $bits_mutator:ident, $special_values:ident, $nth_mutation:ident) => {
+        /// The default mutator of floats.
+        ///
+        /// It mutates the values structurally: it tries small perturbations, special values
+        /// such as NaN, infinities, and subnormals, and edits of the exponent and mantissa bits
+        /// before trying arbitrary bit patterns.
+        #[derive(Clone)]
+        pub struct $name_mutator {
+            bits_mutator: $bits_mutator,
+            rng: fastrand::Rng,
+        }
+        impl Default for $name_mutator {
+            #[no_coverage]
+            fn default() -> Self {
+                Self {
+                    bits_mutator: <_>::default(),
+                    rng: fastrand::Rng::default(),
+                }
+            }
+        }
+
+        impl $name_mutator {
+            /// Returns the arbitrary bit pattern at the given step, or `None` if all of them were generated
+            #[no_coverage]
+            fn nth_bit_pattern(&self, step: u64) -> Option<$name> {
+                let mut step = step;
+                self.bits_mutator
+                    .ordered_arbitrary(&mut step, <$bits>::BITS as f64)
+                    .map(
+                        #[no_coverage]
+                        |(bits, _)| <$name>::from_bits(bits),
+                    )
+            }
+        }
+
+        impl Mutator<$name> for $name_mutator {
+            #[doc(hidden)]
+            type Cache = ();
+            #[doc(hidden)]
+            type MutationStep = u64; // mutation step
+            #[doc(hidden)]
+            type ArbitraryStep = u64;
+            #[doc(hidden)]
+            type UnmutateToken = $name; // old value
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+                0
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn is_valid(&self, _value: &$name) -> bool {
+                true
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn validate_value(&self, _value: &$name) -> Option<Self::Cache> {
+                Some(())
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn default_mutation_step(&self, _value: &$name, _cache: &Self::Cache) -> Self::MutationStep {
+                INITIAL_MUTATION_STEP
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn global_search_space_complexity(&self) -> f64 {
+                <$bits>::BITS as f64
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn max_complexity(&self) -> f64 {
+                <$bits>::BITS as f64
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn min_complexity(&self) -> f64 {
+                <$bits>::BITS as f64
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn complexity(&self, _value: &$name, _cache: &Self::Cache) -> f64 {
+                <$bits>::BITS as f64
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<($name, f64)> {
+                if max_cplx < self.min_complexity() {
+                    return None;
+                }
+                let special_values = $special_values();
+                loop {
+                    if (*step as usize) < special_values.len() {
+                        let value = special_values[*step as usize];
+                        *step += 1;
+                        return Some((value, <$bits>::BITS as f64));
+                    }
+                    let value = self.nth_bit_pattern(*step - special_values.len() as u64)?;
+                    *step += 1;
+                    // the special values were already generated
+                    if !special_values.iter().any(
+                        #[no_coverage]
+                        |x| x.to_bits() == value.to_bits(),
+                    ) {
+                        return Some((value, <$bits>::BITS as f64));
+                    }
+                }
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn random_arbitrary(&self, _max_cplx: f64) -> ($name, f64) {
+                let value = match self.rng.u8(..4) {
+                    0 => {
+                        let special_values = $special_values();
+                        special_values[self.rng.usize(..special_values.len())]
+                    }
+                    1 => {
+                        let x = self.rng.$name();
+                        if self.rng.bool() {
+                            x
+                        } else {
+                            -x
+                        }
+                    }
+                    2 => self.rng.i32(-1000..=1000) as $name,
+                    _ => <$name>::from_bits(self.rng.$bits(..)),
+                };
+                (value, <$bits>::BITS as f64)
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn ordered_mutate(
+                &self,
+                value: &mut $name,
+                _cache: &mut Self::Cache,
+                step: &mut Self::MutationStep,
+                _subvalue_provider: &dyn crate::SubValueProvider,
+                max_cplx: f64,
+            ) -> Option<(Self::UnmutateToken, f64)> {
+                if max_cplx < self.min_complexity() {
+                    return None;
+                }
+                let nbr_structured_mutations =
+                    NBR_PERTURBATIONS + $special_values().len() as u64 + (<$bits>::BITS - 1) as u64;
+                loop {
+                    let new_value = if *step < nbr_structured_mutations {
+                        $nth_mutation(*value, *step).unwrap()
+                    } else {
+                        self.nth_bit_pattern(*step - nbr_structured_mutations)?
+                    };
+                    *step += 1;
+                    // e.g. rounding an integer, or the special value that is equal to the current one
+                    if new_value.to_bits() != value.to_bits() {
+                        return Some((std::mem::replace(value, new_value), <$bits>::BITS as f64));
+                    }
+                }
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn random_mutate(
+                &self,
+                value: &mut $name,
+                _cache: &mut Self::Cache,
+                max_cplx: f64,
+            ) -> (Self::UnmutateToken, f64) {
+                loop {
+                    let new_value = match self.rng.u8(..4) {
+                        0 => $nth_mutation(*value, self.rng.u64(..NBR_PERTURBATIONS)).unwrap(),
+                        1 => {
+                            let special_values = $special_values();
+                            special_values[self.rng.usize(..special_values.len())]
+                        }
+                        2 => <$name>::from_bits(value.to_bits() ^ (1 << self.rng.u32(..<$bits>::BITS - 1))),
+                        _ => self.random_arbitrary(max_cplx).0,
+                    };
+                    if new_value.to_bits() != value.to_bits() {
+                        return (std::mem::replace(value, new_value), <$bits>::BITS as f64);
+                    }
+                }
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn unmutate(&self, value: &mut $name, _cache: &mut Self::Cache, t: Self::UnmutateToken) {
+                *value = t;
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn visit_subvalues<'a>(
+                &self,
+                _value: &'a $name,
+                _cache: &'a Self::Cache,
+                _visit: &mut dyn FnMut(&'a dyn Any, f64),
+            ) {
+            }
+        }
+
+        impl DefaultMutator for $name {
+            type Mutator = $name_mutator;
+            #[no_coverage]
+            fn default_mutator() -> Self::Mutator {
+                <$name_mutator>::default()
+            }
+        }
+    };
+}
+
+impl_float_mutator!(f32, u32, F32Mutator, U32Mutator, special_values_f32, nth_mutation_f32);
+impl_float_mutator!(f64, u64, F64Mutator, U64Mutator, special_values_f64, nth_mutation_f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::subvalue_provider::EmptySubValueProvider;
+
+    #[test]
+    fn test_ordered_bits() {
+        let values = [
+            f32::NEG_INFINITY,
+            f32::MIN,
+            -1.0,
+            -f32::from_bits(1),
+            -0.0,
+            0.0,
+            f32::from_bits(1),
+            1.0,
+            f32::MAX,
+            f32::INFINITY,
+        ];
+        for w in values.windows(2) {
+            assert!(to_ordered_bits_f32(w[0]) < to_ordered_bits_f32(w[1]));
+        }
+        for x in values {
+            assert_eq!(from_ordered_bits_f32(to_ordered_bits_f32(x)).to_bits(), x.to_bits());
+        }
+        assert_eq!(to_ordered_bits_f32(-0.0) + 1, to_ordered_bits_f32(0.0));
+        assert_eq!(next_up_f64(1.0), 1.0 + f64::EPSILON);
+        assert_eq!(next_down_f64(0.0), -f64::from_bits(1));
+        assert_eq!(next_up_f64(-f64::from_bits(1)), -0.0);
+    }
+
+    #[test]
+    fn test_float_mutator() {
+        let m = f64::default_mutator();
+        let mut step = m.default_arbitrary_step();
+        let arbitrary = (0..100)
+            .map(|_| m.ordered_arbitrary(&mut step, 64.0).unwrap().0.to_bits())
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(arbitrary.len(), 100);
+
+        let mut value = 3.0;
+        m.validate_value(&value).unwrap();
+        let mut step = m.default_mutation_step(&value, &());
+        let mut mutations = vec![];
+        for _ in 0..1000 {
+            let (token, _) = m
+                .ordered_mutate(&mut value, &mut (), &mut step, &EmptySubValueProvider, 64.0)
+                .unwrap();
+            mutations.push(value);
+            m.unmutate(&mut value, &mut (), token);
+            assert_eq!(value, 3.0);
+        }
+        // rounding 3.0 is skipped since it does not change the value
+        assert_eq!(
+            &mutations[..8],
+            &[-3.0, next_up_f64(3.0), next_down_f64(3.0), 4.0, 2.0, 6.0, 1.5, 0.0]
+        );
+        assert!(mutations.iter().all(|x| x.to_bits() != 3f64.to_bits()));
+        assert!(mutations.iter().any(|x| x.is_nan()));
+        assert!(mutations.contains(&f64::INFINITY));
+        assert!(mutations.contains(&f64::from_bits(1)));
+        // flipping the highest bit of the exponent
+        assert!(mutations.contains(&f64::from_bits(3f64.to_bits() ^ (1 << 62))));
+
+        for special_value in special_values_f64() {
+            let mut value = special_value;
+            let mut step = m.default_mutation_step(&value, &());
+            for _ in 0..100 {
+                let (token, _) = m
+                    .ordered_mutate(&mut value, &mut (), &mut step, &EmptySubValueProvider, 64.0)
+                    .unwrap();
+                assert_ne!(value.to_bits(), special_value.to_bits());
+                m.unmutate(&mut value, &mut (), token);
+                let (token, _) = m.random_mutate(&mut value, &mut (), 64.0);
+                assert_ne!(value.to_bits(), special_value.to_bits());
+                m.unmutate(&mut value, &mut (), token);
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::any::Any;
+use std::ops::{Bound, RangeBounds};
+
+use crate::mutators::float::{
+    from_ordered_bits_f32, from_ordered_bits_f64, next_down_f32, next_down_f64, next_up_f32, next_up_f64,
+    nth_mutation_f32, nth_mutation_f64, special_values_f32, special_values_f64, to_ordered_bits_f32,
+    to_ordered_bits_f64, NBR_PERTURBATIONS,
+};
+use crate::mutators::integer::{binary_search_arbitrary_u32, binary_search_arbitrary_u64};
+use crate::mutators::DefaultMutatorWithinRange;
+use crate::Mutator;
+const INITIAL_MUTATION_STEP: u64 = 0;
+
+/*
+    The floats of the range are mapped to a range of integers, preserving their order (see `to_ordered_bits`).
+    The arbitrary values are then chosen by a binary search in that range of integers, which spreads them
+    across all orders of magnitude contained in the range.
+
+    The mutations are the same as those of the default float mutators, except that the values outside of
+    the range are skipped, and that they are followed by the arbitrary values instead of arbitrary bit patterns.
+*/
+
+macro_rules! impl_float_mutator_constrained {
+    ($name:ident, $bits:ident, $name_mutator:ident, $binary_arbitrary:ident, $special_values:ident, $nth_mutation:ident, $next_up:ident, $next_down:ident, $to_ordered_bits:ident, $from_ordered_bits:ident) => {
+        /// A mutator for floats within a given range.
+        ///
+        /// NaN is never part of the range.
+        pub struct $name_mutator {
+            start_range: $name,
+            end_range: $name,
+            start_bits: $bits,
+            len_range: $bits,
+            search_space_complexity: f64,
+            rng: fastrand::Rng,
+        }
+        impl $name_mutator {
+            #[no_coverage]
+            pub fn new<RB: RangeBounds<$name>>(range: RB) -> Self {
+                let start = match range.start_bound() {
+                    Bound::Included(b) => *b,
+                    Bound::Excluded(b) => $next_up(*b),
+                    Bound::Unbounded => <$name>::NEG_INFINITY,
+                };
+                let end = match range.end_bound() {
+                    Bound::Included(b) => *b,
+                    Bound::Excluded(b) => $next_down(*b),
+                    Bound::Unbounded => <$name>::INFINITY,
+                };
+                if start.is_nan() || end.is_nan() || start > end {
+                    panic!(
+                        "You have provided a float range where the value of the start of the range \
+                        is larger than the end of the range, or which contains NaN!\nRange start: {:#?}\nRange end: {:#?}",
+                        range.start_bound(),
+                        range.end_bound()
+                    )
+                }
+                let start_bits = $to_ordered_bits(start);
+                let len_range = $to_ordered_bits(end) - start_bits;
+                Self {
+                    start_range: start,
+                    end_range: end,
+                    start_bits,
+                    len_range,
+                    search_space_complexity: super::size_to_cplxity(len_range as usize),
+                    rng: fastrand::Rng::default(),
+                }
+            }
+
+            /// The arbitrary value at the given step, or `None` if all the values of the range were generated
+            #[no_coverage]
+            fn nth_arbitrary(&self, step: u64) -> Option<$name> {
+                if step > self.len_range as u64 {
+                    None
+                } else {
+                    let bits = $binary_arbitrary(0, self.len_range, step);
+                    Some($from_ordered_bits(self.start_bits + bits))
+                }
+            }
+
+            #[no_coverage]
+            fn random_value(&self) -> $name {
+                $from_ordered_bits(self.start_bits + self.rng.$bits(..=self.len_range))
+            }
+        }
+
+        impl Mutator<$name> for $name_mutator {
+            #[doc(hidden)]
+            type Cache = ();
+            #[doc(hidden)]
+            type MutationStep = u64; // mutation step
+            #[doc(hidden)]
+            type ArbitraryStep = u64;
+            #[doc(hidden)]
+            type UnmutateToken = $name; // old value
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+                0
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn is_valid(&self, value: &$name) -> bool {
+                (self.start_range..=self.end_range).contains(value)
+            }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn validate_value(&self, value: &$name) -> Option<Self::Cache> {
+                if self.is_valid(value) {
+                    Some(())
+                } else {
+                    None
+                }
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn default_mutation_step(&self, _value: &$name, _cache: &Self::Cache) -> Self::MutationStep {
+                INITIAL_MUTATION_STEP
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn global_search_space_complexity(&self) -> f64 {
+                self.search_space_complexity
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn max_complexity(&self) -> f64 {
+                <$bits>::BITS as f64
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn min_complexity(&self) -> f64 {
+                <$bits>::BITS as f64
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn complexity(&self, _value: &$name, _cache: &Self::Cache) -> f64 {
+                <$bits>::BITS as f64
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<($name, f64)> {
+                if max_cplx < self.min_complexity() {
+                    return None;
+                }
+                let special_values = $special_values();
+                loop {
+                    let value = if (*step as usize) < special_values.len() {
+                        special_values[*step as usize]
+                    } else {
+                        let value = self.nth_arbitrary(*step - special_values.len() as u64)?;
+                        // the special values of the range were already generated
+                        if special_values.iter().any(
+                            #[no_coverage]
+                            |x| x.to_bits() == value.to_bits(),
+                        ) {
+                            *step += 1;
+                            continue;
+                        }
+                        value
+                    };
+                    *step += 1;
+                    if self.is_valid(&value) {
+                        return Some((value, <$bits>::BITS as f64));
+                    }
+                }
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn random_arbitrary(&self, _max_cplx: f64) -> ($name, f64) {
+                (self.random_value(), <$bits>::BITS as f64)
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn ordered_mutate(
+                &self,
+                value: &mut $name,
+                _cache: &mut Self::Cache,
+                step: &mut Self::MutationStep,
+                _subvalue_provider: &dyn crate::SubValueProvider,
+                max_cplx: f64,
+            ) -> Option<(Self::UnmutateToken, f64)> {
+                if max_cplx < self.min_complexity() {
+                    return None;
+                }
+                let nbr_structured_mutations =
+                    NBR_PERTURBATIONS + $special_values().len() as u64 + (<$bits>::BITS - 1) as u64;
+                loop {
+                    let new_value = if *step < nbr_structured_mutations {
+                        $nth_mutation(*value, *step).unwrap()
+                    } else {
+                        self.nth_arbitrary(*step - nbr_structured_mutations)?
+                    };
+                    *step += 1;
+                    if new_value.to_bits() != value.to_bits() && self.is_valid(&new_value) {
+                        return Some((std::mem::replace(value, new_value), <$bits>::BITS as f64));
+                    }
+                }
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn random_mutate(
+                &self,
+                value: &mut $name,
+                _cache: &mut Self::Cache,
+                _max_cplx: f64,
+            ) -> (Self::UnmutateToken, f64) {
+                let perturbation = $nth_mutation(*value, self.rng.u64(..NBR_PERTURBATIONS)).unwrap();
+                let new_value = if self.rng.bool() && self.is_valid(&perturbation) {
+                    perturbation
+                } else {
+                    self.random_value()
+                };
+                (std::mem::replace(value, new_value), <$bits>::BITS as f64)
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn unmutate(&self, value: &mut $name, _cache: &mut Self::Cache, t: Self::UnmutateToken) {
+                *value = t;
+            }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn visit_subvalues<'a>(
+                &self,
+                _value: &'a $name,
+                _cache: &'a Self::Cache,
+                _visit: &mut dyn FnMut(&'a dyn Any, f64),
+            ) {
+            }
+        }
+        impl DefaultMutatorWithinRange for $name {
+            type Mutator = $name_mutator;
+            #[no_coverage]
+            fn default_mutator_within_range<RB: RangeBounds<$name>>(range: RB) -> Self::Mutator {
+                $name_mutator::new(range)
+            }
+        }
+    };
+}
+
+impl_float_mutator_constrained!(
+    f32,
+    u32,
+    F32WithinRangeMutator,
+    binary_search_arbitrary_u32,
+    special_values_f32,
+    nth_mutation_f32,
+    next_up_f32,
+    next_down_f32,
+    to_ordered_bits_f32,
+    from_ordered_bits_f32
+);
+impl_float_mutator_constrained!(
+    f64,
+    u64,
+    F64WithinRangeMutator,
+    binary_search_arbitrary_u64,
+    special_values_f64,
+    nth_mutation_f64,
+    next_up_f64,
+    next_down_f64,
+    to_ordered_bits_f64,
+    from_ordered_bits_f64
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{F32WithinRangeMutator, F64WithinRangeMutator};
+    use crate::subvalue_provider::EmptySubValueProvider;
+    use crate::Mutator;
+
+    #[test]
+    fn test_float_constrained() {
+        let m = F32WithinRangeMutator::new(-1.0..2.5);
+        assert!(m.is_valid(&-1.0));
+        assert!(m.is_valid(&2.4999998));
+        assert!(!m.is_valid(&2.5));
+        assert!(!m.is_valid(&f32::NAN));
+
+        let m = F64WithinRangeMutator::new(1.0..=1e10);
+        let mut step = m.default_arbitrary_step();
+        for _ in 0..1000 {
+            let (x, _) = m.ordered_arbitrary(&mut step, 64.0).unwrap();
+            assert!((1.0..=1e10).contains(&x));
+        }
+        let mut value = 5.0;
+        m.validate_value(&value).unwrap();
+        let mut step = m.default_mutation_step(&value, &());
+        for _ in 0..1000 {
+            let (token, _) = m
+                .ordered_mutate(&mut value, &mut (), &mut step, &EmptySubValueProvider, 64.0)
+                .unwrap();
+            assert!((1.0..=1e10).contains(&value));
+            m.unmutate(&mut value, &mut (), token);
+            let _ = m.random_mutate(&mut value, &mut (), 64.0);
+            assert!((1.0..=1e10).contains(&value));
+        }
+    }
+
+    #[test]
+    fn test_float_single_value_range() {
+        let m = F64WithinRangeMutator::new(0.5..=0.5);
+        let mut step = m.default_arbitrary_step();
+        assert_eq!(m.ordered_arbitrary(&mut step, 64.0), Some((0.5, 64.0)));
+        assert_eq!(m.ordered_arbitrary(&mut step, 64.0), None);
+    }
+}
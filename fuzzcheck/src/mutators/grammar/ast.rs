@@ -20,6 +20,60 @@ pub enum AST {
     Token(char),
     #[doc(hidden)]
     Sequence(Vec<AST>),
+    #[doc(hidden)]
+    Rule { rule: String, ast: Box<AST> },
+}
+
+/// A visitor of the nodes of an [`AST`] that were produced by a named grammar rule.
+///
+/// Grammar rules are named with [`rule`](crate::mutators::grammar::rule), or automatically when the grammar
+/// is created from a grammar file, for example with [`GrammarRules`](crate::mutators::grammar::GrammarRules).
+///
+/// ```
+/// use fuzzcheck::mutators::grammar::*;
+/// use fuzzcheck::Mutator;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Rule {
+///     Number,
+///     Digit,
+/// }
+///
+/// struct DigitCounter(usize);
+///
+/// impl ASTVisitor for DigitCounter {
+///     type Rule = Rule;
+///     fn rule_from_name(&self, name: &str) -> Option<Rule> {
+///         match name {
+///             "number" => Some(Rule::Number),
+///             "digit" => Some(Rule::Digit),
+///             _ => None,
+///         }
+///     }
+///     fn visit(&mut self, rule: Rule, _ast: &AST) -> bool {
+///         if rule == Rule::Digit {
+///             self.0 += 1;
+///         }
+///         true
+///     }
+/// }
+///
+/// let digit = rule("digit", literal_range('0'..='9'));
+/// let number = rule("number", concatenation([literal('-'), repetition(digit, 1..)]));
+/// let mutator = grammar_based_ast_mutator(number);
+///
+/// let (ast, _) = mutator.random_arbitrary(20.0);
+/// let mut counter = DigitCounter(0);
+/// ast.visit(&mut counter);
+/// assert_eq!(counter.0, ast.to_string().len() - 1);
+/// ```
+pub trait ASTVisitor {
+    /// The type identifying the rules of the grammar, typically an enum with one variant per rule
+    type Rule;
+    /// Identify a rule from its name, or return `None` to skip the node and only visit its children
+    fn rule_from_name(&self, name: &str) -> Option<Self::Rule>;
+    /// Visit a node produced by the given rule, and return whether its children should be visited as well
+    fn visit(&mut self, rule: Self::Rule, ast: &AST) -> bool;
 }
 
 impl AST {
@@ -34,6 +88,60 @@ impl AST {
                     ast.generate_string_in(string);
                 }
             }
+            AST::Rule { ast, .. } => {
+                ast.generate_string_in(string);
+            }
+        }
+    }
+
+    /// Append the bytes of the syntax tree to the vector, where each character is converted to the byte of
+    /// the same value
+    #[no_coverage]
+    pub fn generate_bytes_in(&self, bytes: &mut Vec<u8>) {
+        match self {
+            AST::Token(c) => {
+                bytes.push(*c as u8);
+            }
+            AST::Sequence(asts) => {
+                for ast in asts {
+                    ast.generate_bytes_in(bytes);
+                }
+            }
+            AST::Rule { ast, .. } => {
+                ast.generate_bytes_in(bytes);
+            }
+        }
+    }
+
+    /// The name of the grammar rule that produced this node, if it was named
+    #[no_coverage]
+    pub fn rule(&self) -> Option<&str> {
+        match self {
+            AST::Rule { rule, .. } => Some(rule),
+            _ => None,
+        }
+    }
+
+    /// Visit the nodes of the syntax tree produced by named grammar rules, in depth-first order
+    #[no_coverage]
+    pub fn visit<V: ASTVisitor>(&self, visitor: &mut V) {
+        match self {
+            AST::Token(_) => {}
+            AST::Sequence(asts) => {
+                for ast in asts {
+                    ast.visit(visitor);
+                }
+            }
+            AST::Rule { rule, ast } => {
+                let visit_children = if let Some(rule) = visitor.rule_from_name(rule) {
+                    visitor.visit(rule, self)
+                } else {
+                    true
+                };
+                if visit_children {
+                    ast.visit(visitor);
+                }
+            }
         }
     }
 
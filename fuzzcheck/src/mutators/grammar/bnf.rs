@@ -0,0 +1,1345 @@
+//! Parsers for grammars written in EBNF or ABNF notation.
+//!
+//! A grammar file is parsed into [`GrammarRules`], a list of named rules which may refer to each other,
+//! possibly recursively. Each rule can then be converted to a [`Grammar`] used by the grammar-based mutators.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::rc::{Rc, Weak};
+
+use super::grammar::Grammar;
+use crate::mutators::vector::LengthDistribution;
+
+/// An error encountered while parsing a grammar.
+///
+/// The line and column, which both start at 1, locate the error in the source of the grammar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrammarParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+impl fmt::Display for GrammarParseError {
+    #[no_coverage]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+impl std::error::Error for GrammarParseError {}
+
+/// A grammar expression, before the rules it refers to are resolved
+#[derive(Clone, Debug)]
+pub(crate) enum Expr {
+    Literal(Vec<RangeInclusive<char>>),
+    Reference {
+        name: String,
+        line: usize,
+        column: usize,
+    },
+    Alternation(Vec<Expr>),
+    Concatenation(Vec<Expr>),
+    /// The end of the range is exclusive, and is `usize::MAX` if the repetition is unbounded
+    Repetition(Box<Expr>, Range<usize>),
+}
+
+impl Expr {
+    #[no_coverage]
+    pub(crate) fn string(s: &str) -> Self {
+        let mut chars = s
+            .chars()
+            .map(
+                #[no_coverage]
+                |c| Expr::Literal(vec![c..=c]),
+            )
+            .collect::<Vec<_>>();
+        if chars.len() == 1 {
+            chars.pop().unwrap()
+        } else {
+            Expr::Concatenation(chars)
+        }
+    }
+
+    /// A string whose ASCII letters match both their lowercase and uppercase versions
+    #[no_coverage]
+    pub(crate) fn case_insensitive_string(s: &str) -> Self {
+        let mut chars = s
+            .chars()
+            .map(
+                #[no_coverage]
+                |c| {
+                    let (lower, upper) = (c.to_ascii_lowercase(), c.to_ascii_uppercase());
+                    if lower == upper {
+                        Expr::Literal(vec![c..=c])
+                    } else {
+                        Expr::Literal(vec![lower..=lower, upper..=upper])
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+        if chars.len() == 1 {
+            chars.pop().unwrap()
+        } else {
+            Expr::Concatenation(chars)
+        }
+    }
+
+    #[no_coverage]
+    fn visit_references(&self, visit: &mut impl FnMut(&str, usize, usize)) {
+        match self {
+            Expr::Literal(_) => {}
+            Expr::Reference { name, line, column } => visit(name, *line, *column),
+            Expr::Alternation(es) | Expr::Concatenation(es) => {
+                for e in es {
+                    e.visit_references(visit);
+                }
+            }
+            Expr::Repetition(e, _) => e.visit_references(visit),
+        }
+    }
+}
+
+/// The named rules of a grammar, parsed from a grammar file.
+///
+/// ```
+/// use fuzzcheck::mutators::grammar::{grammar_based_ast_mutator, GrammarRules};
+///
+/// let rules = GrammarRules::from_ebnf(
+///     r#"
+///     expr   = term, { ("+" | "-"), term } ;
+///     term   = factor, { ("*" | "/"), factor } ;
+///     factor = number | "(", expr, ")" ;
+///     number = digit, { digit } ;
+///     digit  = '0' .. '9' ;
+///     "#,
+/// )
+/// .unwrap();
+/// let mutator = grammar_based_ast_mutator(rules.grammar("expr").unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct GrammarRules {
+    names: Vec<String>,
+    exprs: Vec<Expr>,
+    /// For each rule that is a variant of another rule, the name of that other rule. These variants are
+    /// implementation details of the grammar, which are not listed by `rule_names`.
+    variant_of: Vec<Option<String>>,
+    indices: HashMap<String, usize>,
+    case_insensitive: bool,
+}
+
+impl GrammarRules {
+    #[no_coverage]
+    pub(crate) fn new(case_insensitive: bool) -> Self {
+        Self {
+            names: vec![],
+            exprs: vec![],
+            variant_of: vec![],
+            indices: HashMap::new(),
+            case_insensitive,
+        }
+    }
+
+    #[no_coverage]
+    fn key(&self, name: &str) -> String {
+        if self.case_insensitive {
+            name.to_ascii_lowercase()
+        } else {
+            name.to_owned()
+        }
+    }
+
+    #[no_coverage]
+    fn index(&self, name: &str) -> Option<usize> {
+        self.indices.get(&self.key(name)).copied()
+    }
+
+    /// Add a rule, returning `false` if a rule with the same name already exists
+    #[no_coverage]
+    pub(crate) fn add_rule(&mut self, name: &str, expr: Expr) -> bool {
+        self.add_rule_with_variant_of(name, expr, None)
+    }
+
+    /// Add a variant of the rule `variant_of`, which is not listed by [`rule_names`](GrammarRules::rule_names)
+    /// and whose syntax trees are annotated with the name of `variant_of`
+    #[no_coverage]
+    #[cfg(feature = "pest_grammar")]
+    pub(crate) fn add_variant_rule(&mut self, name: &str, variant_of: &str, expr: Expr) -> bool {
+        self.add_rule_with_variant_of(name, expr, Some(variant_of.to_owned()))
+    }
+
+    #[no_coverage]
+    fn add_rule_with_variant_of(&mut self, name: &str, expr: Expr, variant_of: Option<String>) -> bool {
+        let key = self.key(name);
+        if self.indices.contains_key(&key) {
+            return false;
+        }
+        self.indices.insert(key, self.names.len());
+        self.names.push(name.to_owned());
+        self.exprs.push(expr);
+        self.variant_of.push(variant_of);
+        true
+    }
+
+    /// Add the alternatives of `expr` to an existing rule, returning `false` if the rule does not exist
+    #[no_coverage]
+    fn extend_rule(&mut self, name: &str, expr: Expr) -> bool {
+        let idx = match self.index(name) {
+            Some(idx) => idx,
+            None => return false,
+        };
+        let alternatives = match std::mem::replace(&mut self.exprs[idx], Expr::Alternation(vec![])) {
+            Expr::Alternation(mut es) => {
+                es.push(expr);
+                es
+            }
+            e => vec![e, expr],
+        };
+        self.exprs[idx] = Expr::Alternation(alternatives);
+        true
+    }
+
+    /// Check that the grammar contains at least one rule and that all referenced rules exist
+    #[no_coverage]
+    pub(crate) fn check(&self) -> Result<(), GrammarParseError> {
+        if self.names.is_empty() {
+            return Err(GrammarParseError {
+                line: 1,
+                column: 1,
+                message: "the grammar does not contain any rule".to_owned(),
+            });
+        }
+        let mut result = Ok(());
+        for expr in &self.exprs {
+            expr.visit_references(&mut |name, line, column| {
+                if result.is_ok() && self.index(name).is_none() {
+                    result = Err(GrammarParseError {
+                        line,
+                        column,
+                        message: format!("the rule `{}` is not defined", name),
+                    });
+                }
+            });
+        }
+        result
+    }
+
+    /// Parse a grammar written in EBNF.
+    ///
+    /// A rule is written `name = expression ;`, where the `::=` sign can be used instead of `=`, and the
+    /// final semicolon can be omitted or replaced by a dot. Rule names are either identifiers, which may contain
+    /// dashes and underscores, or any text between angle brackets, e.g. `<rule name>`. Expressions are made of:
+    /// * strings between single or double quotes, e.g. `"abc"` or `'\n'`, with the escape sequences `\n`, `\r`,
+    ///   `\t`, `\0`, `\\`, `\"`, `\'` and `\u{..}`
+    /// * hexadecimal characters, such as `#x41`
+    /// * character ranges, such as `'a' .. 'z'`
+    /// * references to other rules, which may be defined later in the file
+    /// * concatenations, whose elements are separated by commas or whitespace
+    /// * alternations, whose elements are separated by `|`
+    /// * groups `( .. )`, options `[ .. ]`, and repetitions `{ .. }`
+    /// * the postfix operators `?`, `*`, and `+`, as well as the prefix operator `n *` for a repetition
+    ///   exactly `n` times
+    ///
+    /// Comments are written between `(*` and `*)`, between `/*` and `*/`, or after `//`.
+    /// Exceptions (`-`) and special sequences (`? .. ?`) are not supported.
+    #[no_coverage]
+    pub fn from_ebnf(source: &str) -> Result<Self, GrammarParseError> {
+        EbnfParser { c: Cursor::new(source) }.parse()
+    }
+
+    /// Parse a grammar written in ABNF, as specified by RFC 5234 and RFC 7405.
+    ///
+    /// Rule names are case-insensitive. The core rules, such as `ALPHA`, `DIGIT`, or `CRLF`, are
+    /// available when they are not defined by the grammar. Prose values (`< .. >`) are not supported.
+    #[no_coverage]
+    pub fn from_abnf(source: &str) -> Result<Self, GrammarParseError> {
+        let mut rules = AbnfParser { c: Cursor::new(source) }.parse()?;
+        rules.add_core_rules();
+        rules.check()?;
+        Ok(rules)
+    }
+
+    /// The names of the rules, in the order in which they are defined
+    #[no_coverage]
+    pub fn rule_names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().zip(self.variant_of.iter()).filter_map(
+            #[no_coverage]
+            |(name, variant_of)| variant_of.is_none().then_some(name.as_str()),
+        )
+    }
+
+    /// The grammar matching the given rule, or `None` if the rule does not exist
+    #[no_coverage]
+    pub fn grammar(&self, rule: &str) -> Option<Rc<Grammar>> {
+        let idx = self.index(rule)?;
+        Some(GrammarBuilder::new(self).rule(idx))
+    }
+
+    /// The grammar matching the first rule
+    #[no_coverage]
+    pub fn start_grammar(&self) -> Rc<Grammar> {
+        GrammarBuilder::new(self).rule(0)
+    }
+}
+
+/// Parse a grammar written in EBNF and return the grammar of its first rule.
+///
+/// See [`GrammarRules::from_ebnf`] for the supported syntax.
+#[no_coverage]
+pub fn ebnf(source: &str) -> Result<Rc<Grammar>, GrammarParseError> {
+    Ok(GrammarRules::from_ebnf(source)?.start_grammar())
+}
+
+/// Parse a grammar written in ABNF and return the grammar of its first rule.
+///
+/// See [`GrammarRules::from_abnf`] for the supported syntax.
+#[no_coverage]
+pub fn abnf(source: &str) -> Result<Rc<Grammar>, GrammarParseError> {
+    Ok(GrammarRules::from_abnf(source)?.start_grammar())
+}
+
+/*
+    Each rule is converted to a `Grammar::Rule` node, such that the syntax trees it generates are annotated with
+    its name. Converting the rules to a `Grammar` is not straightforward, because rules can be mutually recursive while
+    a `Grammar` can only refer to itself through a `Recurse` node pointing to a `Recursive` node.
+
+    So a rule that can reach itself is expanded only once, in a `Recursive` node, and all the other references to it
+    become `Recurse` nodes pointing to it, even the references that are not inside the `Recursive` node. The other
+    rules are expanded once as well, and their expansion is shared between all references to them. Expanding each rule
+    once keeps the size of the grammar linear in the number of rules, even when many rules are mutually recursive.
+*/
+struct GrammarBuilder<'a> {
+    rules: &'a GrammarRules,
+    /// Whether each rule can reach itself
+    is_recursive: Vec<bool>,
+    /// The inner grammar of the `Recursive` node of each recursive rule that was expanded
+    recursive: HashMap<usize, Weak<Grammar>>,
+    /// The expansion of each non-recursive rule
+    cache: HashMap<usize, Rc<Grammar>>,
+}
+
+impl<'a> GrammarBuilder<'a> {
+    #[no_coverage]
+    fn new(rules: &'a GrammarRules) -> Self {
+        let references = rules
+            .exprs
+            .iter()
+            .map(
+                #[no_coverage]
+                |expr| {
+                    let mut references = HashSet::new();
+                    expr.visit_references(&mut |name, _, _| {
+                        references.insert(rules.index(name).unwrap());
+                    });
+                    references
+                },
+            )
+            .collect::<Vec<_>>();
+        let is_recursive = (0..rules.names.len())
+            .map(
+                #[no_coverage]
+                |start| {
+                    let mut reachable = HashSet::new();
+                    let mut to_visit = references[start].iter().copied().collect::<Vec<_>>();
+                    while let Some(idx) = to_visit.pop() {
+                        if idx == start {
+                            return true;
+                        }
+                        if reachable.insert(idx) {
+                            to_visit.extend(references[idx].iter().copied());
+                        }
+                    }
+                    false
+                },
+            )
+            .collect();
+        Self {
+            rules,
+            is_recursive,
+            recursive: HashMap::new(),
+            cache: HashMap::new(),
+        }
+    }
+
+    #[no_coverage]
+    fn rule(&mut self, idx: usize) -> Rc<Grammar> {
+        if let Some(weak) = self.recursive.get(&idx) {
+            return Rc::new(Grammar::Recurse(weak.clone()));
+        }
+        if let Some(grammar) = self.cache.get(&idx) {
+            return grammar.clone();
+        }
+        let name = self.rules.variant_of[idx]
+            .as_ref()
+            .unwrap_or(&self.rules.names[idx])
+            .clone();
+        if self.is_recursive[idx] {
+            let inner = Rc::new_cyclic(
+                #[no_coverage]
+                |weak| {
+                    self.recursive.insert(idx, weak.clone());
+                    Grammar::Rule(name, self.expr(&self.rules.exprs[idx]))
+                },
+            );
+            Rc::new(Grammar::Recursive(inner))
+        } else {
+            let grammar = Rc::new(Grammar::Rule(name, self.expr(&self.rules.exprs[idx])));
+            self.cache.insert(idx, grammar.clone());
+            grammar
+        }
+    }
+
+    #[no_coverage]
+    fn expr(&mut self, expr: &Expr) -> Rc<Grammar> {
+        match expr {
+            Expr::Literal(ranges) => Rc::new(Grammar::Literal(ranges.clone())),
+            Expr::Reference { name, .. } => self.rule(self.rules.index(name).unwrap()),
+            Expr::Alternation(es) => {
+                let mut gs = Vec::with_capacity(es.len());
+                for e in es {
+                    gs.push(self.expr(e));
+                }
+                let weights = vec![1.0; gs.len()];
+                Rc::new(Grammar::Alternation(gs, weights))
+            }
+            Expr::Concatenation(es) if es.is_empty() => {
+                // the empty string
+                Rc::new(Grammar::Repetition(
+                    Rc::new(Grammar::Literal(vec!['\0'..='\0'])),
+                    0..1,
+                    LengthDistribution::Uniform,
+                ))
+            }
+            Expr::Concatenation(es) => {
+                let mut gs = Vec::with_capacity(es.len());
+                for e in es {
+                    gs.push(self.expr(e));
+                }
+                Rc::new(Grammar::Concatenation(gs))
+            }
+            Expr::Repetition(e, range) => Rc::new(Grammar::Repetition(
+                self.expr(e),
+                range.clone(),
+                LengthDistribution::Uniform,
+            )),
+        }
+    }
+}
+
+/// The source of a grammar, with the position of the next character to parse
+pub(crate) struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Cursor {
+    #[no_coverage]
+    pub(crate) fn new(source: &str) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            pos: 0,
+        }
+    }
+    #[no_coverage]
+    pub(crate) fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+    #[no_coverage]
+    pub(crate) fn peek_nth(&self, n: usize) -> Option<char> {
+        self.chars.get(self.pos + n).copied()
+    }
+    #[no_coverage]
+    pub(crate) fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+    #[no_coverage]
+    pub(crate) fn starts_with(&self, s: &str) -> bool {
+        let mut chars = self.chars[self.pos..].iter();
+        s.chars().all(
+            #[no_coverage]
+            |c| chars.next() == Some(&c),
+        )
+    }
+    #[no_coverage]
+    pub(crate) fn eat(&mut self, s: &str) -> bool {
+        if self.starts_with(s) {
+            self.pos += s.chars().count();
+            true
+        } else {
+            false
+        }
+    }
+    #[no_coverage]
+    pub(crate) fn take_while(&mut self, f: impl Fn(char) -> bool) -> String {
+        let mut s = String::new();
+        while let Some(c) = self.peek() {
+            if !f(c) {
+                break;
+            }
+            s.push(c);
+            self.pos += 1;
+        }
+        s
+    }
+    #[no_coverage]
+    pub(crate) fn pos(&self) -> usize {
+        self.pos
+    }
+    #[no_coverage]
+    pub(crate) fn line_column(&self, pos: usize) -> (usize, usize) {
+        let mut line = 1;
+        let mut column = 1;
+        for c in &self.chars[..pos.min(self.chars.len())] {
+            if *c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+    #[no_coverage]
+    pub(crate) fn error_at(&self, pos: usize, message: impl Into<String>) -> GrammarParseError {
+        let (line, column) = self.line_column(pos);
+        GrammarParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+    #[no_coverage]
+    pub(crate) fn error(&self, message: impl Into<String>) -> GrammarParseError {
+        self.error_at(self.pos, message)
+    }
+    #[no_coverage]
+    pub(crate) fn reference(&self, name: String, pos: usize) -> Expr {
+        let (line, column) = self.line_column(pos);
+        Expr::Reference { name, line, column }
+    }
+    /// A description of the next character, for error messages
+    #[no_coverage]
+    pub(crate) fn describe_next(&self) -> String {
+        match self.peek() {
+            Some(c) => format!("`{}`", c.escape_debug()),
+            None => "the end of the grammar".to_owned(),
+        }
+    }
+}
+
+struct EbnfParser {
+    c: Cursor,
+}
+
+impl EbnfParser {
+    #[no_coverage]
+    fn parse(mut self) -> Result<GrammarRules, GrammarParseError> {
+        let mut rules = GrammarRules::new(false);
+        loop {
+            self.skip_trivia()?;
+            if self.c.peek().is_none() {
+                break;
+            }
+            let pos = self.c.pos();
+            let name = self.identifier()?.ok_or_else(|| {
+                self.c
+                    .error(format!("expected a rule name, found {}", self.c.describe_next()))
+            })?;
+            self.skip_trivia()?;
+            if !(self.c.eat("::=") || self.c.eat("=")) {
+                return Err(self.c.error(format!(
+                    "expected `=` or `::=` after the name of the rule, found {}",
+                    self.c.describe_next()
+                )));
+            }
+            let expr = self.alternation()?;
+            self.skip_trivia()?;
+            if !self.c.eat(";") && !self.c.starts_with("..") {
+                self.c.eat(".");
+            }
+            if !rules.add_rule(&name, expr) {
+                return Err(self
+                    .c
+                    .error_at(pos, format!("the rule `{}` is defined more than once", name)));
+            }
+        }
+        rules.check()?;
+        Ok(rules)
+    }
+
+    #[no_coverage]
+    fn skip_trivia(&mut self) -> Result<(), GrammarParseError> {
+        loop {
+            let pos = self.c.pos();
+            if matches!(self.c.peek(), Some(c) if c.is_whitespace()) {
+                self.c.bump();
+            } else if self.c.eat("(*") {
+                while !self.c.eat("*)") {
+                    if self.c.bump().is_none() {
+                        return Err(self.c.error_at(pos, "unterminated comment"));
+                    }
+                }
+            } else if self.c.eat("/*") {
+                while !self.c.eat("*/") {
+                    if self.c.bump().is_none() {
+                        return Err(self.c.error_at(pos, "unterminated comment"));
+                    }
+                }
+            } else if self.c.eat("//") {
+                self.c.take_while(
+                    #[no_coverage]
+                    |c| c != '\n',
+                );
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    #[no_coverage]
+    fn identifier(&mut self) -> Result<Option<String>, GrammarParseError> {
+        match self.c.peek() {
+            Some('<') => {
+                let pos = self.c.pos();
+                self.c.bump();
+                let name = self.c.take_while(
+                    #[no_coverage]
+                    |c| c != '>' && c != '\n',
+                );
+                if !self.c.eat(">") {
+                    return Err(self.c.error_at(pos, "unterminated rule name"));
+                }
+                Ok(Some(name.trim().to_owned()))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some(c) = self.c.peek() {
+                    let is_inner_dash = c == '-' && matches!(self.c.peek_nth(1), Some(c) if c.is_alphanumeric());
+                    if c.is_alphanumeric() || c == '_' || is_inner_dash {
+                        name.push(c);
+                        self.c.bump();
+                    } else {
+                        break;
+                    }
+                }
+                Ok(Some(name))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether the next tokens are the beginning of a new rule
+    #[no_coverage]
+    fn at_rule_start(&mut self) -> bool {
+        let pos = self.c.pos();
+        let result = matches!(self.identifier(), Ok(Some(_)))
+            && self.skip_trivia().is_ok()
+            && (self.c.starts_with("::=") || (self.c.starts_with("=") && !self.c.starts_with("==")));
+        self.c.pos = pos;
+        result
+    }
+
+    #[no_coverage]
+    fn alternation(&mut self) -> Result<Expr, GrammarParseError> {
+        let mut alternatives = vec![self.concatenation()?];
+        loop {
+            self.skip_trivia()?;
+            if self.c.eat("|") {
+                alternatives.push(self.concatenation()?);
+            } else {
+                break;
+            }
+        }
+        if alternatives.len() == 1 {
+            Ok(alternatives.pop().unwrap())
+        } else {
+            Ok(Expr::Alternation(alternatives))
+        }
+    }
+
+    #[no_coverage]
+    fn concatenation(&mut self) -> Result<Expr, GrammarParseError> {
+        let mut items = vec![];
+        loop {
+            self.skip_trivia()?;
+            match self.c.peek() {
+                None | Some('|' | ')' | ']' | '}' | ';') => break,
+                Some('.') if !self.c.starts_with("..") => break,
+                Some('-') => return Err(self.c.error("exceptions (`-`) are not supported")),
+                _ => {}
+            }
+            if self.at_rule_start() {
+                break;
+            }
+            items.push(self.term()?);
+            self.skip_trivia()?;
+            self.c.eat(",");
+        }
+        if items.len() == 1 {
+            Ok(items.pop().unwrap())
+        } else {
+            Ok(Expr::Concatenation(items))
+        }
+    }
+
+    #[no_coverage]
+    fn term(&mut self) -> Result<Expr, GrammarParseError> {
+        let mut expr = if matches!(self.c.peek(), Some(c) if c.is_ascii_digit()) {
+            let pos = self.c.pos();
+            let count = self.c.take_while(
+                #[no_coverage]
+                |c| c.is_ascii_digit(),
+            );
+            let count = count
+                .parse::<usize>()
+                .map_err(|_| self.c.error_at(pos, "invalid repetition count"))?;
+            self.skip_trivia()?;
+            if !self.c.eat("*") {
+                return Err(self.c.error(format!(
+                    "expected `*` after the repetition count, found {}",
+                    self.c.describe_next()
+                )));
+            }
+            self.skip_trivia()?;
+            Expr::Repetition(Box::new(self.primary()?), count..count.saturating_add(1))
+        } else {
+            self.primary()?
+        };
+        loop {
+            self.skip_trivia()?;
+            let range = if self.c.eat("?") {
+                0..2
+            } else if self.c.eat("*") {
+                0..usize::MAX
+            } else if self.c.eat("+") {
+                1..usize::MAX
+            } else {
+                break;
+            };
+            expr = Expr::Repetition(Box::new(expr), range);
+        }
+        Ok(expr)
+    }
+
+    #[no_coverage]
+    fn primary(&mut self) -> Result<Expr, GrammarParseError> {
+        let pos = self.c.pos();
+        match self.c.peek() {
+            Some('"' | '\'' | '#') => {
+                let s = self.terminal()?;
+                self.skip_trivia()?;
+                if self.c.eat("..") {
+                    self.skip_trivia()?;
+                    let end_pos = self.c.pos();
+                    let end = self.terminal()?;
+                    let (start, end) = match (single_char(&s), single_char(&end)) {
+                        (Some(start), Some(end)) => (start, end),
+                        (None, _) => {
+                            return Err(self.c.error_at(pos, "the bounds of a range must be single characters"))
+                        }
+                        (_, None) => {
+                            return Err(self
+                                .c
+                                .error_at(end_pos, "the bounds of a range must be single characters"))
+                        }
+                    };
+                    if start > end {
+                        return Err(self.c.error_at(pos, "the start of the range is larger than its end"));
+                    }
+                    Ok(Expr::Literal(vec![start..=end]))
+                } else {
+                    Ok(Expr::string(&s))
+                }
+            }
+            Some(open @ ('(' | '[' | '{')) => {
+                self.c.bump();
+                let expr = self.alternation()?;
+                self.skip_trivia()?;
+                let close = match open {
+                    '(' => ")",
+                    '[' => "]",
+                    _ => "}",
+                };
+                if !self.c.eat(close) {
+                    return Err(self.c.error(format!(
+                        "expected `{}` to match the `{}` at line {}, column {}, found {}",
+                        close,
+                        open,
+                        self.c.line_column(pos).0,
+                        self.c.line_column(pos).1,
+                        self.c.describe_next()
+                    )));
+                }
+                Ok(match open {
+                    '(' => expr,
+                    '[' => Expr::Repetition(Box::new(expr), 0..2),
+                    _ => Expr::Repetition(Box::new(expr), 0..usize::MAX),
+                })
+            }
+            Some('?') => Err(self.c.error("special sequences (`? .. ?`) are not supported")),
+            _ => {
+                if let Some(name) = self.identifier()? {
+                    Ok(self.c.reference(name, pos))
+                } else {
+                    Err(self
+                        .c
+                        .error(format!("expected an expression, found {}", self.c.describe_next())))
+                }
+            }
+        }
+    }
+
+    /// A string between quotes, or a hexadecimal character such as `#x41`
+    #[no_coverage]
+    fn terminal(&mut self) -> Result<String, GrammarParseError> {
+        let pos = self.c.pos();
+        if self.c.eat("#x") {
+            let digits = self.c.take_while(
+                #[no_coverage]
+                |c| c.is_ascii_hexdigit(),
+            );
+            let c = u32::from_str_radix(&digits, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| self.c.error_at(pos, "invalid hexadecimal character"))?;
+            return Ok(c.to_string());
+        }
+        let quote = match self.c.bump() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(self.c.error_at(pos, "expected a string")),
+        };
+        let mut s = String::new();
+        loop {
+            let c = self
+                .c
+                .bump()
+                .ok_or_else(|| self.c.error_at(pos, "unterminated string"))?;
+            if c == quote {
+                return Ok(s);
+            }
+            if c != '\\' {
+                s.push(c);
+                continue;
+            }
+            let escape_pos = self.c.pos() - 1;
+            let c = match self.c.bump() {
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                Some('0') => '\0',
+                Some(c @ ('\\' | '"' | '\'')) => c,
+                Some('u') if self.c.eat("{") => {
+                    let digits = self.c.take_while(
+                        #[no_coverage]
+                        |c| c.is_ascii_hexdigit(),
+                    );
+                    if !self.c.eat("}") {
+                        return Err(self.c.error_at(escape_pos, "invalid unicode escape"));
+                    }
+                    u32::from_str_radix(&digits, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| self.c.error_at(escape_pos, "invalid unicode escape"))?
+                }
+                _ => return Err(self.c.error_at(escape_pos, "invalid escape sequence")),
+            };
+            s.push(c);
+        }
+    }
+}
+
+#[no_coverage]
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+/// The core rules of ABNF, defined in appendix B of RFC 5234
+const ABNF_CORE_RULES: &str = r#"
+ALPHA = %x41-5A / %x61-7A
+BIT = "0" / "1"
+CHAR = %x01-7F
+CR = %x0D
+CRLF = CR LF
+CTL = %x00-1F / %x7F
+DIGIT = %x30-39
+DQUOTE = %x22
+HEXDIG = DIGIT / "A" / "B" / "C" / "D" / "E" / "F"
+HTAB = %x09
+LF = %x0A
+LWSP = *(WSP / CRLF WSP)
+OCTET = %x00-FF
+SP = %x20
+VCHAR = %x21-7E
+WSP = SP / HTAB
+"#;
+
+impl GrammarRules {
+    /// Add the core rules that are referenced but not defined
+    #[no_coverage]
+    fn add_core_rules(&mut self) {
+        let core_rules = AbnfParser {
+            c: Cursor::new(ABNF_CORE_RULES),
+        }
+        .parse()
+        .unwrap();
+        loop {
+            let mut missing = vec![];
+            for expr in &self.exprs {
+                expr.visit_references(&mut |name, _, _| {
+                    if self.index(name).is_none() {
+                        if let Some(idx) = core_rules.index(name) {
+                            missing.push(idx);
+                        }
+                    }
+                });
+            }
+            if missing.is_empty() {
+                break;
+            }
+            for idx in missing {
+                self.add_rule(&core_rules.names[idx], core_rules.exprs[idx].clone());
+            }
+        }
+    }
+}
+
+struct AbnfParser {
+    c: Cursor,
+}
+
+impl AbnfParser {
+    #[no_coverage]
+    fn parse(mut self) -> Result<GrammarRules, GrammarParseError> {
+        let mut rules = GrammarRules::new(true);
+        loop {
+            self.skip_blank_lines();
+            if self.c.peek().is_none() {
+                break;
+            }
+            let pos = self.c.pos();
+            let name = self.rule_name().ok_or_else(|| {
+                self.c
+                    .error(format!("expected a rule name, found {}", self.c.describe_next()))
+            })?;
+            self.skip_whitespace();
+            let is_incremental = if self.c.eat("=/") {
+                true
+            } else if self.c.eat("=") {
+                false
+            } else {
+                return Err(self.c.error(format!(
+                    "expected `=` or `=/` after the name of the rule, found {}",
+                    self.c.describe_next()
+                )));
+            };
+            let expr = self.alternation()?;
+            self.skip_whitespace();
+            if !matches!(self.c.peek(), None | Some('\r' | '\n')) {
+                return Err(self.c.error(format!("unexpected {}", self.c.describe_next())));
+            }
+            if is_incremental {
+                if !rules.extend_rule(&name, expr) {
+                    return Err(self.c.error_at(pos, format!("the rule `{}` is not defined", name)));
+                }
+            } else if !rules.add_rule(&name, expr) {
+                return Err(self
+                    .c
+                    .error_at(pos, format!("the rule `{}` is defined more than once", name)));
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Skip whitespace, comments, and line breaks followed by whitespace, which continue the current rule
+    #[no_coverage]
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.c.peek() {
+                Some(' ' | '\t') => {
+                    self.c.bump();
+                }
+                Some(';') => {
+                    self.c.take_while(
+                        #[no_coverage]
+                        |c| c != '\r' && c != '\n',
+                    );
+                }
+                Some('\r' | '\n') => {
+                    let newline_len = if self.c.starts_with("\r\n") { 2 } else { 1 };
+                    if matches!(self.c.peek_nth(newline_len), Some(' ' | '\t')) {
+                        for _ in 0..newline_len {
+                            self.c.bump();
+                        }
+                    } else {
+                        return;
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    #[no_coverage]
+    fn skip_blank_lines(&mut self) {
+        loop {
+            self.skip_whitespace();
+            if self.c.eat("\r\n") || self.c.eat("\n") || self.c.eat("\r") {
+                continue;
+            }
+            return;
+        }
+    }
+
+    #[no_coverage]
+    fn rule_name(&mut self) -> Option<String> {
+        if !self.c.peek()?.is_ascii_alphabetic() {
+            return None;
+        }
+        Some(self.c.take_while(
+            #[no_coverage]
+            |c| c.is_ascii_alphanumeric() || c == '-',
+        ))
+    }
+
+    #[no_coverage]
+    fn alternation(&mut self) -> Result<Expr, GrammarParseError> {
+        let mut alternatives = vec![self.concatenation()?];
+        loop {
+            self.skip_whitespace();
+            if self.c.eat("/") {
+                alternatives.push(self.concatenation()?);
+            } else {
+                break;
+            }
+        }
+        if alternatives.len() == 1 {
+            Ok(alternatives.pop().unwrap())
+        } else {
+            Ok(Expr::Alternation(alternatives))
+        }
+    }
+
+    #[no_coverage]
+    fn concatenation(&mut self) -> Result<Expr, GrammarParseError> {
+        let mut items = vec![];
+        loop {
+            self.skip_whitespace();
+            if matches!(self.c.peek(), None | Some('/' | ')' | ']' | '\r' | '\n')) {
+                break;
+            }
+            items.push(self.repetition()?);
+        }
+        match items.len() {
+            0 => Err(self
+                .c
+                .error(format!("expected an element, found {}", self.c.describe_next()))),
+            1 => Ok(items.pop().unwrap()),
+            _ => Ok(Expr::Concatenation(items)),
+        }
+    }
+
+    #[no_coverage]
+    fn number(&mut self, radix: u32) -> Result<Option<u32>, GrammarParseError> {
+        let pos = self.c.pos();
+        let digits = self.c.take_while(
+            #[no_coverage]
+            |c| c.is_digit(radix),
+        );
+        if digits.is_empty() {
+            return Ok(None);
+        }
+        u32::from_str_radix(&digits, radix)
+            .map(Some)
+            .map_err(|_| self.c.error_at(pos, "the number is too large"))
+    }
+
+    #[no_coverage]
+    fn repetition(&mut self) -> Result<Expr, GrammarParseError> {
+        let min = self.number(10)?;
+        let range = if self.c.eat("*") {
+            let max = self.number(10)?;
+            Some(min.unwrap_or(0) as usize..max.map_or(usize::MAX, |max| max as usize + 1))
+        } else {
+            min.map(|min| min as usize..min as usize + 1)
+        };
+        let element = self.element()?;
+        Ok(match range {
+            Some(range) => Expr::Repetition(Box::new(element), range),
+            None => element,
+        })
+    }
+
+    #[no_coverage]
+    fn element(&mut self) -> Result<Expr, GrammarParseError> {
+        let pos = self.c.pos();
+        match self.c.peek() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                let name = self.rule_name().unwrap();
+                Ok(self.c.reference(name, pos))
+            }
+            Some(open @ ('(' | '[')) => {
+                self.c.bump();
+                self.skip_whitespace();
+                let expr = self.alternation()?;
+                self.skip_whitespace();
+                let close = if open == '(' { ")" } else { "]" };
+                if !self.c.eat(close) {
+                    let (line, column) = self.c.line_column(pos);
+                    return Err(self.c.error(format!(
+                        "expected `{}` to match the `{}` at line {}, column {}, found {}",
+                        close,
+                        open,
+                        line,
+                        column,
+                        self.c.describe_next()
+                    )));
+                }
+                Ok(if open == '(' {
+                    expr
+                } else {
+                    Expr::Repetition(Box::new(expr), 0..2)
+                })
+            }
+            Some('"') => self.char_val(false),
+            Some('%') => {
+                self.c.bump();
+                if self.c.eat("s") {
+                    self.char_val(true)
+                } else if self.c.eat("i") {
+                    self.char_val(false)
+                } else {
+                    self.num_val(pos)
+                }
+            }
+            Some('<') => Err(self.c.error("prose values (`< .. >`) are not supported")),
+            _ => Err(self
+                .c
+                .error(format!("expected an element, found {}", self.c.describe_next()))),
+        }
+    }
+
+    #[no_coverage]
+    fn char_val(&mut self, case_sensitive: bool) -> Result<Expr, GrammarParseError> {
+        let pos = self.c.pos();
+        if !self.c.eat("\"") {
+            return Err(self.c.error("expected a string"));
+        }
+        let s = self.c.take_while(
+            #[no_coverage]
+            |c| c != '"' && c != '\r' && c != '\n',
+        );
+        if !self.c.eat("\"") {
+            return Err(self.c.error_at(pos, "unterminated string"));
+        }
+        if case_sensitive {
+            Ok(Expr::string(&s))
+        } else {
+            Ok(Expr::case_insensitive_string(&s))
+        }
+    }
+
+    /// A numeric value such as `%x41`, `%x41-5A`, or `%d13.10`, whose `%` was already parsed
+    #[no_coverage]
+    fn num_val(&mut self, pos: usize) -> Result<Expr, GrammarParseError> {
+        let radix = match self.c.bump() {
+            Some('x' | 'X') => 16,
+            Some('d' | 'D') => 10,
+            Some('b' | 'B') => 2,
+            _ => return Err(self.c.error_at(pos, "expected `x`, `d`, or `b` after `%`")),
+        };
+        let char = #[no_coverage]
+        |parser: &mut Self| -> Result<char, GrammarParseError> {
+            let pos = parser.c.pos();
+            parser
+                .number(radix)?
+                .and_then(char::from_u32)
+                .ok_or_else(|| parser.c.error_at(pos, "invalid character code"))
+        };
+        let start = char(self)?;
+        if self.c.eat("-") {
+            let end = char(self)?;
+            if start > end {
+                return Err(self.c.error_at(pos, "the start of the range is larger than its end"));
+            }
+            Ok(Expr::Literal(vec![start..=end]))
+        } else {
+            let mut chars = vec![Expr::Literal(vec![start..=start])];
+            while self.c.eat(".") {
+                let c = char(self)?;
+                chars.push(Expr::Literal(vec![c..=c]));
+            }
+            Ok(if chars.len() == 1 {
+                chars.pop().unwrap()
+            } else {
+                Expr::Concatenation(chars)
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::rc::Rc;
+
+    use super::{GrammarParseError, GrammarRules};
+    use crate::mutators::grammar::{grammar_based_ast_mutator, Grammar};
+    use crate::mutators::testing_utilities::test_mutator;
+    use crate::Mutator;
+
+    #[no_coverage]
+    fn generate_strings(rules: &GrammarRules, rule: &str) -> Vec<String> {
+        let m = grammar_based_ast_mutator(rules.grammar(rule).unwrap());
+        (0..200)
+            .map(
+                #[no_coverage]
+                |_| m.random_arbitrary(100.0).0.to_string(),
+            )
+            .collect()
+    }
+
+    /// The number of distinct `Grammar::Rule` nodes reachable from the grammar, that is, the number of times
+    /// a rule was expanded to build it
+    #[no_coverage]
+    fn count_rule_expansions(grammar: &Rc<Grammar>, visited: &mut HashSet<*const Grammar>) -> usize {
+        if !visited.insert(Rc::as_ptr(grammar)) {
+            return 0;
+        }
+        match grammar.as_ref() {
+            Grammar::Literal(_) | Grammar::Recurse(_) => 0,
+            Grammar::Alternation(gs, _) | Grammar::Concatenation(gs) => gs
+                .iter()
+                .map(
+                    #[no_coverage]
+                    |g| count_rule_expansions(g, visited),
+                )
+                .sum(),
+            Grammar::Repetition(g, _, _) | Grammar::Recursive(g) => count_rule_expansions(g, visited),
+            Grammar::Rule(_, g) => 1 + count_rule_expansions(g, visited),
+        }
+    }
+
+    #[test]
+    fn test_ebnf() {
+        let rules = GrammarRules::from_ebnf(
+            r#"
+            (* arithmetic expressions *)
+            expr ::= term { ('+' | '-') term }
+            term ::= factor, { ("*" | "/"), factor }
+            factor ::= number | '(' expr ')' | <negation>
+            <negation> = "-" number ;
+            number = digit+ [ '.' 3 * digit ] ; // a number
+            digit = '0' .. '9' .
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            rules.rule_names().collect::<Vec<_>>(),
+            ["expr", "term", "factor", "negation", "number", "digit"]
+        );
+        for s in generate_strings(&rules, "expr") {
+            assert!(!s.is_empty());
+            assert!(s.chars().all(|c| "0123456789.+-*/()".contains(c)), "{}", s);
+        }
+        for s in generate_strings(&rules, "number") {
+            let (integer, decimals) = s.split_once('.').unwrap_or((&s, "000"));
+            assert!(!integer.is_empty() && decimals.len() == 3, "{}", s);
+        }
+        let (ast, _) = grammar_based_ast_mutator(rules.grammar("number").unwrap()).random_arbitrary(100.0);
+        assert_eq!(ast.rule(), Some("number"));
+        let m = grammar_based_ast_mutator(rules.start_grammar());
+        test_mutator(m, 200., 200., false, true, 50, 50);
+    }
+
+    #[test]
+    fn test_abnf() {
+        let rules = GrammarRules::from_abnf(
+            "request = method SP path CRLF *( header CRLF ) ; a request\r\n\
+             method = \"GET\" / %s\"POST\"\r\n\
+             method =/ %x44.45.4C ; DEL\r\n\
+             path = 1*( \"/\" 1*8ALPHA ) [ \"?\" ]\r\n\
+             header = 1*ALPHA \":\" \r\n\
+             \x20  1*2( SP 1*DIGIT )\r\n",
+        )
+        .unwrap();
+        for s in generate_strings(&rules, "REQUEST") {
+            let (method, rest) = s.split_once(' ').unwrap();
+            assert!(
+                method.eq_ignore_ascii_case("get") || method == "POST" || method == "DEL",
+                "{:?}",
+                s
+            );
+            assert!(rest.starts_with('/') && rest.ends_with("\r\n"), "{:?}", s);
+        }
+        let m = grammar_based_ast_mutator(rules.start_grammar());
+        test_mutator(m, 200., 200., false, true, 50, 50);
+    }
+
+    #[test]
+    fn test_mutually_recursive_rules() {
+        // every rule can reach every other one
+        let source = (0..40)
+            .map(
+                #[no_coverage]
+                |i| {
+                    let others = (0..40)
+                        .filter(
+                            #[no_coverage]
+                            |j| *j != i,
+                        )
+                        .map(
+                            #[no_coverage]
+                            |j| format!("r{}", j),
+                        )
+                        .collect::<Vec<_>>();
+                    format!("r{} = '(' , ( {} ) , ')' | 'x' ;\n", i, others.join(" | "))
+                },
+            )
+            .collect::<String>();
+        let rules = GrammarRules::from_ebnf(&source).unwrap();
+        let grammar = rules.start_grammar();
+        // each rule is expanded exactly once
+        assert_eq!(count_rule_expansions(&grammar, &mut HashSet::new()), 40);
+        let m = grammar_based_ast_mutator(grammar);
+        for s in generate_strings(&rules, "r0") {
+            assert!(s.chars().all(|c| "()x".contains(c)), "{}", s);
+        }
+        test_mutator(m, 200., 200., false, true, 50, 50);
+    }
+
+    #[test]
+    fn test_grammar_errors() {
+        let error = |line, column, message: &str| GrammarParseError {
+            line,
+            column,
+            message: message.to_owned(),
+        };
+        assert_eq!(
+            GrammarRules::from_ebnf("a = b ;\nb = 'x' | c ;").unwrap_err(),
+            error(2, 11, "the rule `c` is not defined")
+        );
+        assert_eq!(
+            GrammarRules::from_ebnf("a = 'x\n").unwrap_err(),
+            error(1, 5, "unterminated string")
+        );
+        assert_eq!(
+            GrammarRules::from_ebnf("a = 'x' - 'y'").unwrap_err(),
+            error(1, 9, "exceptions (`-`) are not supported")
+        );
+        assert_eq!(
+            GrammarRules::from_ebnf("a = ( 'x' ;").unwrap_err(),
+            error(1, 11, "expected `)` to match the `(` at line 1, column 5, found `;`")
+        );
+        assert_eq!(
+            GrammarRules::from_abnf("a = b\nb = <prose>").unwrap_err(),
+            error(2, 5, "prose values (`< .. >`) are not supported")
+        );
+        assert_eq!(
+            GrammarRules::from_abnf("a = \"x\"\na = \"y\"").unwrap_err(),
+            error(2, 1, "the rule `a` is defined more than once")
+        );
+    }
+}
@@ -0,0 +1,722 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::{Range, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use nu_ansi_term::Color;
+
+use super::ast::AST;
+use super::grammar::Grammar;
+use crate::data_structures::{Slab, SlabKey};
+use crate::fenwick_tree::FenwickTree;
+use crate::sensors_and_pools::CountBuckets;
+use crate::traits::{CorpusDelta, Pool, SaveToStatsFolder, Sensor, Stats};
+use crate::{CSVField, CompatibleWithObservations, PoolStorageIndex, ToCSV};
+
+/// A node of the grammar, as seen by the grammar coverage sensor
+enum Node {
+    Literal(Vec<RangeInclusive<char>>),
+    /// The production of the `i`-th alternative is `first_production + i`
+    Alternation {
+        alternatives: Vec<usize>,
+        first_production: usize,
+    },
+    Concatenation(Vec<usize>),
+    /// The production of a repetition of length `len` is `first_production + bucket(len) - min_bucket`
+    Repetition {
+        item: usize,
+        range: Range<usize>,
+        first_production: usize,
+        min_bucket: usize,
+    },
+    Recurse(usize),
+    Recursive(usize),
+    Rule {
+        name: String,
+        node: usize,
+        production: usize,
+    },
+}
+
+/// The grammar, flattened into a list of nodes, and the description of each of its productions
+struct Productions {
+    nodes: Vec<Node>,
+    descriptions: Vec<String>,
+}
+
+/// The bucket of the length of a repetition: `0`, `1`, `2`, `3`, `4-7`, `8-15`, etc.
+#[no_coverage]
+fn length_bucket(len: usize) -> usize {
+    CountBuckets::log2().bucket(len as u64) as usize
+}
+
+/// The lengths of the repetitions that belong to the bucket
+#[no_coverage]
+fn describe_length_bucket(bucket: usize) -> String {
+    if bucket <= 3 {
+        format!("{}", bucket)
+    } else {
+        let start = 1u128 << (bucket - 2);
+        format!("{}-{}", start, 2 * start - 1)
+    }
+}
+
+impl Productions {
+    #[no_coverage]
+    fn new(grammar: &Rc<Grammar>) -> Self {
+        let mut productions = Self {
+            nodes: vec![],
+            descriptions: vec![],
+        };
+        let mut ids = HashMap::new();
+        let root = productions.add_grammar(grammar, "<root>", &mut ids);
+        assert_eq!(root, 0);
+        productions
+    }
+
+    #[no_coverage]
+    fn add_production(&mut self, description: String) -> usize {
+        self.descriptions.push(description);
+        self.descriptions.len() - 1
+    }
+
+    /// Add the node of the grammar, if it was not already added, and return its index.
+    ///
+    /// `rule` is the name of the closest named rule containing the grammar, which is used to
+    /// describe its productions.
+    #[no_coverage]
+    fn add_grammar(&mut self, grammar: &Rc<Grammar>, rule: &str, ids: &mut HashMap<*const Grammar, usize>) -> usize {
+        let ptr = Rc::as_ptr(grammar);
+        if let Some(id) = ids.get(&ptr) {
+            return *id;
+        }
+        // the node is registered before its children are created, such that
+        // recursive grammars can refer to it
+        let id = self.nodes.len();
+        self.nodes.push(Node::Concatenation(vec![]));
+        ids.insert(ptr, id);
+
+        let node = match grammar.as_ref() {
+            Grammar::Literal(ranges) => Node::Literal(ranges.clone()),
+            Grammar::Alternation(gs, _) => {
+                let count_alternations = self
+                    .nodes
+                    .iter()
+                    .filter(
+                        #[no_coverage]
+                        |n| matches!(n, Node::Alternation { .. }),
+                    )
+                    .count();
+                let first_production = self.descriptions.len();
+                for i in 0..gs.len() {
+                    self.add_production(format!(
+                        "{}: alternation {}, alternative {}",
+                        rule, count_alternations, i
+                    ));
+                }
+                // reserve the node of the alternation before adding the alternatives, such that the
+                // alternations are numbered in the order in which they are encountered
+                self.nodes[id] = Node::Alternation {
+                    alternatives: vec![],
+                    first_production,
+                };
+                let alternatives = gs
+                    .iter()
+                    .map(
+                        #[no_coverage]
+                        |g| self.add_grammar(g, rule, ids),
+                    )
+                    .collect();
+                Node::Alternation {
+                    alternatives,
+                    first_production,
+                }
+            }
+            Grammar::Concatenation(gs) => Node::Concatenation(
+                gs.iter()
+                    .map(
+                        #[no_coverage]
+                        |g| self.add_grammar(g, rule, ids),
+                    )
+                    .collect(),
+            ),
+            Grammar::Repetition(g, range, _) => {
+                let count_repetitions = self
+                    .nodes
+                    .iter()
+                    .filter(
+                        #[no_coverage]
+                        |n| matches!(n, Node::Repetition { .. }),
+                    )
+                    .count();
+                let first_production = self.descriptions.len();
+                let min_bucket = length_bucket(range.start);
+                if !range.is_empty() {
+                    for bucket in min_bucket..=length_bucket(range.end - 1) {
+                        self.add_production(format!(
+                            "{}: repetition {}, length {}",
+                            rule,
+                            count_repetitions,
+                            describe_length_bucket(bucket)
+                        ));
+                    }
+                }
+                self.nodes[id] = Node::Repetition {
+                    item: 0,
+                    range: range.clone(),
+                    first_production,
+                    min_bucket,
+                };
+                Node::Repetition {
+                    item: self.add_grammar(g, rule, ids),
+                    range: range.clone(),
+                    first_production,
+                    min_bucket,
+                }
+            }
+            Grammar::Recurse(g) => {
+                let g = g.upgrade().expect("the recursive grammar should be alive");
+                Node::Recurse(self.add_grammar(&g, rule, ids))
+            }
+            Grammar::Recursive(g) => Node::Recursive(self.add_grammar(g, rule, ids)),
+            Grammar::Rule(name, g) => {
+                let production = self.add_production(format!("rule {}", name));
+                Node::Rule {
+                    name: name.clone(),
+                    node: self.add_grammar(g, name, ids),
+                    production,
+                }
+            }
+        };
+        self.nodes[id] = node;
+        id
+    }
+
+    /// Push the productions used by the syntax tree to `used`, and return whether the syntax tree
+    /// was generated by the node.
+    ///
+    /// If the syntax tree was not generated by the node, some productions may have been pushed to `used`
+    /// anyway. They should be removed by the caller.
+    #[no_coverage]
+    fn collect_productions(&self, node: usize, ast: &AST, used: &mut Vec<usize>) -> bool {
+        match (&self.nodes[node], ast) {
+            (Node::Literal(ranges), AST::Token(c)) => ranges.iter().any(
+                #[no_coverage]
+                |r| r.contains(c),
+            ),
+            (
+                Node::Alternation {
+                    alternatives,
+                    first_production,
+                },
+                _,
+            ) => {
+                for (i, &alternative) in alternatives.iter().enumerate() {
+                    let len = used.len();
+                    if self.collect_productions(alternative, ast, used) {
+                        used.push(first_production + i);
+                        return true;
+                    }
+                    used.truncate(len);
+                }
+                false
+            }
+            (Node::Concatenation(nodes), AST::Sequence(asts)) => {
+                nodes.len() == asts.len()
+                    && nodes.iter().zip(asts.iter()).all(
+                        #[no_coverage]
+                        |(&node, ast)| self.collect_productions(node, ast, used),
+                    )
+            }
+            (
+                Node::Repetition {
+                    item,
+                    range,
+                    first_production,
+                    min_bucket,
+                },
+                AST::Sequence(asts),
+            ) => {
+                if !range.contains(&asts.len())
+                    || !asts.iter().all(
+                        #[no_coverage]
+                        |ast| self.collect_productions(*item, ast, used),
+                    )
+                {
+                    return false;
+                }
+                used.push(first_production + length_bucket(asts.len()) - min_bucket);
+                true
+            }
+            (Node::Recurse(node), AST::Sequence(asts)) => {
+                asts.len() == 1 && self.collect_productions(*node, &asts[0], used)
+            }
+            (Node::Recursive(node), _) => self.collect_productions(*node, ast, used),
+            (Node::Rule { name, node, production }, AST::Rule { rule, ast }) => {
+                if name != rule || !self.collect_productions(*node, ast, used) {
+                    return false;
+                }
+                used.push(*production);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The productions used by the syntax trees recorded since the sensor started recording
+struct Recording {
+    hits: Vec<u64>,
+    used: Vec<usize>,
+}
+
+struct SharedCoverage {
+    productions: Productions,
+    recording: RefCell<Recording>,
+}
+
+/// A handle given to the test function to record the syntax trees it tests, created by
+/// [`GrammarCoverageSensor::recorder`].
+#[derive(Clone)]
+pub struct GrammarCoverageRecorder {
+    coverage: Rc<SharedCoverage>,
+}
+
+impl GrammarCoverageRecorder {
+    /// Record the grammar productions used by the syntax tree.
+    ///
+    /// Returns `false`, and records nothing, if the syntax tree was not generated by the grammar of the sensor.
+    #[no_coverage]
+    pub fn record(&self, ast: &AST) -> bool {
+        let mut recording = self.coverage.recording.borrow_mut();
+        let Recording { hits, used } = &mut *recording;
+        let len = used.len();
+        if !self.coverage.productions.collect_productions(0, ast, used) {
+            used.truncate(len);
+            return false;
+        }
+        for &production in &used[len..] {
+            hits[production] += 1;
+        }
+        true
+    }
+}
+
+/// A sensor that records which productions of a grammar were used by the syntax trees given to the test function.
+///
+/// The productions of a grammar are:
+/// * each named [rule](crate::mutators::grammar::rule)
+/// * each alternative of each [alternation](crate::mutators::grammar::alternation)
+/// * each bucket of lengths of each [repetition](crate::mutators::grammar::repetition). The buckets are
+/// `0`, `1`, `2`, `3`, `4-7`, `8-15`, `16-31`, etc.
+///
+/// The sensor cannot see the inputs of the test function by itself. Instead, the test function must give
+/// the syntax trees it tests to the [recorder](GrammarCoverageSensor::recorder) of the sensor.
+///
+/// The observations of the sensor are a list of `(production_index, number_of_uses)`. Therefore, it is compatible
+/// with the same pools as the [`CodeCoverageSensor`](crate::sensors_and_pools::CodeCoverageSensor), but it is best
+/// paired with a [`GrammarCoveragePool`]. It is meant to be used alongside the default code coverage sensor and pool:
+/// ```no_run
+/// use fuzzcheck::mutators::grammar::*;
+/// use fuzzcheck::sensors_and_pools::{AndSensor, DifferentObservations};
+/// use fuzzcheck::PoolExt;
+///
+/// let grammar = repetition(alternation([rule("digit", literal_range('0'..='9')), literal('-')]), 1..10);
+///
+/// let sensor = GrammarCoverageSensor::new(grammar.clone());
+/// let pool = GrammarCoveragePool::new("grammar_coverage", sensor.nbr_productions());
+/// let recorder = sensor.recorder();
+///
+/// let (cov_sensor, cov_pool) = fuzzcheck::builder::default_sensor_and_pool().finish();
+/// let sensor = AndSensor(cov_sensor, sensor);
+/// let pool = cov_pool.and(pool, Some(0.5), DifferentObservations);
+///
+/// let result = fuzzcheck::fuzz_test(move |(string, ast): &(String, AST)| {
+///         recorder.record(ast);
+///         string.parse::<i64>().is_ok() || string.contains("--")
+///     })
+///     .mutator(grammar_based_ast_mutator(grammar).with_string())
+///     .serde_serializer()
+///     .sensor_and_pool(sensor, pool)
+///     .arguments_from_cargo_fuzzcheck()
+///     .launch();
+/// ```
+/// At the end of the fuzz test, the number of times that each production was used by the tested inputs is
+/// saved to the file `grammar_coverage.csv` of the stats folder.
+pub struct GrammarCoverageSensor {
+    coverage: Rc<SharedCoverage>,
+    total_hits: Vec<u64>,
+}
+
+impl GrammarCoverageSensor {
+    #[no_coverage]
+    pub fn new(grammar: Rc<Grammar>) -> Self {
+        let productions = Productions::new(&grammar);
+        let nbr_productions = productions.descriptions.len();
+        Self {
+            coverage: Rc::new(SharedCoverage {
+                productions,
+                recording: RefCell::new(Recording {
+                    hits: vec![0; nbr_productions],
+                    used: vec![],
+                }),
+            }),
+            total_hits: vec![0; nbr_productions],
+        }
+    }
+
+    /// The handle that the test function should use to record the syntax trees it tests
+    #[no_coverage]
+    pub fn recorder(&self) -> GrammarCoverageRecorder {
+        GrammarCoverageRecorder {
+            coverage: self.coverage.clone(),
+        }
+    }
+
+    /// The number of productions of the grammar, which is the number of counters that
+    /// the pool paired with the sensor must be created with
+    #[no_coverage]
+    pub fn nbr_productions(&self) -> usize {
+        self.coverage.productions.descriptions.len()
+    }
+
+    /// A human-readable description of the production with the given index, such as
+    /// `"rule number"` or `"number: alternation 0, alternative 2"`
+    #[no_coverage]
+    pub fn describe_production(&self, production: usize) -> &str {
+        &self.coverage.productions.descriptions[production]
+    }
+}
+
+impl SaveToStatsFolder for GrammarCoverageSensor {
+    #[no_coverage]
+    fn save_to_stats_folder(&self) -> Vec<(PathBuf, Vec<u8>)> {
+        let mut fields = vec![
+            CSVField::String("production".to_string()),
+            CSVField::String("total_hits".to_string()),
+        ];
+        let mut content = CSVField::to_bytes(&fields);
+        for (description, &hits) in self
+            .coverage
+            .productions
+            .descriptions
+            .iter()
+            .zip(self.total_hits.iter())
+        {
+            fields = vec![CSVField::String(description.clone()), CSVField::Integer(hits as isize)];
+            content.extend(CSVField::to_bytes(&fields));
+        }
+        vec![(PathBuf::new().join("grammar_coverage.csv"), content)]
+    }
+}
+
+impl Sensor for GrammarCoverageSensor {
+    type Observations = Vec<(usize, u64)>;
+
+    #[no_coverage]
+    fn start_recording(&mut self) {
+        let mut recording = self.coverage.recording.borrow_mut();
+        let Recording { hits, used } = &mut *recording;
+        for &production in used.iter() {
+            hits[production] = 0;
+        }
+        used.clear();
+    }
+
+    #[no_coverage]
+    fn stop_recording(&mut self) {}
+
+    #[no_coverage]
+    fn get_observations(&mut self) -> Self::Observations {
+        let mut recording = self.coverage.recording.borrow_mut();
+        let Recording { hits, used } = &mut *recording;
+        used.sort_unstable();
+        used.dedup();
+        let mut observations = Vec::with_capacity(used.len());
+        for &production in used.iter() {
+            observations.push((production, hits[production]));
+            self.total_hits[production] += hits[production];
+            hits[production] = 0;
+        }
+        used.clear();
+        observations
+    }
+}
+
+#[derive(Clone)]
+pub struct GrammarCoveragePoolStats {
+    pub name: String,
+    pub size: usize,
+    pub coverage: (usize, usize),
+}
+impl Display for GrammarCoveragePoolStats {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            Color::Cyan.paint(format!(
+                "{}({} productions: {}/{})",
+                self.name, self.size, self.coverage.0, self.coverage.1
+            ))
+        )
+    }
+}
+impl ToCSV for GrammarCoveragePoolStats {
+    #[no_coverage]
+    fn csv_headers(&self) -> Vec<CSVField> {
+        vec![
+            CSVField::String(format!("{}-size", self.name)),
+            CSVField::String(format!("{}-productions", self.name)),
+        ]
+    }
+    #[no_coverage]
+    fn to_csv_record(&self) -> Vec<CSVField> {
+        vec![
+            CSVField::Integer(self.size as isize),
+            CSVField::Integer(self.coverage.0 as isize),
+        ]
+    }
+}
+impl Stats for GrammarCoveragePoolStats {}
+
+struct Input {
+    /// The productions for which this input is the simplest one in the pool
+    best_for_productions: Vec<usize>,
+    data: PoolStorageIndex,
+    score: f64,
+    number_times_chosen: usize,
+}
+
+/// A pool that keeps the simplest input using each production of a grammar, and prefers the
+/// inputs using rarely used productions.
+///
+/// It is meant to be paired with a [`GrammarCoverageSensor`], but it is compatible with any sensor
+/// whose observations are given by an iterator of `(usize, u64)`, where the first element is the index of
+/// the counter and must be smaller than the `nbr_productions` given to [`GrammarCoveragePool::new`].
+///
+/// The pool counts how many of the tested inputs used each production. Each input in the pool then
+/// gets a score equal to the sum of the rarity, i.e. the inverse of that count, of the productions
+/// for which it is the simplest input. Inputs with a higher score are chosen more often to be mutated.
+pub struct GrammarCoveragePool {
+    name: String,
+    hits: Vec<u64>,
+    least_complexity: Vec<f64>,
+    best_input: Vec<Option<SlabKey<Input>>>,
+    inputs: Slab<Input>,
+    ranked_inputs: FenwickTree,
+    stats: GrammarCoveragePoolStats,
+    rng: fastrand::Rng,
+}
+
+impl GrammarCoveragePool {
+    #[no_coverage]
+    pub fn new(name: &str, nbr_productions: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            hits: vec![0; nbr_productions],
+            least_complexity: vec![f64::INFINITY; nbr_productions],
+            best_input: vec![None; nbr_productions],
+            inputs: Slab::new(),
+            ranked_inputs: FenwickTree::new(vec![]),
+            stats: GrammarCoveragePoolStats {
+                name: name.to_string(),
+                size: 0,
+                coverage: (0, nbr_productions),
+            },
+            rng: fastrand::Rng::new(),
+        }
+    }
+
+    #[no_coverage]
+    fn update_stats(&mut self) {
+        let Self {
+            hits,
+            least_complexity,
+            inputs,
+            ranked_inputs,
+            stats,
+            ..
+        } = self;
+        let keys = inputs.keys().collect::<Vec<_>>();
+        let mut ranks = Vec::with_capacity(keys.len());
+        for key in keys {
+            let input = &mut inputs[key];
+            input.score = input
+                .best_for_productions
+                .iter()
+                .map(
+                    #[no_coverage]
+                    |&production| 1.0 / hits[production] as f64,
+                )
+                .sum();
+            ranks.push(input.score / (input.number_times_chosen as f64));
+        }
+        *ranked_inputs = FenwickTree::new(ranks);
+
+        stats.size = inputs.len();
+        stats.coverage.0 = least_complexity
+            .iter()
+            .filter(
+                #[no_coverage]
+                |c| c.is_finite(),
+            )
+            .count();
+    }
+}
+
+impl Pool for GrammarCoveragePool {
+    type Stats = GrammarCoveragePoolStats;
+
+    #[no_coverage]
+    fn stats(&self) -> Self::Stats {
+        self.stats.clone()
+    }
+
+    #[no_coverage]
+    fn get_random_index(&mut self) -> Option<PoolStorageIndex> {
+        let choice = self.ranked_inputs.sample(&self.rng)?;
+        let key = self.inputs.get_nth_key(choice);
+
+        let input = &mut self.inputs[key];
+        let old_rank = input.score / (input.number_times_chosen as f64);
+        input.number_times_chosen += 1;
+        let new_rank = input.score / (input.number_times_chosen as f64);
+
+        let delta = new_rank - old_rank;
+        self.ranked_inputs.update(choice, delta);
+        Some(input.data)
+    }
+}
+
+impl SaveToStatsFolder for GrammarCoveragePool {
+    #[no_coverage]
+    fn save_to_stats_folder(&self) -> Vec<(PathBuf, Vec<u8>)> {
+        vec![]
+    }
+}
+
+impl<O> CompatibleWithObservations<O> for GrammarCoveragePool
+where
+    for<'a> &'a O: IntoIterator<Item = &'a (usize, u64)>,
+{
+    #[no_coverage]
+    fn process(&mut self, input_id: PoolStorageIndex, observations: &O, complexity: f64) -> Vec<CorpusDelta> {
+        let mut best_for_productions = vec![];
+        for &(production, _) in observations.into_iter() {
+            self.hits[production] += 1;
+            if complexity < self.least_complexity[production] {
+                best_for_productions.push(production);
+            }
+        }
+        if best_for_productions.is_empty() {
+            return vec![];
+        }
+        let input_key = self.inputs.insert(Input {
+            best_for_productions: best_for_productions.clone(),
+            data: input_id,
+            score: 0.0,
+            number_times_chosen: 1,
+        });
+
+        let mut removed_keys = vec![];
+        for production in best_for_productions {
+            self.least_complexity[production] = complexity;
+            if let Some(previous_best_key) = self.best_input[production].replace(input_key) {
+                let previous_best = &mut self.inputs[previous_best_key];
+                previous_best.best_for_productions.retain(
+                    #[no_coverage]
+                    |&p| p != production,
+                );
+                if previous_best.best_for_productions.is_empty() {
+                    removed_keys.push(previous_best_key);
+                }
+            }
+        }
+        let mut removed_idxs = vec![];
+        for removed_key in removed_keys {
+            removed_idxs.push(self.inputs[removed_key].data);
+            self.inputs.remove(removed_key);
+        }
+        self.update_stats();
+        vec![CorpusDelta {
+            path: Path::new(&self.name).to_path_buf(),
+            add: true,
+            remove: removed_idxs,
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GrammarCoveragePool, GrammarCoverageSensor};
+    use crate::mutators::grammar::{alternation, concatenation, literal, repetition, rule, AST};
+    use crate::traits::{CompatibleWithObservations, Pool, Sensor};
+    use crate::PoolStorageIndex;
+
+    #[test]
+    fn test_grammar_coverage_sensor() {
+        let grammar = concatenation([
+            rule("sign", alternation([literal('+'), literal('-')])),
+            repetition(literal('1'), 0..6),
+        ]);
+        let mut sensor = GrammarCoverageSensor::new(grammar);
+        // rule sign, 2 alternatives, lengths 0, 1, 2, 3, 4-7
+        assert_eq!(sensor.nbr_productions(), 8);
+        assert_eq!(sensor.describe_production(0), "rule sign");
+        assert_eq!(sensor.describe_production(2), "sign: alternation 0, alternative 1");
+        assert_eq!(sensor.describe_production(7), "<root>: repetition 0, length 4-7");
+
+        let recorder = sensor.recorder();
+        let ast = |sign: char, len: usize| {
+            AST::Sequence(vec![
+                AST::Rule {
+                    rule: "sign".to_string(),
+                    ast: Box::new(AST::Token(sign)),
+                },
+                AST::Sequence(vec![AST::Token('1'); len]),
+            ])
+        };
+        sensor.start_recording();
+        assert!(recorder.record(&ast('-', 5)));
+        assert!(recorder.record(&ast('-', 0)));
+        assert!(!recorder.record(&ast('*', 1)));
+        assert!(!recorder.record(&ast('+', 6)));
+        sensor.stop_recording();
+        assert_eq!(sensor.get_observations(), vec![(0, 2), (2, 2), (3, 1), (7, 1)]);
+
+        sensor.start_recording();
+        sensor.stop_recording();
+        assert_eq!(sensor.get_observations(), vec![]);
+    }
+
+    #[test]
+    fn test_grammar_coverage_pool() {
+        let mut pool = GrammarCoveragePool::new("grammar", 4);
+        assert!(!pool
+            .process(PoolStorageIndex::mock(0), &vec![(0, 1), (1, 1)], 2.0)
+            .is_empty());
+        // no new production, and not simpler
+        assert!(pool.process(PoolStorageIndex::mock(1), &vec![(0, 1)], 3.0).is_empty());
+        // simpler for both productions: replaces the first input
+        let deltas = pool.process(PoolStorageIndex::mock(2), &vec![(0, 1), (1, 3)], 1.0);
+        assert_eq!(deltas[0].remove, vec![PoolStorageIndex::mock(0)]);
+        assert!(!pool
+            .process(PoolStorageIndex::mock(3), &vec![(0, 1), (3, 1)], 4.0)
+            .is_empty());
+        let stats = pool.stats();
+        assert_eq!(stats.size, 2);
+        assert_eq!(stats.coverage, (3, 4));
+
+        // the input using the rare production 3 is chosen more often
+        let mut count_rare = 0;
+        for _ in 0..1000 {
+            if pool.get_random_index() == Some(PoolStorageIndex::mock(3)) {
+                count_rare += 1;
+            }
+        }
+        assert!(count_rare > 500);
+    }
+}
@@ -3,6 +3,9 @@ use std::rc::{Rc, Weak};
 
 #[cfg(feature = "regex_grammar")]
 use crate::mutators::grammar::regex::grammar_from_regex;
+#[cfg(feature = "regex_grammar")]
+use crate::mutators::grammar::GrammarParseError;
+use crate::mutators::vector::LengthDistribution;
 
 #[derive(Clone, Debug)]
 /// A grammar which can be used for fuzzing.
@@ -10,18 +13,70 @@ use crate::mutators::grammar::regex::grammar_from_regex;
 /// See [the module documentation](crate::mutators::grammar) for advice on how to create a grammar.
 pub enum Grammar {
     Literal(Vec<RangeInclusive<char>>),
-    Alternation(Vec<Rc<Grammar>>),
+    /// The alternatives, each with the weight of its probability to be chosen
+    Alternation(Vec<Rc<Grammar>>, Vec<f64>),
     Concatenation(Vec<Rc<Grammar>>),
-    Repetition(Rc<Grammar>, Range<usize>),
+    Repetition(Rc<Grammar>, Range<usize>, LengthDistribution),
     Recurse(Weak<Grammar>),
     Recursive(Rc<Grammar>),
+    Rule(String, Rc<Grammar>),
 }
 
+/// A grammar matching the strings matched by the regular expression.
+///
+/// Anchors and word boundaries are ignored: the generated strings are always matched by the regular
+/// expression as a whole.
+///
+/// # Panics
+/// Panics if the regular expression is invalid. Use [`try_regex`] to handle the error instead.
 #[cfg(feature = "regex_grammar")]
 #[doc(cfg(feature = "regex_grammar"))]
 #[no_coverage]
 pub fn regex(s: &str) -> Rc<Grammar> {
-    grammar_from_regex(s)
+    try_regex(s).unwrap_or_else(
+        #[no_coverage]
+        |e| panic!("invalid regular expression {:?}: {}", s, e),
+    )
+}
+
+/// A grammar matching the strings matched by the regular expression, or an error if the regular expression is invalid.
+///
+/// See [`regex`] for more details.
+#[cfg(feature = "regex_grammar")]
+#[doc(cfg(feature = "regex_grammar"))]
+#[no_coverage]
+pub fn try_regex(s: &str) -> Result<Rc<Grammar>, GrammarParseError> {
+    grammar_from_regex(s, false)
+}
+
+/// A grammar matching the byte strings matched by the regular expression.
+///
+/// Each character of the grammar stands for the byte of the same value, such that the syntax trees it generates
+/// can be turned into a `Vec<u8>` with [`ASTMutator::with_bytes`](crate::mutators::grammar::ASTMutator::with_bytes).
+/// Unicode support is disabled by default, so `.` and the character classes match any byte. It can be enabled
+/// with the `u` flag, e.g. `(?u:é)`, in which case the characters are matched by their UTF-8 encoding.
+///
+/// # Panics
+/// Panics if the regular expression is invalid. Use [`try_bytes_regex`] to handle the error instead.
+#[cfg(feature = "regex_grammar")]
+#[doc(cfg(feature = "regex_grammar"))]
+#[no_coverage]
+pub fn bytes_regex(s: &str) -> Rc<Grammar> {
+    try_bytes_regex(s).unwrap_or_else(
+        #[no_coverage]
+        |e| panic!("invalid regular expression {:?}: {}", s, e),
+    )
+}
+
+/// A grammar matching the byte strings matched by the regular expression, or an error if the regular expression
+/// is invalid.
+///
+/// See [`bytes_regex`] for more details.
+#[cfg(feature = "regex_grammar")]
+#[doc(cfg(feature = "regex_grammar"))]
+#[no_coverage]
+pub fn try_bytes_regex(s: &str) -> Result<Rc<Grammar>, GrammarParseError> {
+    grammar_from_regex(s, true)
 }
 #[no_coverage]
 pub fn literal_ranges(ranges: Vec<RangeInclusive<char>>) -> Rc<Grammar> {
@@ -50,7 +105,25 @@ where
 }
 #[no_coverage]
 pub fn alternation(gs: impl IntoIterator<Item = Rc<Grammar>>) -> Rc<Grammar> {
-    Rc::new(Grammar::Alternation(gs.into_iter().collect()))
+    let gs = gs.into_iter().collect::<Vec<_>>();
+    let weights = vec![1.0; gs.len()];
+    Rc::new(Grammar::Alternation(gs, weights))
+}
+/// An alternation where each alternative is chosen with a probability proportional to its weight.
+///
+/// The weights do not need to add up to 1. They only affect how often each alternative is chosen
+/// when a new syntax tree is generated randomly.
+///
+/// ```
+/// use fuzzcheck::mutators::grammar::{literal, weighted_alternation};
+///
+/// // `a` is generated nine times more often than `b`
+/// let rule = weighted_alternation([(literal('a'), 9.0), (literal('b'), 1.0)]);
+/// ```
+#[no_coverage]
+pub fn weighted_alternation(gs: impl IntoIterator<Item = (Rc<Grammar>, f64)>) -> Rc<Grammar> {
+    let (gs, weights) = gs.into_iter().unzip();
+    Rc::new(Grammar::Alternation(gs, weights))
 }
 #[no_coverage]
 pub fn concatenation(gs: impl IntoIterator<Item = Rc<Grammar>>) -> Rc<Grammar> {
@@ -58,6 +131,24 @@ pub fn concatenation(gs: impl IntoIterator<Item = Rc<Grammar>>) -> Rc<Grammar> {
 }
 #[no_coverage]
 pub fn repetition<R>(gs: Rc<Grammar>, range: R) -> Rc<Grammar>
+where
+    R: RangeBounds<usize>,
+{
+    repetition_with_distribution(gs, range, LengthDistribution::Uniform)
+}
+/// A repetition whose number of items is chosen according to the given distribution when a new syntax tree
+/// is generated randomly.
+///
+/// ```
+/// use fuzzcheck::mutators::grammar::{literal, repetition_with_distribution, LengthDistribution};
+///
+/// // usually a few items, rarely more than ten
+/// let rule = repetition_with_distribution(literal('a'), 1.., LengthDistribution::Geometric(0.7));
+/// // one or two items, twice as often one as two
+/// let rule = repetition_with_distribution(literal('a'), 1..=2, LengthDistribution::Weights(vec![0.0, 2.0, 1.0]));
+/// ```
+#[no_coverage]
+pub fn repetition_with_distribution<R>(gs: Rc<Grammar>, range: R, distribution: LengthDistribution) -> Rc<Grammar>
 where
     R: RangeBounds<usize>,
 {
@@ -71,7 +162,17 @@ where
         std::ops::Bound::Excluded(x) => *x,
         std::ops::Bound::Unbounded => usize::MAX,
     };
-    Rc::new(Grammar::Repetition(gs, start..end))
+    Rc::new(Grammar::Repetition(gs, start..end, distribution))
+}
+
+/// A named grammar rule.
+///
+/// The syntax trees generated by the rule are annotated with its name, which makes it possible to
+/// [visit](crate::mutators::grammar::AST::visit) them. Crossover mutations only replace them with subtrees
+/// that were generated by a rule of the same name.
+#[no_coverage]
+pub fn rule(name: &str, g: Rc<Grammar>) -> Rc<Grammar> {
+    Rc::new(Grammar::Rule(name.to_owned(), g))
 }
 
 #[no_coverage]
@@ -5,18 +5,35 @@
 //! transformed into a `Mutator<(AST, String)>`, where the second element of the tuple is the string corresponding
 //! to the abstract syntax tree, by calling [`.with_string()`](ASTMutator::with_string).
 //!
+//! Existing strings can be turned into the syntax trees of a grammar with a [`GrammarParser`], and
+//! [`grammar_based_string_mutator`] creates a `Mutator<String>` which parses the strings it is given,
+//! such that the corpus can hold plain text files. Alternatively, [`GrammarSerializer`] saves the values of
+//! the [`.with_string()`](ASTMutator::with_string) mutator as plain text files.
+//!
+//! The [`GrammarCoverageSensor`] records which rules, alternatives, and repetition lengths of the grammar are used
+//! by the tested syntax trees, and the [`GrammarCoveragePool`] keeps the inputs using rarely used productions.
+//!
 //! To specify a grammar, you should use the following functions:
 #![cfg_attr(
     feature = "regex_grammar",
-    doc = "* [`regex`](crate::mutators::grammar::regex) to create a grammar from a regular expression **(only supported on crate feature `regex_grammar`)**"
+    doc = "* [`regex`](crate::mutators::grammar::regex) to create a grammar from a regular expression, or [`bytes_regex`](crate::mutators::grammar::bytes_regex) for a grammar of bytes **(only supported on crate feature `regex_grammar`)**"
 )]
 //! * [`literal`] for a grammar that matches a single character
 //! * [`literal_ranges`] for a grammar matching a single character within a specified ranges
 //! * [`literal_ranges`] for a grammar matching a single character within any of multiple ranges
-//! * [`alternation`] for a grammar matching any of a list of grammar rules
+//! * [`alternation`] for a grammar matching any of a list of grammar rules, or [`weighted_alternation`]
+//! to choose some rules more often than others
 //! * [`concatenation`] matching multiple grammar rules one after the other
-//! * [`repetition`] matching a grammar rule multiple times
+//! * [`repetition`] matching a grammar rule multiple times, or [`repetition_with_distribution`] to choose
+//! the number of repetitions according to a [`LengthDistribution`]
 //! * [`recursive`] and [`recurse`] to create recursive grammar rules
+//! * [`rule`] to name a grammar rule, such that the syntax trees it generates can be visited with an [`ASTVisitor`]
+//! * [`ebnf`] and [`abnf`] to create a grammar from a grammar file written in EBNF or ABNF, or
+//! [`GrammarRules`] to access each of its named rules
+#![cfg_attr(
+    feature = "pest_grammar",
+    doc = "* [`pest`](crate::mutators::grammar::pest()) to create a grammar from a rule of a pest grammar **(only supported on crate feature `pest_grammar`)**"
+)]
 #![cfg_attr(
     feature = "regex_grammar",
     doc = r###"
@@ -63,23 +80,44 @@ let rule = recursive(|rule| {
 #![allow(clippy::nonstandard_macro_braces)]
 
 mod ast;
+mod bnf;
+mod coverage;
 mod grammar;
 mod mutators;
+mod parser;
+
+#[cfg(feature = "pest_grammar")]
+mod pest;
 
 #[cfg(feature = "regex_grammar")]
 mod regex;
 
 #[doc(inline)]
-pub use ast::AST;
+pub use ast::{ASTVisitor, AST};
+#[doc(inline)]
+pub use bnf::{abnf, ebnf, GrammarParseError, GrammarRules};
+#[doc(inline)]
+pub use coverage::{GrammarCoveragePool, GrammarCoveragePoolStats, GrammarCoverageRecorder, GrammarCoverageSensor};
 #[cfg(feature = "regex_grammar")]
 #[doc(inline)]
 #[doc(cfg(feature = "regex_grammar"))]
-pub use grammar::regex;
+pub use grammar::{bytes_regex, regex, try_bytes_regex, try_regex};
 #[doc(inline)]
 pub use grammar::Grammar;
 #[doc(inline)]
-pub use grammar::{alternation, concatenation, literal, literal_range, literal_ranges, recurse, recursive, repetition};
+pub use grammar::{
+    alternation, concatenation, literal, literal_range, literal_ranges, recurse, recursive, repetition,
+    repetition_with_distribution, rule, weighted_alternation,
+};
 #[doc(inline)]
-pub use mutators::grammar_based_ast_mutator;
+pub use mutators::{grammar_based_ast_mutator, grammar_based_string_mutator, GrammarStringMutator};
 #[doc(inline)]
 pub use mutators::ASTMutator;
+#[doc(inline)]
+pub use parser::{GrammarParser, GrammarSerializer};
+#[doc(no_inline)]
+pub use crate::mutators::vector::LengthDistribution;
+#[cfg(feature = "pest_grammar")]
+#[doc(inline)]
+#[doc(cfg(feature = "pest_grammar"))]
+pub use self::pest::pest;
@@ -1,6 +1,6 @@
 extern crate self as fuzzcheck;
 
-use std::any::Any;
+use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::rc::{Rc, Weak};
 
@@ -12,11 +12,13 @@ use crate::mutators::character_classes::CharacterMutator;
 use crate::mutators::either::Either;
 use crate::mutators::fixed_len_vector::FixedLenVecMutator;
 use crate::mutators::grammar::ast::AST;
-use crate::mutators::map::AndMapMutator;
+use crate::mutators::grammar::parser::GrammarParser;
+use crate::mutators::map::{AndMapMutator, MapMutator};
 use crate::mutators::recursive::{RecurToMutator, RecursiveMutator};
 use crate::mutators::tuples::Tuple1Mutator;
 use crate::mutators::vector::VecMutator;
-use crate::Mutator;
+use crate::mutators::CrossoverStep;
+use crate::{Mutator, SubValueProvider, SubValueProviderId, CROSSOVER_RATE};
 
 make_single_variant_mutator! {
     pub enum AST {
@@ -37,7 +39,7 @@ type InnerASTMutator = Either<
                 >,
             >,
         >,
-        RecursiveMutator<ASTMutator>,
+        Either<RecursiveMutator<ASTMutator>, ASTRuleMutator>,
     >,
 >;
 
@@ -61,6 +63,23 @@ impl ASTMutator {
             String::with_capacity(16),
         )
     }
+
+    /// Transform the mutator into a `Mutator<(Vec<u8>, AST)>`, where each character of the syntax tree
+    /// is converted to the byte of the same value.
+    ///
+    /// It is meant to be used with grammars of bytes, such as those created by
+    /// [`bytes_regex`](crate::mutators::grammar::bytes_regex), whose characters are all below `'\u{100}'`.
+    #[no_coverage]
+    pub fn with_bytes(self) -> impl Mutator<(Vec<u8>, AST)> {
+        AndMapMutator::new(
+            self,
+            |x, bytes| {
+                bytes.clear();
+                x.generate_bytes_in(bytes);
+            },
+            Vec::<u8>::with_capacity(16),
+        )
+    }
 }
 
 #[derive(Clone)]
@@ -211,6 +230,38 @@ pub fn grammar_based_ast_mutator(grammar: Rc<Grammar>) -> ASTMutator {
     ASTMutator::from_grammar(grammar)
 }
 
+/// The mutator of the strings matched by a grammar, created by [`grammar_based_string_mutator`]
+pub type GrammarStringMutator = impl Mutator<String>;
+
+/// Create a mutator of the strings matched by the grammar.
+///
+/// Unlike [`ASTMutator::with_string`], the values of the mutator are plain strings, which can be saved
+/// in the corpus with a [`StringSerializer`](crate::StringSerializer). Each string is parsed into its syntax
+/// tree by a [`GrammarParser`] when it is validated, for example when the corpus is loaded. Strings that
+/// do not match the grammar are rejected.
+///
+/// ```
+/// use fuzzcheck::mutators::grammar::*;
+/// use fuzzcheck::Mutator;
+///
+/// let mutator = grammar_based_string_mutator(repetition(literal_range('a'..='z'), 1..10));
+/// assert!(mutator.validate_value(&"hello".to_owned()).is_some());
+/// assert!(mutator.validate_value(&"Hello".to_owned()).is_none());
+/// ```
+#[no_coverage]
+pub fn grammar_based_string_mutator(grammar: Rc<Grammar>) -> GrammarStringMutator {
+    let parser = GrammarParser::new(grammar.clone());
+    MapMutator::new(
+        grammar_based_ast_mutator(grammar),
+        #[no_coverage]
+        move |string: &String| parser.parse(string),
+        #[no_coverage]
+        |ast: &AST| ast.to_string(),
+        #[no_coverage]
+        |_, cplx| cplx,
+    )
+}
+
 impl ASTMutator {
     #[no_coverage]
     fn token(m: CharacterMutator) -> Self {
@@ -245,7 +296,13 @@ impl ASTMutator {
     #[no_coverage]
     fn recursive(m: impl FnMut(&Weak<Self>) -> Self) -> Self {
         Self {
-            inner: Box::new(Either::Right(Either::Right(RecursiveMutator::new(m)))),
+            inner: Box::new(Either::Right(Either::Right(Either::Left(RecursiveMutator::new(m))))),
+        }
+    }
+    #[no_coverage]
+    fn rule(m: ASTRuleMutator) -> Self {
+        Self {
+            inner: Box::new(Either::Right(Either::Right(Either::Right(m)))),
         }
     }
 
@@ -262,15 +319,18 @@ impl ASTMutator {
     ) -> Self {
         match grammar.as_ref() {
             Grammar::Literal(l) => Self::token(CharacterMutator::new(l.clone())),
-            Grammar::Alternation(gs) => Self::alternation(AlternationMutator::new(
-                gs.iter()
-                    .map(
-                        #[no_coverage]
-                        |g| Self::from_grammar_rec(g.clone(), others),
-                    )
-                    .collect(),
-                0.0,
-            )),
+            Grammar::Alternation(gs, weights) => Self::alternation(
+                AlternationMutator::new(
+                    gs.iter()
+                        .map(
+                            #[no_coverage]
+                            |g| Self::from_grammar_rec(g.clone(), others),
+                        )
+                        .collect(),
+                    0.0,
+                )
+                .with_weights(weights.clone()),
+            ),
             Grammar::Concatenation(gs) => {
                 let mut ms = Vec::<ASTMutator>::new();
                 for g in gs {
@@ -279,11 +339,14 @@ impl ASTMutator {
                 }
                 Self::sequence(Either::Left(FixedLenVecMutator::new(ms, false)))
             }
-            Grammar::Repetition(g, range) => Self::sequence(Either::Right(VecMutator::new(
-                Self::from_grammar_rec(g.clone(), others),
-                range.start..=range.end - 1,
-                false,
-            ))),
+            Grammar::Repetition(g, range, distribution) => Self::sequence(Either::Right(
+                VecMutator::new(
+                    Self::from_grammar_rec(g.clone(), others),
+                    range.start..=range.end - 1,
+                    false,
+                )
+                .with_length_distribution(distribution.clone()),
+            )),
             Grammar::Recurse(g) => {
                 if let Some(m) = others.get(&g.as_ptr()) {
                     Self::recur(RecurToMutator::from(m))
@@ -299,6 +362,237 @@ impl ASTMutator {
                     Self::from_grammar_rec(g.clone(), others)
                 },
             ),
+            Grammar::Rule(name, g) => Self::rule(ASTRuleMutator::new(
+                name.clone(),
+                Self::from_grammar_rec(g.clone(), others),
+            )),
+        }
+    }
+}
+
+/// The mutator of the syntax trees generated by a named grammar rule.
+///
+/// The syntax trees are wrapped in an `AST::Rule` node holding the name of the rule. Crossover mutations
+/// replace the whole tree with a subtree of another test case that was generated by a rule of the same name.
+pub struct ASTRuleMutator {
+    rule: String,
+    mutator: ASTMutator,
+    rng: fastrand::Rng,
+}
+impl ASTRuleMutator {
+    #[no_coverage]
+    fn new(rule: String, mutator: ASTMutator) -> Self {
+        Self {
+            rule,
+            mutator,
+            rng: fastrand::Rng::new(),
+        }
+    }
+    /// The tree wrapped by the `AST::Rule` node, if the node was generated by this rule
+    #[no_coverage]
+    fn inner<'a>(&self, value: &'a AST) -> Option<&'a AST> {
+        match value {
+            AST::Rule { rule, ast } if *rule == self.rule => Some(ast),
+            _ => None,
         }
     }
+    #[no_coverage]
+    fn inner_mut(value: &mut AST) -> &mut AST {
+        match value {
+            AST::Rule { ast, .. } => ast,
+            _ => unreachable!(),
+        }
+    }
+    #[no_coverage]
+    fn wrap(&self, ast: AST) -> AST {
+        AST::Rule {
+            rule: self.rule.clone(),
+            ast: Box::new(ast),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ASTRuleMutationStep {
+    crossover_step: CrossoverStep<AST>,
+    inner: ASTMutatorMutationStep,
+}
+
+pub enum ASTRuleUnmutateToken {
+    Replace(AST, ASTMutatorCache),
+    Inner(ASTMutatorUnmutateToken),
+}
+
+/// A subvalue provider that only gives the subtrees annotated with a rule name.
+///
+/// It is passed to the mutator of the tree wrapped by an `AST::Rule` node, so that
+/// anonymous parts of the tree are never replaced by parts of a different rule.
+struct RuleSubValueProvider<'a> {
+    provider: &'a dyn SubValueProvider,
+}
+impl<'a> RuleSubValueProvider<'a> {
+    #[no_coverage]
+    fn filter(subvalue: Option<(&'a dyn Any, f64)>) -> Option<(&'a dyn Any, f64)> {
+        match subvalue {
+            Some((value, cplx)) if matches!(value.downcast_ref::<AST>(), Some(AST::Rule { .. })) => Some((value, cplx)),
+            _ => None,
+        }
+    }
+}
+impl<'a> SubValueProvider for RuleSubValueProvider<'a> {
+    #[no_coverage]
+    fn identifier(&self) -> SubValueProviderId {
+        self.provider.identifier()
+    }
+
+    #[no_coverage]
+    fn get_random_subvalue(&self, typeid: TypeId, max_cplx: f64) -> Option<(&dyn Any, f64)> {
+        Self::filter(self.provider.get_random_subvalue(typeid, max_cplx))
+    }
+
+    #[no_coverage]
+    fn get_subvalue(&self, typeid: TypeId, max_cplx: f64, index: &mut usize) -> Option<(&dyn Any, f64)> {
+        loop {
+            let subvalue = self.provider.get_subvalue(typeid, max_cplx, index)?;
+            if let Some(subvalue) = Self::filter(Some(subvalue)) {
+                return Some(subvalue);
+            }
+        }
+    }
+}
+
+impl Mutator<AST> for ASTRuleMutator {
+    #[doc(hidden)]
+    type Cache = ASTMutatorCache;
+    #[doc(hidden)]
+    type MutationStep = ASTRuleMutationStep;
+    #[doc(hidden)]
+    type ArbitraryStep = ASTMutatorArbitraryStep;
+    #[doc(hidden)]
+    type UnmutateToken = ASTRuleUnmutateToken;
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+        self.mutator.default_arbitrary_step()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn is_valid(&self, value: &AST) -> bool {
+        match self.inner(value) {
+            Some(ast) => self.mutator.is_valid(ast),
+            None => false,
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn validate_value(&self, value: &AST) -> Option<Self::Cache> {
+        self.mutator.validate_value(self.inner(value)?)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_mutation_step(&self, value: &AST, cache: &Self::Cache) -> Self::MutationStep {
+        ASTRuleMutationStep {
+            crossover_step: CrossoverStep::default(),
+            inner: self.mutator.default_mutation_step(self.inner(value).unwrap(), cache),
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        self.mutator.global_search_space_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        self.mutator.max_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        self.mutator.min_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn complexity(&self, value: &AST, cache: &Self::Cache) -> f64 {
+        self.mutator.complexity(self.inner(value).unwrap(), cache)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(AST, f64)> {
+        let (ast, cplx) = self.mutator.ordered_arbitrary(step, max_cplx)?;
+        Some((self.wrap(ast), cplx))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_arbitrary(&self, max_cplx: f64) -> (AST, f64) {
+        let (ast, cplx) = self.mutator.random_arbitrary(max_cplx);
+        (self.wrap(ast), cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut AST,
+        cache: &mut Self::Cache,
+        step: &mut Self::MutationStep,
+        subvalue_provider: &dyn crate::SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        if self.rng.u8(..CROSSOVER_RATE) == 0 {
+            // only the subtrees generated by the same rule can replace the value
+            while let Some((subvalue, subcplx)) = step.crossover_step.get_next_subvalue(subvalue_provider, max_cplx) {
+                if let Some(subcache) = self.validate_value(subvalue) {
+                    let old_value = std::mem::replace(value, subvalue.clone());
+                    let old_cache = std::mem::replace(cache, subcache);
+                    return Some((ASTRuleUnmutateToken::Replace(old_value, old_cache), subcplx));
+                }
+            }
+        }
+        let (token, cplx) = self.mutator.ordered_mutate(
+            Self::inner_mut(value),
+            cache,
+            &mut step.inner,
+            &RuleSubValueProvider {
+                provider: subvalue_provider,
+            },
+            max_cplx,
+        )?;
+        Some((ASTRuleUnmutateToken::Inner(token), cplx))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_mutate(&self, value: &mut AST, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
+        let (token, cplx) = self.mutator.random_mutate(Self::inner_mut(value), cache, max_cplx);
+        (ASTRuleUnmutateToken::Inner(token), cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn unmutate(&self, value: &mut AST, cache: &mut Self::Cache, t: Self::UnmutateToken) {
+        match t {
+            ASTRuleUnmutateToken::Replace(old_value, old_cache) => {
+                *value = old_value;
+                *cache = old_cache;
+            }
+            ASTRuleUnmutateToken::Inner(t) => self.mutator.unmutate(Self::inner_mut(value), cache, t),
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn visit_subvalues<'a>(&self, value: &'a AST, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
+        self.mutator.visit_subvalues(self.inner(value).unwrap(), cache, visit);
+    }
 }
@@ -0,0 +1,597 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+use super::ast::AST;
+use super::grammar::Grammar;
+use crate::Serializer;
+
+/// The maximum number of optional items of a repetition that are matched by a chain of nonterminals
+const MAX_CHAINED_TAIL_LEN: usize = 32;
+
+/// A symbol of a production of the context-free grammar used by the parser
+#[derive(Clone)]
+enum Symbol {
+    Char(Vec<RangeInclusive<char>>),
+    NonTerminal(usize),
+}
+
+/// How the syntax tree of a nonterminal is built from the trees of the symbols of its production
+enum Shape {
+    /// `AST::Token` of the matched character
+    Token,
+    /// The tree of the only symbol of the production
+    Transparent,
+    /// `AST::Sequence` of the trees of all the symbols of the production
+    Sequence,
+    /// `AST::Sequence` of the trees of all the symbols of the production, where the last
+    /// symbol is a repetition tail whose items are appended to the sequence
+    Repetition,
+    /// The remaining items of a repetition, as a chain of nonterminals matching one fewer item each
+    Tail,
+    /// The remaining items of a repetition with a large or no upper bound. It is left-recursive, which
+    /// Earley parsers handle in linear time, and the number of items is only checked when building the tree.
+    LongTail { item: usize, max_len: usize },
+    /// `AST::Sequence` containing the tree of the recursive grammar
+    Recurse,
+    /// `AST::Rule` wrapping the tree of the only symbol of the production
+    Rule(String),
+}
+
+struct NonTerminal {
+    shape: Shape,
+    productions: Vec<Vec<Symbol>>,
+}
+
+/// The syntax tree of a nonterminal
+#[derive(Clone)]
+enum Tree {
+    Node(AST),
+    Items(Vec<AST>),
+}
+impl Tree {
+    #[no_coverage]
+    fn into_ast(self) -> AST {
+        match self {
+            Tree::Node(ast) => ast,
+            Tree::Items(_) => unreachable!(),
+        }
+    }
+    #[no_coverage]
+    fn into_items(self) -> Vec<AST> {
+        match self {
+            Tree::Items(items) => items,
+            Tree::Node(_) => unreachable!(),
+        }
+    }
+}
+
+/// A parser of the strings matched by a [`Grammar`].
+///
+/// The syntax tree returned by [`parse`](GrammarParser::parse) has the exact shape of the trees
+/// generated by the [`ASTMutator`](crate::mutators::grammar::ASTMutator) of the same grammar. It can
+/// therefore be used to import existing text inputs into a grammar-based fuzz test.
+///
+/// The parser is an Earley parser, which accepts any grammar, including ambiguous and left-recursive
+/// ones. When a string can be parsed in multiple ways, only one of its syntax trees is returned.
+///
+/// ```
+/// use fuzzcheck::mutators::grammar::*;
+/// use fuzzcheck::Mutator;
+///
+/// let grammar = concatenation([rule("sign", repetition(literal('-'), 0..=1)), repetition(literal_range('0'..='9'), 1..)]);
+/// let parser = GrammarParser::new(grammar.clone());
+/// let mutator = grammar_based_ast_mutator(grammar);
+///
+/// let ast = parser.parse("-1234").unwrap();
+/// assert_eq!(ast.to_string(), "-1234");
+/// assert!(mutator.is_valid(&ast));
+///
+/// assert!(parser.parse("12-34").is_none());
+/// ```
+pub struct GrammarParser {
+    nonterminals: Vec<NonTerminal>,
+    nullable: Vec<bool>,
+}
+
+impl GrammarParser {
+    #[no_coverage]
+    pub fn new(grammar: Rc<Grammar>) -> Self {
+        let mut parser = Self {
+            nonterminals: vec![],
+            nullable: vec![],
+        };
+        let mut ids = HashMap::new();
+        let start = parser.add_grammar(&grammar, &mut ids);
+        assert_eq!(start, 0);
+        parser.nullable = parser.compute_nullable();
+        parser
+    }
+
+    /// Parse the string into the syntax tree that the grammar-based mutator would generate for it,
+    /// or return `None` if the string is not matched by the grammar
+    #[no_coverage]
+    pub fn parse(&self, string: &str) -> Option<AST> {
+        let chars = string.chars().collect::<Vec<_>>();
+        let chart = self.recognize(&chars);
+        if !chart.matches(0, 0, chars.len()) {
+            return None;
+        }
+        let mut builder = TreeBuilder {
+            parser: self,
+            chars: &chars,
+            chart: &chart,
+            memo: HashMap::new(),
+            in_progress: HashSet::new(),
+            hit_cycle: false,
+        };
+        builder.build(0, 0, chars.len()).map(Tree::into_ast)
+    }
+
+    #[no_coverage]
+    fn push(&mut self, shape: Shape, productions: Vec<Vec<Symbol>>) -> usize {
+        self.nonterminals.push(NonTerminal { shape, productions });
+        self.nonterminals.len() - 1
+    }
+
+    #[no_coverage]
+    fn add_grammar(&mut self, grammar: &Rc<Grammar>, ids: &mut HashMap<*const Grammar, usize>) -> usize {
+        let ptr = Rc::as_ptr(grammar);
+        if let Some(id) = ids.get(&ptr) {
+            return *id;
+        }
+        // the nonterminal is registered before its productions are created, such that
+        // recursive grammars can refer to it
+        let id = self.push(Shape::Transparent, vec![]);
+        ids.insert(ptr, id);
+
+        let (shape, productions) = match grammar.as_ref() {
+            Grammar::Literal(ranges) => (Shape::Token, vec![vec![Symbol::Char(ranges.clone())]]),
+            Grammar::Alternation(gs, _) => (
+                Shape::Transparent,
+                gs.iter()
+                    .map(
+                        #[no_coverage]
+                        |g| vec![Symbol::NonTerminal(self.add_grammar(g, ids))],
+                    )
+                    .collect(),
+            ),
+            Grammar::Concatenation(gs) => (
+                Shape::Sequence,
+                vec![gs
+                    .iter()
+                    .map(
+                        #[no_coverage]
+                        |g| Symbol::NonTerminal(self.add_grammar(g, ids)),
+                    )
+                    .collect()],
+            ),
+            Grammar::Repetition(g, range, _) => {
+                if range.is_empty() {
+                    (Shape::Repetition, vec![])
+                } else {
+                    let item = self.add_grammar(g, ids);
+                    let max_len = if range.end == usize::MAX {
+                        usize::MAX
+                    } else {
+                        range.end - 1 - range.start
+                    };
+                    let tail = if max_len <= MAX_CHAINED_TAIL_LEN {
+                        self.tail(item, max_len)
+                    } else {
+                        self.long_tail(item, max_len)
+                    };
+                    let mut symbols = vec![Symbol::NonTerminal(item); range.start];
+                    symbols.push(Symbol::NonTerminal(tail));
+                    (Shape::Repetition, vec![symbols])
+                }
+            }
+            Grammar::Recurse(g) => {
+                let g = g.upgrade().expect("the recursive grammar should be alive");
+                (
+                    Shape::Recurse,
+                    vec![vec![Symbol::NonTerminal(self.add_grammar(&g, ids))]],
+                )
+            }
+            Grammar::Recursive(g) => (
+                Shape::Transparent,
+                vec![vec![Symbol::NonTerminal(self.add_grammar(g, ids))]],
+            ),
+            Grammar::Rule(name, g) => (
+                Shape::Rule(name.clone()),
+                vec![vec![Symbol::NonTerminal(self.add_grammar(g, ids))]],
+            ),
+        };
+        self.nonterminals[id] = NonTerminal { shape, productions };
+        id
+    }
+
+    #[no_coverage]
+    fn long_tail(&mut self, item: usize, max_len: usize) -> usize {
+        let tail = self.push(Shape::LongTail { item, max_len }, vec![]);
+        self.nonterminals[tail].productions = vec![vec![], vec![Symbol::NonTerminal(tail), Symbol::NonTerminal(item)]];
+        tail
+    }
+
+    #[no_coverage]
+    fn tail(&mut self, item: usize, max_len: usize) -> usize {
+        let mut tail = self.push(Shape::Tail, vec![vec![]]);
+        for _ in 0..max_len {
+            tail = self.push(
+                Shape::Tail,
+                vec![vec![], vec![Symbol::NonTerminal(item), Symbol::NonTerminal(tail)]],
+            );
+        }
+        tail
+    }
+
+    #[no_coverage]
+    fn compute_nullable(&self) -> Vec<bool> {
+        let mut nullable = vec![false; self.nonterminals.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (id, nonterminal) in self.nonterminals.iter().enumerate() {
+                if nullable[id] {
+                    continue;
+                }
+                let is_nullable = nonterminal.productions.iter().any(
+                    #[no_coverage]
+                    |symbols| {
+                        symbols.iter().all(
+                            #[no_coverage]
+                            |symbol| matches!(symbol, Symbol::NonTerminal(nt) if nullable[*nt]),
+                        )
+                    },
+                );
+                if is_nullable {
+                    nullable[id] = true;
+                    changed = true;
+                }
+            }
+        }
+        nullable
+    }
+
+    /// Run the Earley recognizer over the characters and return the substrings matched by each nonterminal
+    #[no_coverage]
+    fn recognize(&self, chars: &[char]) -> Chart {
+        let mut sets = vec![Vec::<Item>::new(); chars.len() + 1];
+        let mut seen = vec![HashSet::<Item>::new(); chars.len() + 1];
+        let mut chart = Chart::default();
+
+        let mut add = #[no_coverage]
+        |sets: &mut Vec<Vec<Item>>, pos: usize, item: Item| {
+            if seen[pos].insert(item) {
+                sets[pos].push(item);
+            }
+        };
+        for production in 0..self.nonterminals[0].productions.len() {
+            add(&mut sets, 0, Item::new(0, production, 0));
+        }
+
+        for pos in 0..=chars.len() {
+            let mut i = 0;
+            while i < sets[pos].len() {
+                let item = sets[pos][i];
+                i += 1;
+                match self.next_symbol(item) {
+                    None => {
+                        chart.insert(item.nonterminal, item.origin, pos);
+                        let parents = sets[item.origin]
+                            .iter()
+                            .filter(
+                                #[no_coverage]
+                                |parent| matches!(self.next_symbol(**parent), Some(Symbol::NonTerminal(nt)) if *nt == item.nonterminal),
+                            )
+                            .copied()
+                            .collect::<Vec<_>>();
+                        for parent in parents {
+                            add(&mut sets, pos, parent.advance());
+                        }
+                    }
+                    Some(Symbol::Char(ranges)) => {
+                        if pos < chars.len() && char_in_ranges(chars[pos], ranges) {
+                            add(&mut sets, pos + 1, item.advance());
+                        }
+                    }
+                    Some(Symbol::NonTerminal(nt)) => {
+                        for production in 0..self.nonterminals[*nt].productions.len() {
+                            add(&mut sets, pos, Item::new(*nt, production, pos));
+                        }
+                        if self.nullable[*nt] {
+                            add(&mut sets, pos, item.advance());
+                        }
+                    }
+                }
+            }
+        }
+        chart
+    }
+
+    #[no_coverage]
+    fn next_symbol(&self, item: Item) -> Option<&Symbol> {
+        self.nonterminals[item.nonterminal].productions[item.production].get(item.dot)
+    }
+}
+
+#[no_coverage]
+fn char_in_ranges(c: char, ranges: &[RangeInclusive<char>]) -> bool {
+    ranges.iter().any(
+        #[no_coverage]
+        |range| range.contains(&c),
+    )
+}
+
+/// An Earley item: a production of a nonterminal, the position of the dot in the
+/// production, and the start position of the substring matched by the nonterminal
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Item {
+    nonterminal: usize,
+    production: usize,
+    dot: usize,
+    origin: usize,
+}
+impl Item {
+    #[no_coverage]
+    fn new(nonterminal: usize, production: usize, origin: usize) -> Self {
+        Self {
+            nonterminal,
+            production,
+            dot: 0,
+            origin,
+        }
+    }
+    #[no_coverage]
+    fn advance(self) -> Self {
+        Self {
+            dot: self.dot + 1,
+            ..self
+        }
+    }
+}
+
+/// The substrings matched by each nonterminal, found by the Earley recognizer
+#[derive(Default)]
+struct Chart {
+    /// the nonterminal, start position, and end position of each matched substring
+    spans: HashSet<(usize, usize, usize)>,
+    /// the end positions of the substrings matched by a nonterminal, keyed by nonterminal and start position
+    ends: HashMap<(usize, usize), Vec<usize>>,
+    /// the start positions of the substrings matched by a nonterminal, keyed by nonterminal and end position
+    starts: HashMap<(usize, usize), Vec<usize>>,
+}
+impl Chart {
+    #[no_coverage]
+    fn insert(&mut self, nonterminal: usize, start: usize, end: usize) {
+        if !self.spans.insert((nonterminal, start, end)) {
+            return;
+        }
+        self.ends.entry((nonterminal, start)).or_default().push(end);
+        self.starts.entry((nonterminal, end)).or_default().push(start);
+    }
+    #[no_coverage]
+    fn matches(&self, nonterminal: usize, start: usize, end: usize) -> bool {
+        self.spans.contains(&(nonterminal, start, end))
+    }
+    #[no_coverage]
+    fn ends(&self, nonterminal: usize, start: usize, max_end: usize) -> Vec<usize> {
+        Self::filter(self.ends.get(&(nonterminal, start)), |end| end <= max_end)
+    }
+    #[no_coverage]
+    fn starts(&self, nonterminal: usize, end: usize, min_start: usize) -> Vec<usize> {
+        Self::filter(self.starts.get(&(nonterminal, end)), |start| start >= min_start)
+    }
+    #[no_coverage]
+    fn filter(positions: Option<&Vec<usize>>, predicate: impl Fn(usize) -> bool) -> Vec<usize> {
+        positions.map_or(vec![], |positions| {
+            positions
+                .iter()
+                .copied()
+                .filter(
+                    #[no_coverage]
+                    |pos| predicate(*pos),
+                )
+                .collect()
+        })
+    }
+}
+
+/// Builds a syntax tree from the substrings matched by each nonterminal
+struct TreeBuilder<'a> {
+    parser: &'a GrammarParser,
+    chars: &'a [char],
+    chart: &'a Chart,
+    memo: HashMap<(usize, usize, usize), Option<Tree>>,
+    in_progress: HashSet<(usize, usize, usize)>,
+    hit_cycle: bool,
+}
+
+impl<'a> TreeBuilder<'a> {
+    /// Build the tree of the nonterminal matching the characters between `start` and `end`.
+    ///
+    /// A derivation that would require building the same tree again (through a cycle of empty
+    /// or single-symbol productions) is ignored, since a shorter derivation must then exist.
+    #[no_coverage]
+    fn build(&mut self, nonterminal: usize, start: usize, end: usize) -> Option<Tree> {
+        let key = (nonterminal, start, end);
+        if let Some(tree) = self.memo.get(&key) {
+            return tree.clone();
+        }
+        if !self.in_progress.insert(key) {
+            self.hit_cycle = true;
+            return None;
+        }
+        let outer_hit_cycle = std::mem::replace(&mut self.hit_cycle, false);
+
+        let parser = self.parser;
+        let nt = &parser.nonterminals[nonterminal];
+        let tree = if let Shape::LongTail { item, max_len } = nt.shape {
+            self.build_long_tail(nonterminal, item, max_len, start, end)
+        } else {
+            let mut tree = None;
+            for production in 0..nt.productions.len() {
+                let mut children = vec![];
+                if self.build_symbols(&nt.productions[production], start, end, &mut children) {
+                    tree = Some(Self::shape_tree(&nt.shape, children));
+                    break;
+                }
+            }
+            tree
+        };
+
+        self.in_progress.remove(&key);
+        if tree.is_some() || !self.hit_cycle {
+            self.memo.insert(key, tree.clone());
+        }
+        self.hit_cycle |= outer_hit_cycle;
+        tree
+    }
+
+    #[no_coverage]
+    fn build_symbols(&mut self, symbols: &[Symbol], start: usize, end: usize, children: &mut Vec<Tree>) -> bool {
+        match symbols.first() {
+            None => start == end,
+            Some(Symbol::Char(ranges)) => {
+                if start < end && char_in_ranges(self.chars[start], ranges) {
+                    children.push(Tree::Node(AST::Token(self.chars[start])));
+                    if self.build_symbols(&symbols[1..], start + 1, end, children) {
+                        return true;
+                    }
+                    children.pop();
+                }
+                false
+            }
+            Some(Symbol::NonTerminal(nonterminal)) => {
+                // the last symbol must match all the remaining characters
+                let mids = if symbols.len() == 1 {
+                    if self.chart.matches(*nonterminal, start, end) {
+                        vec![end]
+                    } else {
+                        vec![]
+                    }
+                } else {
+                    self.chart.ends(*nonterminal, start, end)
+                };
+                for mid in mids {
+                    if let Some(tree) = self.build(*nonterminal, start, mid) {
+                        children.push(tree);
+                        if self.build_symbols(&symbols[1..], mid, end, children) {
+                            return true;
+                        }
+                        children.pop();
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    /// Build the items of a long repetition iteratively, to avoid a recursion as deep as the number of items.
+    ///
+    /// The recognizer does not check the maximum number of items, so the substring is split into the
+    /// smallest number of items, with a breadth-first search from its end, which is then compared with `max_len`.
+    #[no_coverage]
+    fn build_long_tail(&mut self, tail: usize, item: usize, max_len: usize, start: usize, end: usize) -> Option<Tree> {
+        // the end position of the item starting at each visited position
+        let mut next_pos = HashMap::new();
+        next_pos.insert(end, end);
+        let mut frontier = vec![end];
+        let mut nbr_items = 0;
+        while !next_pos.contains_key(&start) {
+            if frontier.is_empty() || nbr_items == max_len {
+                return None;
+            }
+            nbr_items += 1;
+            for pos in std::mem::take(&mut frontier) {
+                for mid in self.chart.starts(item, pos, start) {
+                    if mid == pos
+                        || next_pos.contains_key(&mid)
+                        || (mid != start && !self.chart.matches(tail, start, mid))
+                    {
+                        continue;
+                    }
+                    next_pos.insert(mid, pos);
+                    frontier.push(mid);
+                }
+            }
+        }
+        let mut items = Vec::with_capacity(nbr_items);
+        let mut pos = start;
+        while pos != end {
+            let next = next_pos[&pos];
+            items.push(self.build(item, pos, next)?.into_ast());
+            pos = next;
+        }
+        Some(Tree::Items(items))
+    }
+
+    #[no_coverage]
+    fn shape_tree(shape: &Shape, mut children: Vec<Tree>) -> Tree {
+        match shape {
+            Shape::Token | Shape::Transparent => children.pop().unwrap(),
+            Shape::Sequence => Tree::Node(AST::Sequence(children.into_iter().map(Tree::into_ast).collect())),
+            Shape::Repetition => {
+                let tail = children.pop().unwrap().into_items();
+                let mut items = children.into_iter().map(Tree::into_ast).collect::<Vec<_>>();
+                items.extend(tail);
+                Tree::Node(AST::Sequence(items))
+            }
+            Shape::LongTail { .. } => unreachable!(),
+            Shape::Tail => {
+                if children.is_empty() {
+                    Tree::Items(vec![])
+                } else {
+                    let tail = children.pop().unwrap().into_items();
+                    let mut items = vec![children.pop().unwrap().into_ast()];
+                    items.extend(tail);
+                    Tree::Items(items)
+                }
+            }
+            Shape::Recurse => Tree::Node(AST::Sequence(vec![children.pop().unwrap().into_ast()])),
+            Shape::Rule(rule) => Tree::Node(AST::Rule {
+                rule: rule.clone(),
+                ast: Box::new(children.pop().unwrap().into_ast()),
+            }),
+        }
+    }
+}
+
+/// A serializer for the values generated by [`ASTMutator::with_string`](crate::mutators::grammar::ASTMutator::with_string).
+///
+/// Only the string is written to the corpus, as a plain text file. When the file is read back, the string is
+/// parsed into its syntax tree with a [`GrammarParser`]. Files that do not match the grammar are rejected.
+pub struct GrammarSerializer {
+    parser: GrammarParser,
+    extension: &'static str,
+}
+
+impl GrammarSerializer {
+    /// Create a serializer for the strings matched by the grammar, saved in files with the given extension
+    #[no_coverage]
+    pub fn new(grammar: Rc<Grammar>, extension: &'static str) -> Self {
+        Self {
+            parser: GrammarParser::new(grammar),
+            extension,
+        }
+    }
+}
+
+impl Serializer for GrammarSerializer {
+    type Value = (String, AST);
+
+    #[no_coverage]
+    fn extension(&self) -> &str {
+        self.extension
+    }
+    #[no_coverage]
+    fn from_data(&self, data: &[u8]) -> Option<Self::Value> {
+        let string = String::from_utf8(data.to_vec()).ok()?;
+        let ast = self.parser.parse(&string)?;
+        Some((string, ast))
+    }
+    #[no_coverage]
+    fn to_data(&self, value: &Self::Value) -> Vec<u8> {
+        value.0.clone().into_bytes()
+    }
+}
@@ -0,0 +1,458 @@
+//! Conversion of [pest](https://pest.rs) grammars to [`Grammar`].
+//!
+//! The rules of a `.pest` file are converted to [`GrammarRules`]. Sequences, choices, repetitions, strings,
+//! and character ranges are mapped onto the equivalent grammar combinators. As in pest, the implicit
+//! `WHITESPACE` and `COMMENT` rules are inserted between the elements of sequences and repetitions of
+//! non-atomic rules.
+//!
+//! Predicates (`&e` and `!e`) and stack operations (`PUSH`, `POP`, `PEEK`, `DROP`, etc.) cannot be represented
+//! by a [`Grammar`], so they are reported as errors.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+
+use ::pest::error::LineColLocation;
+use pest_meta::ast::{Expr as PestExpr, Rule as PestRule, RuleType};
+use pest_meta::parser::{self, Rule};
+
+use super::bnf::{Expr, GrammarParseError, GrammarRules};
+use super::grammar::Grammar;
+
+/// The built-in rules of pest that manipulate the stack
+const STACK_RULES: &[&str] = &["PUSH", "POP", "POP_ALL", "PEEK", "PEEK_ALL", "DROP"];
+
+impl GrammarRules {
+    /// Parse a grammar written in the syntax of [pest](https://pest.rs).
+    ///
+    /// The rules are listed in the order in which they are defined. Errors caused by unsupported constructs,
+    /// such as predicates and stack operations, are located at the definition of the rule using them.
+    #[no_coverage]
+    #[doc(cfg(feature = "pest_grammar"))]
+    pub fn from_pest(source: &str) -> Result<Self, GrammarParseError> {
+        let pairs = parser::parse(Rule::grammar_rules, source).map_err(
+            #[no_coverage]
+            |e| pest_error(&e),
+        )?;
+        let mut locations = HashMap::new();
+        for pair in pairs.clone() {
+            if pair.as_rule() != Rule::grammar_rule {
+                continue;
+            }
+            if let Some(name) = pair.into_inner().next().filter(
+                #[no_coverage]
+                |name| name.as_rule() == Rule::identifier,
+            ) {
+                locations.insert(name.as_str().to_owned(), name.as_span().start_pos().line_col());
+            }
+        }
+        let rules = parser::consume_rules(pairs).map_err(
+            #[no_coverage]
+            |errors| pest_error(&errors[0]),
+        )?;
+        PestConverter::new(&rules, locations).convert()
+    }
+}
+
+/// Parse a grammar written in the syntax of [pest](https://pest.rs) and return the grammar of the given rule.
+///
+/// See [`GrammarRules::from_pest`] for more details.
+#[no_coverage]
+pub fn pest(source: &str, rule: &str) -> Result<Rc<Grammar>, GrammarParseError> {
+    GrammarRules::from_pest(source)?.grammar(rule).ok_or_else(
+        #[no_coverage]
+        || GrammarParseError {
+            line: 1,
+            column: 1,
+            message: format!("the rule `{}` is not defined", rule),
+        },
+    )
+}
+
+#[no_coverage]
+fn pest_error(error: &::pest::error::Error<Rule>) -> GrammarParseError {
+    let (line, column) = match error.line_col {
+        LineColLocation::Pos(pos) => pos,
+        LineColLocation::Span(start, _) => start,
+    };
+    GrammarParseError {
+        line,
+        column,
+        message: error.variant.message().into_owned(),
+    }
+}
+
+/*
+    In pest, whether a rule is atomic depends on the context in which it is called: a normal rule called from
+    an atomic rule is atomic too. So each normal rule may be converted twice: once as a non-atomic rule, under its
+    own name, and once as an atomic rule, under a hidden name.
+*/
+struct PestConverter<'a> {
+    rules: HashMap<&'a str, &'a PestRule>,
+    order: Vec<&'a str>,
+    locations: HashMap<String, (usize, usize)>,
+    has_whitespace: bool,
+    has_comment: bool,
+    queue: VecDeque<(&'a str, bool)>,
+    queued: HashSet<(&'a str, bool)>,
+}
+
+impl<'a> PestConverter<'a> {
+    #[no_coverage]
+    fn new(rules: &'a [PestRule], locations: HashMap<String, (usize, usize)>) -> Self {
+        Self {
+            rules: rules
+                .iter()
+                .map(
+                    #[no_coverage]
+                    |rule| (rule.name.as_str(), rule),
+                )
+                .collect(),
+            order: rules
+                .iter()
+                .map(
+                    #[no_coverage]
+                    |rule| rule.name.as_str(),
+                )
+                .collect(),
+            locations,
+            has_whitespace: rules.iter().any(
+                #[no_coverage]
+                |rule| rule.name == "WHITESPACE",
+            ),
+            has_comment: rules.iter().any(
+                #[no_coverage]
+                |rule| rule.name == "COMMENT",
+            ),
+            queue: VecDeque::new(),
+            queued: HashSet::new(),
+        }
+    }
+
+    #[no_coverage]
+    fn convert(mut self) -> Result<GrammarRules, GrammarParseError> {
+        let mut result = GrammarRules::new(false);
+        for name in self.order.clone() {
+            let atomic = self.is_atomic(name, false);
+            self.enqueue(name, atomic);
+        }
+        while let Some((name, atomic)) = self.queue.pop_front() {
+            let rule: &'a PestRule = self.rules[name];
+            let expr = self.expr(&rule.expr, atomic, name)?;
+            if atomic == self.is_atomic(name, false) {
+                result.add_rule(name, expr);
+            } else {
+                result.add_variant_rule(&Self::atomic_variant_name(name), name, expr);
+            }
+        }
+        result.check()?;
+        Ok(result)
+    }
+
+    /// Whether the body of the rule is atomic when it is called from a context that is or isn't atomic
+    #[no_coverage]
+    fn is_atomic(&self, name: &str, context_is_atomic: bool) -> bool {
+        if name == "WHITESPACE" || name == "COMMENT" {
+            return true;
+        }
+        match self.rules[name].ty {
+            RuleType::Atomic | RuleType::CompoundAtomic => true,
+            RuleType::NonAtomic => false,
+            RuleType::Normal | RuleType::Silent => context_is_atomic,
+        }
+    }
+
+    #[no_coverage]
+    fn atomic_variant_name(name: &str) -> String {
+        format!("{}#atomic", name)
+    }
+
+    #[no_coverage]
+    fn enqueue(&mut self, name: &'a str, atomic: bool) {
+        if self.queued.insert((name, atomic)) {
+            self.queue.push_back((name, atomic));
+        }
+    }
+
+    #[no_coverage]
+    fn reference(&mut self, name: &'a str, context_is_atomic: bool, rule: &str) -> Expr {
+        let atomic = self.is_atomic(name, context_is_atomic);
+        self.enqueue(name, atomic);
+        let name = if atomic == self.is_atomic(name, false) {
+            name.to_owned()
+        } else {
+            Self::atomic_variant_name(name)
+        };
+        let (line, column) = self.location(rule);
+        Expr::Reference { name, line, column }
+    }
+
+    #[no_coverage]
+    fn location(&self, rule: &str) -> (usize, usize) {
+        self.locations.get(rule).copied().unwrap_or((1, 1))
+    }
+
+    #[no_coverage]
+    fn unsupported(&self, rule: &str, construct: &str) -> GrammarParseError {
+        let (line, column) = self.location(rule);
+        GrammarParseError {
+            line,
+            column,
+            message: format!("the rule `{}` uses {}, which are not supported", rule, construct),
+        }
+    }
+
+    /// The implicit whitespace and comments between the elements of a sequence in non-atomic rules
+    #[no_coverage]
+    fn skip(&mut self, rule: &str) -> Option<Expr> {
+        let mut alternatives = vec![];
+        if self.has_whitespace {
+            alternatives.push(self.reference("WHITESPACE", true, rule));
+        }
+        if self.has_comment {
+            alternatives.push(self.reference("COMMENT", true, rule));
+        }
+        let e = match alternatives.len() {
+            0 => return None,
+            1 => alternatives.pop().unwrap(),
+            _ => Expr::Alternation(alternatives),
+        };
+        Some(Expr::Repetition(Box::new(e), 0..usize::MAX))
+    }
+
+    /// Repeat `e` a number of times within `min..max`, separated by the implicit whitespace of non-atomic rules
+    #[no_coverage]
+    fn repeat(&mut self, e: Expr, min: usize, max: usize, atomic: bool, rule: &str) -> Expr {
+        let skip = if atomic { None } else { self.skip(rule) };
+        let skip = match skip {
+            Some(skip) => skip,
+            None => return Expr::Repetition(Box::new(e), min..max),
+        };
+        if max <= 1 {
+            return Expr::Concatenation(vec![]);
+        }
+        let rest_max = if max == usize::MAX { max } else { max - 1 };
+        let rest = Expr::Repetition(
+            Box::new(Expr::Concatenation(vec![skip, e.clone()])),
+            min.saturating_sub(1)..rest_max,
+        );
+        let repetition = Expr::Concatenation(vec![e, rest]);
+        if min == 0 {
+            Expr::Repetition(Box::new(repetition), 0..2)
+        } else {
+            repetition
+        }
+    }
+
+    #[no_coverage]
+    fn expr(&mut self, expr: &'a PestExpr, atomic: bool, rule: &'a str) -> Result<Expr, GrammarParseError> {
+        Ok(match expr {
+            PestExpr::Str(s) => Expr::string(s),
+            PestExpr::Insens(s) => Expr::case_insensitive_string(s),
+            PestExpr::Range(start, end) => {
+                let mut start_chars = start.chars();
+                let mut end_chars = end.chars();
+                match (
+                    start_chars.next(),
+                    start_chars.next(),
+                    end_chars.next(),
+                    end_chars.next(),
+                ) {
+                    (Some(start), None, Some(end), None) if start <= end => Expr::Literal(vec![start..=end]),
+                    _ => return Err(self.unsupported(rule, "invalid character ranges")),
+                }
+            }
+            PestExpr::Ident(name) => {
+                if self.rules.contains_key(name.as_str()) {
+                    self.reference(name, atomic, rule)
+                } else if let Some(e) = builtin(name) {
+                    e
+                } else if STACK_RULES.contains(&name.as_str()) {
+                    return Err(self.unsupported(rule, "stack operations"));
+                } else {
+                    return Err(self.unsupported(rule, &format!("built-in rules such as `{}`", name)));
+                }
+            }
+            PestExpr::PosPred(_) | PestExpr::NegPred(_) => return Err(self.unsupported(rule, "predicates")),
+            PestExpr::PeekSlice(..) | PestExpr::Push(_) => return Err(self.unsupported(rule, "stack operations")),
+            PestExpr::Seq(..) => {
+                let mut elements = vec![];
+                flatten_sequence(expr, &mut elements);
+                let mut items = vec![];
+                for (i, e) in elements.into_iter().enumerate() {
+                    if i > 0 && !atomic {
+                        items.extend(self.skip(rule));
+                    }
+                    items.push(self.expr(e, atomic, rule)?);
+                }
+                Expr::Concatenation(items)
+            }
+            PestExpr::Choice(..) => {
+                let mut elements = vec![];
+                flatten_choice(expr, &mut elements);
+                let mut alternatives = vec![];
+                for e in elements {
+                    alternatives.push(self.expr(e, atomic, rule)?);
+                }
+                Expr::Alternation(alternatives)
+            }
+            PestExpr::Opt(e) => Expr::Repetition(Box::new(self.expr(e, atomic, rule)?), 0..2),
+            PestExpr::Rep(e) => {
+                let e = self.expr(e, atomic, rule)?;
+                self.repeat(e, 0, usize::MAX, atomic, rule)
+            }
+            PestExpr::RepOnce(e) => {
+                let e = self.expr(e, atomic, rule)?;
+                self.repeat(e, 1, usize::MAX, atomic, rule)
+            }
+            PestExpr::RepExact(e, n) => {
+                let e = self.expr(e, atomic, rule)?;
+                self.repeat(e, *n as usize, *n as usize + 1, atomic, rule)
+            }
+            PestExpr::RepMin(e, min) => {
+                let e = self.expr(e, atomic, rule)?;
+                self.repeat(e, *min as usize, usize::MAX, atomic, rule)
+            }
+            PestExpr::RepMax(e, max) => {
+                let e = self.expr(e, atomic, rule)?;
+                self.repeat(e, 0, *max as usize + 1, atomic, rule)
+            }
+            PestExpr::RepMinMax(e, min, max) => {
+                let e = self.expr(e, atomic, rule)?;
+                self.repeat(e, *min as usize, *max as usize + 1, atomic, rule)
+            }
+            _ => return Err(self.unsupported(rule, "constructs specific to the pest optimizer")),
+        })
+    }
+}
+
+#[no_coverage]
+fn flatten_sequence<'a>(expr: &'a PestExpr, elements: &mut Vec<&'a PestExpr>) {
+    if let PestExpr::Seq(lhs, rhs) = expr {
+        flatten_sequence(lhs, elements);
+        flatten_sequence(rhs, elements);
+    } else {
+        elements.push(expr);
+    }
+}
+
+#[no_coverage]
+fn flatten_choice<'a>(expr: &'a PestExpr, elements: &mut Vec<&'a PestExpr>) {
+    if let PestExpr::Choice(lhs, rhs) = expr {
+        flatten_choice(lhs, elements);
+        flatten_choice(rhs, elements);
+    } else {
+        elements.push(expr);
+    }
+}
+
+/// The grammar of the built-in rules of pest that do not depend on the stack or on unicode properties
+#[no_coverage]
+fn builtin(name: &str) -> Option<Expr> {
+    let ranges = match name {
+        "ANY" => vec!['\0'..='\u{D7FF}', '\u{E000}'..=char::MAX],
+        "SOI" | "EOI" => return Some(Expr::Concatenation(vec![])),
+        "NEWLINE" => {
+            return Some(Expr::Alternation(vec![
+                Expr::string("\n"),
+                Expr::string("\r\n"),
+                Expr::string("\r"),
+            ]))
+        }
+        "ASCII_DIGIT" => vec!['0'..='9'],
+        "ASCII_NONZERO_DIGIT" => vec!['1'..='9'],
+        "ASCII_BIN_DIGIT" => vec!['0'..='1'],
+        "ASCII_OCT_DIGIT" => vec!['0'..='7'],
+        "ASCII_HEX_DIGIT" => vec!['0'..='9', 'a'..='f', 'A'..='F'],
+        "ASCII_ALPHA_LOWER" => vec!['a'..='z'],
+        "ASCII_ALPHA_UPPER" => vec!['A'..='Z'],
+        "ASCII_ALPHA" => vec!['a'..='z', 'A'..='Z'],
+        "ASCII_ALPHANUMERIC" => vec!['a'..='z', 'A'..='Z', '0'..='9'],
+        "ASCII" => vec!['\0'..='\x7F'],
+        _ => return None,
+    };
+    Some(Expr::Literal(ranges))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mutators::grammar::{grammar_based_ast_mutator, GrammarParseError, GrammarRules};
+    use crate::mutators::testing_utilities::test_mutator;
+    use crate::Mutator;
+
+    const JSON: &str = r#"
+        WHITESPACE = _{ " " | "\t" | NEWLINE }
+
+        json = { SOI ~ value ~ EOI }
+        object = { "{" ~ pair ~ ("," ~ pair)* ~ "}" | "{" ~ "}" }
+        pair = { string ~ ":" ~ value }
+        array = { "[" ~ value ~ ("," ~ value)* ~ "]" | "[" ~ "]" }
+        value = _{ object | array | string | number | boolean | null }
+        boolean = { "true" | "false" }
+        null = { ^"null" }
+        string = ${ "\"" ~ inner ~ "\"" }
+        inner = @{ char* }
+        char = { 'a'..'z' | "\\" ~ ("\"" | "\\" | "n") }
+        number = @{ "-"? ~ ("0" | ASCII_NONZERO_DIGIT ~ ASCII_DIGIT{0, 3}) }
+    "#;
+
+    #[test]
+    fn test_pest() {
+        let rules = GrammarRules::from_pest(JSON).unwrap();
+        assert_eq!(
+            rules.rule_names().collect::<Vec<_>>(),
+            [
+                "WHITESPACE",
+                "json",
+                "object",
+                "pair",
+                "array",
+                "value",
+                "boolean",
+                "null",
+                "string",
+                "inner",
+                "char",
+                "number"
+            ]
+        );
+        let m = grammar_based_ast_mutator(rules.grammar("string").unwrap());
+        for _ in 0..200 {
+            let s = m.random_arbitrary(50.0).0.to_string();
+            assert!(s.starts_with('"') && s.ends_with('"') && !s.contains(' '), "{:?}", s);
+        }
+        let m = grammar_based_ast_mutator(rules.grammar("number").unwrap());
+        for _ in 0..200 {
+            let s = m.random_arbitrary(50.0).0.to_string();
+            assert!(s.trim_start_matches('-').parse::<u16>().is_ok(), "{:?}", s);
+        }
+        let m = grammar_based_ast_mutator(rules.grammar("json").unwrap());
+        test_mutator(m, 200., 200., false, true, 50, 50);
+    }
+
+    #[test]
+    fn test_pest_errors() {
+        let error = |line, column, message: &str| GrammarParseError {
+            line,
+            column,
+            message: message.to_owned(),
+        };
+        assert_eq!(
+            GrammarRules::from_pest("a = { \"x\" }\nb = { !a ~ ANY }").unwrap_err(),
+            error(2, 1, "the rule `b` uses predicates, which are not supported")
+        );
+        assert_eq!(
+            GrammarRules::from_pest("a = { PUSH(\"x\") ~ POP }").unwrap_err(),
+            error(1, 1, "the rule `a` uses stack operations, which are not supported")
+        );
+        assert_eq!(
+            GrammarRules::from_pest("a = { LETTER }").unwrap_err(),
+            error(
+                1,
+                1,
+                "the rule `a` uses built-in rules such as `LETTER`, which are not supported"
+            )
+        );
+        let e = GrammarRules::from_pest("a = { \"x\" \n").unwrap_err();
+        assert_eq!((e.line, e.column), (2, 1));
+    }
+}
@@ -1,24 +1,87 @@
 use std::rc::Rc;
 
 use regex_syntax::hir::{Class, HirKind, Literal, RepetitionKind, RepetitionRange};
+use regex_syntax::utf8::Utf8Sequences;
 
-use crate::mutators::grammar::{alternation, concatenation, literal, literal_ranges, repetition, Grammar};
+use crate::mutators::grammar::{
+    alternation, concatenation, literal, literal_range, literal_ranges, repetition, Grammar, GrammarParseError,
+};
 
 #[no_coverage]
-pub(crate) fn grammar_from_regex(regex: &str) -> Rc<Grammar> {
-    let mut parser = regex_syntax::Parser::new();
-    let hir = parser.parse(regex).unwrap();
-    grammar_from_regex_hir_kind(hir.kind())
+pub(crate) fn grammar_from_regex(regex: &str, bytes: bool) -> Result<Rc<Grammar>, GrammarParseError> {
+    let mut parser = regex_syntax::ParserBuilder::new()
+        .unicode(!bytes)
+        .allow_invalid_utf8(bytes)
+        .build();
+    let hir = parser.parse(regex).map_err(regex_error)?;
+    Ok(grammar_from_regex_hir_kind(hir.kind(), bytes))
 }
+
+#[no_coverage]
+fn regex_error(error: regex_syntax::Error) -> GrammarParseError {
+    let (span, message) = match &error {
+        regex_syntax::Error::Parse(e) => (*e.span(), e.kind().to_string()),
+        regex_syntax::Error::Translate(e) => (*e.span(), e.kind().to_string()),
+        _ => {
+            return GrammarParseError {
+                line: 1,
+                column: 1,
+                message: error.to_string(),
+            }
+        }
+    };
+    GrammarParseError {
+        line: span.start.line,
+        column: span.start.column,
+        message,
+    }
+}
+
+/// Convert the regular expression to a grammar.
+///
+/// If `bytes` is true, each character of the grammar stands for the byte of the same value, and
+/// the unicode characters of the regular expression are matched by their UTF-8 encoding.
+///
+/// Anchors and word boundaries match the empty string, since the strings generated by the grammar
+/// are never surrounded by other text.
 #[no_coverage]
-pub fn grammar_from_regex_hir_kind(hir: &HirKind) -> Rc<Grammar> {
+pub fn grammar_from_regex_hir_kind(hir: &HirKind, bytes: bool) -> Rc<Grammar> {
     match hir {
-        HirKind::Empty => panic!("empty regexes are not supported"),
+        HirKind::Empty | HirKind::Anchor(_) | HirKind::WordBoundary(_) => {
+            // the empty string, since the mutator of an empty concatenation cannot be built
+            repetition(literal('\0'), 0..1)
+        }
         HirKind::Literal(l) => match l {
+            Literal::Unicode(l) if bytes => {
+                let mut buffer = [0; 4];
+                concatenation(l.encode_utf8(&mut buffer).bytes().map(
+                    #[no_coverage]
+                    |b| literal(b as char),
+                ))
+            }
             Literal::Unicode(l) => literal(*l),
-            Literal::Byte(_) => panic!("non-unicode regexes are not supported"),
+            // without the `bytes` option, the parser only accepts ASCII bytes
+            Literal::Byte(b) => literal(*b as char),
         },
         HirKind::Class(class) => match class {
+            Class::Unicode(class) if bytes => alternation(
+                class
+                    .ranges()
+                    .iter()
+                    .flat_map(
+                        #[no_coverage]
+                        |r| Utf8Sequences::new(r.start(), r.end()),
+                    )
+                    .map(
+                        #[no_coverage]
+                        |sequence| {
+                            concatenation(sequence.as_slice().iter().map(
+                                #[no_coverage]
+                                |r| literal_range(r.start as char..=r.end as char),
+                            ))
+                        },
+                    ),
+            ),
             Class::Unicode(class) => {
                 let ranges = class
                     .ranges()
@@ -30,10 +93,18 @@ pub fn grammar_from_regex_hir_kind(hir: &HirKind) -> Rc<Grammar> {
                     .collect::<Vec<_>>();
                 literal_ranges(ranges)
             }
-            Class::Bytes(_) => panic!("non-unicode regexes are not supported"),
+            Class::Bytes(class) => {
+                let ranges = class
+                    .ranges()
+                    .iter()
+                    .map(
+                        #[no_coverage]
+                        |r| r.start() as char..=r.end() as char,
+                    )
+                    .collect::<Vec<_>>();
+                literal_ranges(ranges)
+            }
         },
-        HirKind::Anchor(_) => panic!("anchors are not supported"),
-        HirKind::WordBoundary(_) => panic!("word boundaries are not supported"),
         HirKind::Repetition(rep) => {
             let range = match rep.kind.clone() {
                 RepetitionKind::ZeroOrOne => 0..=1u32,
@@ -46,17 +117,17 @@ pub fn grammar_from_regex_hir_kind(hir: &HirKind) -> Rc<Grammar> {
                 },
             };
             let range = (*range.start() as usize)..=(*range.end() as usize);
-            let grammar = grammar_from_regex_hir_kind(rep.hir.kind());
+            let grammar = grammar_from_regex_hir_kind(rep.hir.kind(), bytes);
             repetition(grammar, range)
         }
-        HirKind::Group(group) => grammar_from_regex_hir_kind(group.hir.kind()),
+        HirKind::Group(group) => grammar_from_regex_hir_kind(group.hir.kind(), bytes),
         HirKind::Concat(concat) => concatenation(concat.iter().map(
             #[no_coverage]
-            |hir| grammar_from_regex_hir_kind(hir.kind()),
+            |hir| grammar_from_regex_hir_kind(hir.kind(), bytes),
         )),
         HirKind::Alternation(alt) => alternation(alt.iter().map(
             #[no_coverage]
-            |hir| grammar_from_regex_hir_kind(hir.kind()),
+            |hir| grammar_from_regex_hir_kind(hir.kind(), bytes),
         )),
     }
 }
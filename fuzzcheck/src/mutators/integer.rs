@@ -72,11 +72,79 @@ binary_search_arbitrary!(binary_search_arbitrary_u64, u64);
 
 const INITIAL_MUTATION_STEP: u64 = 0;
 
+/// The number of arithmetic mutations, which add or subtract a number between 1 and 35 to the value
+pub(crate) const NBR_ARITHMETIC_MUTATIONS: u64 = 70;
+
+/*
+    The ordered mutations of integers are, in order:
+    1. arithmetic mutations: +1, -1, +2, -2, ..., +35, -35
+    2. interesting values: the values given by the user, then 0, 1, -1, MIN, MAX, MIN+1, MAX-1,
+       the powers of two, the powers of two minus one, and their negation for signed integers
+    3. the value with its bytes swapped (i.e. with the opposite endianness)
+    4. the arbitrary values, which span the whole search space
+    The values that were already produced by an earlier mutation are skipped, such that each value is tried once.
+*/
+macro_rules! impl_int_helpers {
+    ($name:ident, $interesting_values:ident, $arithmetic_mutation:ident) => {
+        /// The default interesting values of the integer type, without duplicates
+        #[no_coverage]
+        pub(crate) fn $interesting_values() -> Vec<$name> {
+            let mut candidates = vec![
+                0,
+                1,
+                (0 as $name).wrapping_sub(1),
+                <$name>::MIN,
+                <$name>::MAX,
+                <$name>::MIN.wrapping_add(1),
+                <$name>::MAX.wrapping_sub(1),
+            ];
+            for i in 1..<$name>::BITS {
+                let power = (1 as $name) << i;
+                candidates.push(power);
+                candidates.push(power.wrapping_sub(1));
+                #[allow(unused_comparisons)]
+                if <$name>::MIN < 0 {
+                    candidates.push(power.wrapping_neg());
+                }
+            }
+            let mut values = Vec::with_capacity(candidates.len());
+            for x in candidates {
+                if !values.contains(&x) {
+                    values.push(x);
+                }
+            }
+            values
+        }
+        /// Add or subtract a number between 1 and 35 to the value, depending on the step,
+        /// which must be smaller than `NBR_ARITHMETIC_MUTATIONS`
+        #[no_coverage]
+        pub(crate) fn $arithmetic_mutation(value: $name, step: u64) -> $name {
+            let delta = (step / 2 + 1) as $name;
+            if step % 2 == 0 {
+                value.wrapping_add(delta)
+            } else {
+                value.wrapping_sub(delta)
+            }
+        }
+    };
+}
+impl_int_helpers!(u8, interesting_values_u8, arithmetic_mutation_u8);
+impl_int_helpers!(u16, interesting_values_u16, arithmetic_mutation_u16);
+impl_int_helpers!(u32, interesting_values_u32, arithmetic_mutation_u32);
+impl_int_helpers!(u64, interesting_values_u64, arithmetic_mutation_u64);
+impl_int_helpers!(usize, interesting_values_usize, arithmetic_mutation_usize);
+impl_int_helpers!(i8, interesting_values_i8, arithmetic_mutation_i8);
+impl_int_helpers!(i16, interesting_values_i16, arithmetic_mutation_i16);
+impl_int_helpers!(i32, interesting_values_i32, arithmetic_mutation_i32);
+impl_int_helpers!(i64, interesting_values_i64, arithmetic_mutation_i64);
+impl_int_helpers!(isize, interesting_values_isize, arithmetic_mutation_isize);
+
 macro_rules! impl_int_mutator {
-    ($name:ident, $name_unsigned: ident, $name_mutator:ident) => {
+    ($name:ident, $name_unsigned: ident, $name_mutator:ident, $interesting_values:ident, $arithmetic_mutation:ident) => {
         #[derive(Clone)]
         pub struct $name_mutator {
             shuffled_integers: [u8; 256],
+            interesting_values: Vec<$name>,
             rng: fastrand::Rng,
         }
         impl Default for $name_mutator {
@@ -90,12 +158,76 @@ macro_rules! impl_int_mutator {
                 rng.shuffle(&mut shuffled_integers);
                 $name_mutator {
                     shuffled_integers,
+                    interesting_values: $interesting_values(),
                     rng,
                 }
             }
         }
 
         impl $name_mutator {
+            /// Add values that the mutator should try before any other value, after the small
+            /// arithmetic mutations of the current value.
+            ///
+            /// ```
+            /// use fuzzcheck::mutators::integer::U16Mutator;
+            ///
+            /// let m = U16Mutator::default().interesting_values([0x7fff, 1000, 443]);
+            /// ```
+            #[no_coverage]
+            pub fn interesting_values(mut self, values: impl IntoIterator<Item = $name>) -> Self {
+                let mut interesting_values = vec![];
+                for x in values.into_iter().chain(self.interesting_values) {
+                    if !interesting_values.contains(&x) {
+                        interesting_values.push(x);
+                    }
+                }
+                self.interesting_values = interesting_values;
+                self
+            }
+
+            /// The value at the given step of the ordered mutations of `value`, or `None` if
+            /// all mutations were tried
+            #[no_coverage]
+            fn nth_mutation(&self, value: $name, step: u64) -> Option<$name> {
+                let nbr_interesting_values = self.interesting_values.len() as u64;
+                if step < NBR_ARITHMETIC_MUTATIONS {
+                    Some($arithmetic_mutation(value, step))
+                } else if step < NBR_ARITHMETIC_MUTATIONS + nbr_interesting_values {
+                    Some(self.interesting_values[(step - NBR_ARITHMETIC_MUTATIONS) as usize])
+                } else if step == NBR_ARITHMETIC_MUTATIONS + nbr_interesting_values {
+                    Some(value.swap_bytes())
+                } else {
+                    let step = step - NBR_ARITHMETIC_MUTATIONS - nbr_interesting_values - 1;
+                    if step > <$name_unsigned>::MAX as u64 {
+                        None
+                    } else {
+                        Some(self.uniform_permutation(step) as $name)
+                    }
+                }
+            }
+
+            /// Whether the value produced at the given step of the ordered mutations of `value` was
+            /// already produced by an earlier step, e.g. an interesting value close to `value`
+            #[no_coverage]
+            fn is_repeated_mutation(&self, value: $name, new_value: $name, step: u64) -> bool {
+                let swap_bytes_step = NBR_ARITHMETIC_MUTATIONS + self.interesting_values.len() as u64;
+                if step < NBR_ARITHMETIC_MUTATIONS {
+                    return false;
+                }
+                let max_delta = NBR_ARITHMETIC_MUTATIONS / 2;
+                let delta = new_value.wrapping_sub(value) as $name_unsigned;
+                if delta as u64 <= max_delta || delta.wrapping_neg() as u64 <= max_delta {
+                    return true;
+                }
+                if step < swap_bytes_step {
+                    // the interesting values do not contain duplicates
+                    false
+                } else {
+                    self.interesting_values.contains(&new_value)
+                        || (step > swap_bytes_step && new_value == value.swap_bytes())
+                }
+            }
+
             #[no_coverage]
             fn uniform_permutation(&self, step: u64) -> $name_unsigned {
                 let size = <$name>::BITS as u64;
@@ -236,27 +368,14 @@ macro_rules! impl_int_mutator {
                 if max_cplx < self.min_complexity() {
                     return None;
                 }
-                if *step > 10u64.saturating_add(<$name>::MAX as u64) {
-                    return None;
-                }
-                let token = *value;
-                *value = {
-                    let mut tmp_step = *step;
-                    if tmp_step < 8 {
-                        let nudge = (tmp_step + 2) as $name;
-                        if nudge % 2 == 0 {
-                            value.wrapping_add(nudge / 2)
-                        } else {
-                            value.wrapping_sub(nudge / 2)
-                        }
-                    } else {
-                        tmp_step -= 7;
-                        self.uniform_permutation(tmp_step) as $name
+                loop {
+                    let new_value = self.nth_mutation(*value, *step)?;
+                    let is_repeated = self.is_repeated_mutation(*value, new_value, *step);
+                    *step = step.wrapping_add(1);
+                    if new_value != *value && !is_repeated {
+                        return Some((std::mem::replace(value, new_value), <$name>::BITS as f64));
                     }
-                };
-                *step = step.wrapping_add(1);
-
-                Some((token, <$name>::BITS as f64))
+                }
             }
             #[doc(hidden)]
             #[no_coverage]
@@ -266,7 +385,12 @@ macro_rules! impl_int_mutator {
                 _cache: &mut Self::Cache,
                 _max_cplx: f64,
             ) -> (Self::UnmutateToken, f64) {
-                (std::mem::replace(value, self.rng.$name(..)), <$name>::BITS as f64)
+                let new_value = match self.rng.u8(..4) {
+                    0 => $arithmetic_mutation(*value, self.rng.u64(..NBR_ARITHMETIC_MUTATIONS)),
+                    1 => self.interesting_values[self.rng.usize(..self.interesting_values.len())],
+                    _ => self.rng.$name(..),
+                };
+                (std::mem::replace(value, new_value), <$name>::BITS as f64)
             }
             #[doc(hidden)]
             #[no_coverage]
@@ -295,13 +419,77 @@ macro_rules! impl_int_mutator {
     };
 }
 
-impl_int_mutator!(u8, u8, U8Mutator);
-impl_int_mutator!(u16, u16, U16Mutator);
-impl_int_mutator!(u32, u32, U32Mutator);
-impl_int_mutator!(u64, u64, U64Mutator);
-impl_int_mutator!(usize, usize, USizeMutator);
-impl_int_mutator!(i8, u8, I8Mutator);
-impl_int_mutator!(i16, u16, I16Mutator);
-impl_int_mutator!(i32, u32, I32Mutator);
-impl_int_mutator!(i64, u64, I64Mutator);
-impl_int_mutator!(isize, isize, ISizeMutator);
+impl_int_mutator!(u8, u8, U8Mutator, interesting_values_u8, arithmetic_mutation_u8);
+impl_int_mutator!(u16, u16, U16Mutator, interesting_values_u16, arithmetic_mutation_u16);
+impl_int_mutator!(u32, u32, U32Mutator, interesting_values_u32, arithmetic_mutation_u32);
+impl_int_mutator!(u64, u64, U64Mutator, interesting_values_u64, arithmetic_mutation_u64);
+impl_int_mutator!(
+    usize,
+    usize,
+    USizeMutator,
+    interesting_values_usize,
+    arithmetic_mutation_usize
+);
+impl_int_mutator!(i8, u8, I8Mutator, interesting_values_i8, arithmetic_mutation_i8);
+impl_int_mutator!(i16, u16, I16Mutator, interesting_values_i16, arithmetic_mutation_i16);
+impl_int_mutator!(i32, u32, I32Mutator, interesting_values_i32, arithmetic_mutation_i32);
+impl_int_mutator!(i64, u64, I64Mutator, interesting_values_i64, arithmetic_mutation_i64);
+impl_int_mutator!(
+    isize,
+    isize,
+    ISizeMutator,
+    interesting_values_isize,
+    arithmetic_mutation_isize
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{I16Mutator, U8Mutator};
+    use crate::subvalue_provider::EmptySubValueProvider;
+    use crate::Mutator;
+
+    #[test]
+    fn test_int_ordered_mutations() {
+        let m = I16Mutator::default().interesting_values([1234]);
+        let mut value = 100;
+        let mut step = m.default_mutation_step(&value, &());
+        let mut mutations = vec![];
+        while let Some((token, _)) = m.ordered_mutate(&mut value, &mut (), &mut step, &EmptySubValueProvider, 16.0) {
+            mutations.push(value);
+            m.unmutate(&mut value, &mut (), token);
+        }
+        assert_eq!(&mutations[..6], &[101, 99, 102, 98, 103, 97]);
+        assert_eq!(&mutations[68..72], &[135, 65, 1234, 0]);
+        for x in [i16::MIN, i16::MAX, 1024, 1023, -1024, 100i16.swap_bytes()] {
+            assert!(mutations[..200].contains(&x));
+        }
+        // the interesting values close to 100 were already produced by the arithmetic mutations
+        for x in [127, 128] {
+            assert_eq!(mutations.iter().filter(|&&y| y == x).count(), 1);
+        }
+        assert_eq!(mutations.len(), u16::MAX as usize);
+        let mut distinct_mutations = mutations.clone();
+        distinct_mutations.sort_unstable();
+        distinct_mutations.dedup();
+        assert_eq!(distinct_mutations.len(), mutations.len());
+    }
+
+    #[test]
+    fn test_int_ordered_mutations_exhaustive() {
+        let m = U8Mutator::default();
+        let mut value = 7;
+        let mut step = m.default_mutation_step(&value, &());
+        let mut mutations = vec![];
+        while let Some((token, _)) = m.ordered_mutate(&mut value, &mut (), &mut step, &EmptySubValueProvider, 8.0) {
+            mutations.push(value);
+            m.unmutate(&mut value, &mut (), token);
+        }
+        // every other value is produced exactly once
+        assert_eq!(mutations.len(), 255);
+        let mut distinct_mutations = mutations.clone();
+        distinct_mutations.sort_unstable();
+        distinct_mutations.dedup();
+        assert_eq!(distinct_mutations.len(), mutations.len());
+        assert!(!mutations.contains(&7));
+    }
+}
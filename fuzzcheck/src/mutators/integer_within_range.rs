@@ -2,16 +2,29 @@ use std::any::Any;
 use std::ops::{Bound, RangeBounds};
 
 use crate::mutators::integer::{
+    arithmetic_mutation_i16, arithmetic_mutation_i32, arithmetic_mutation_i64, arithmetic_mutation_i8,
+    arithmetic_mutation_u16, arithmetic_mutation_u32, arithmetic_mutation_u64, arithmetic_mutation_u8,
     binary_search_arbitrary_u16, binary_search_arbitrary_u32, binary_search_arbitrary_u64, binary_search_arbitrary_u8,
+    interesting_values_i16, interesting_values_i32, interesting_values_i64, interesting_values_i8,
+    interesting_values_u16, interesting_values_u32, interesting_values_u64, interesting_values_u8,
+    NBR_ARITHMETIC_MUTATIONS,
 };
+use crate::mutators::DefaultMutatorWithinRange;
 use crate::Mutator;
 const INITIAL_MUTATION_STEP: u64 = 0;
 
+/*
+    The ordered mutations are the same as those of the default integer mutators (see integer.rs), except
+    that the values outside of the range are skipped, and that the bounds of the range and their neighbours
+    are the first interesting values.
+*/
+
 macro_rules! impl_int_mutator_constrained {
-    ($name:ident,$name_unsigned:ident, $name_mutator:ident, $name_binary_arbitrary_function: ident) => {
+    ($name:ident,$name_unsigned:ident, $name_mutator:ident, $name_binary_arbitrary_function: ident, $interesting_values:ident, $arithmetic_mutation:ident) => {
         pub struct $name_mutator {
             start_range: $name,
             len_range: $name_unsigned,
+            interesting_values: Vec<$name>,
             search_space_complexity: f64,
             rng: fastrand::Rng,
         }
@@ -43,13 +56,88 @@ macro_rules! impl_int_mutator_constrained {
                     )
                 }
                 let length = end.wrapping_sub(start);
+                let bounds = [start, end, start.saturating_add(1), end.saturating_sub(1)];
+                let mut interesting_values: Vec<$name> = vec![];
+                for x in bounds.into_iter().chain($interesting_values()) {
+                    if (start..=end).contains(&x) && !interesting_values.contains(&x) {
+                        interesting_values.push(x);
+                    }
+                }
                 Self {
                     start_range: start,
                     len_range: end.wrapping_sub(start) as $name_unsigned,
+                    interesting_values,
                     search_space_complexity: super::size_to_cplxity(length as usize),
                     rng: fastrand::Rng::default(),
                 }
             }
+
+            /// Add values that the mutator should try before any other value, after the small
+            /// arithmetic mutations of the current value. The values outside of the range are ignored.
+            #[no_coverage]
+            pub fn interesting_values(mut self, values: impl IntoIterator<Item = $name>) -> Self {
+                let mut interesting_values = vec![];
+                for x in values
+                    .into_iter()
+                    .chain(std::mem::take(&mut self.interesting_values))
+                {
+                    if self.is_valid(&x) && !interesting_values.contains(&x) {
+                        interesting_values.push(x);
+                    }
+                }
+                self.interesting_values = interesting_values;
+                self
+            }
+
+            /// The value at the given step of the ordered mutations of `value`, or `None` if
+            /// all mutations were tried. The value may be outside of the range.
+            #[no_coverage]
+            fn nth_mutation(&self, value: $name, step: u64) -> Option<$name> {
+                let nbr_interesting_values = self.interesting_values.len() as u64;
+                if step < NBR_ARITHMETIC_MUTATIONS {
+                    Some($arithmetic_mutation(value, step))
+                } else if step < NBR_ARITHMETIC_MUTATIONS + nbr_interesting_values {
+                    Some(self.interesting_values[(step - NBR_ARITHMETIC_MUTATIONS) as usize])
+                } else if step == NBR_ARITHMETIC_MUTATIONS + nbr_interesting_values {
+                    Some(value.swap_bytes())
+                } else {
+                    let step = step - NBR_ARITHMETIC_MUTATIONS - nbr_interesting_values - 1;
+                    if step > self.len_range as u64 {
+                        None
+                    } else {
+                        let result = $name_binary_arbitrary_function(0, self.len_range, step);
+                        Some(self.start_range.wrapping_add(result as $name))
+                    }
+                }
+            }
+
+            /// Whether the value produced at the given step of the ordered mutations of `value` was
+            /// already produced by an earlier step, e.g. an interesting value close to `value`
+            #[no_coverage]
+            fn is_repeated_mutation(&self, value: $name, new_value: $name, step: u64) -> bool {
+                let swap_bytes_step = NBR_ARITHMETIC_MUTATIONS + self.interesting_values.len() as u64;
+                if step < NBR_ARITHMETIC_MUTATIONS {
+                    return false;
+                }
+                let max_delta = NBR_ARITHMETIC_MUTATIONS / 2;
+                let delta = new_value.wrapping_sub(value) as $name_unsigned;
+                if delta as u64 <= max_delta || delta.wrapping_neg() as u64 <= max_delta {
+                    return true;
+                }
+                if step < swap_bytes_step {
+                    // the interesting values do not contain duplicates
+                    false
+                } else {
+                    self.interesting_values.contains(&new_value)
+                        || (step > swap_bytes_step && new_value == value.swap_bytes())
+                }
+            }
+
+            #[no_coverage]
+            fn random_value(&self) -> $name {
+                self.rng
+                    .$name(self.start_range..=self.start_range.wrapping_add(self.len_range as $name))
+            }
         }
 
         impl Mutator<$name> for $name_mutator {
@@ -133,10 +221,7 @@ macro_rules! impl_int_mutator_constrained {
             #[doc(hidden)]
             #[no_coverage]
             fn random_arbitrary(&self, _max_cplx: f64) -> ($name, f64) {
-                let value = self
-                    .rng
-                    .$name(self.start_range..=self.start_range.wrapping_add(self.len_range as $name));
-                (value, <$name>::BITS as f64)
+                (self.random_value(), <$name>::BITS as f64)
             }
 
             #[doc(hidden)]
@@ -152,16 +237,14 @@ macro_rules! impl_int_mutator_constrained {
                 if max_cplx < self.min_complexity() {
                     return None;
                 }
-                if *step > self.len_range as u64 {
-                    return None;
+                loop {
+                    let new_value = self.nth_mutation(*value, *step)?;
+                    let is_repeated = self.is_repeated_mutation(*value, new_value, *step);
+                    *step = step.wrapping_add(1);
+                    if new_value != *value && self.is_valid(&new_value) && !is_repeated {
+                        return Some((std::mem::replace(value, new_value), <$name>::BITS as f64));
+                    }
                 }
-                let token = *value;
-
-                let result = $name_binary_arbitrary_function(0, self.len_range, *step);
-                *value = self.start_range.wrapping_add(result as $name);
-                *step = step.wrapping_add(1);
-
-                Some((token, <$name>::BITS as f64))
             }
 
             #[doc(hidden)]
@@ -172,14 +255,17 @@ macro_rules! impl_int_mutator_constrained {
                 _cache: &mut Self::Cache,
                 _max_cplx: f64,
             ) -> (Self::UnmutateToken, f64) {
-                (
-                    std::mem::replace(
-                        value,
-                        self.rng
-                            .$name(self.start_range..=self.start_range.wrapping_add(self.len_range as $name)),
-                    ),
-                    <$name>::BITS as f64,
-                )
+                let new_value = match self.rng.u8(..4) {
+                    0 => $arithmetic_mutation(*value, self.rng.u64(..NBR_ARITHMETIC_MUTATIONS)),
+                    1 => self.interesting_values[self.rng.usize(..self.interesting_values.len())],
+                    _ => self.random_value(),
+                };
+                let new_value = if self.is_valid(&new_value) {
+                    new_value
+                } else {
+                    self.random_value()
+                };
+                (std::mem::replace(value, new_value), <$name>::BITS as f64)
             }
 
             #[doc(hidden)]
@@ -198,21 +284,85 @@ macro_rules! impl_int_mutator_constrained {
             ) {
             }
         }
+        impl DefaultMutatorWithinRange for $name {
+            type Mutator = $name_mutator;
+            #[no_coverage]
+            fn default_mutator_within_range<RB: RangeBounds<$name>>(range: RB) -> Self::Mutator {
+                $name_mutator::new(range)
+            }
+        }
     };
 }
 
-impl_int_mutator_constrained!(u8, u8, U8WithinRangeMutator, binary_search_arbitrary_u8);
-impl_int_mutator_constrained!(u16, u16, U16WithinRangeMutator, binary_search_arbitrary_u16);
-impl_int_mutator_constrained!(u32, u32, U32WithinRangeMutator, binary_search_arbitrary_u32);
-impl_int_mutator_constrained!(u64, u64, U64WithinRangeMutator, binary_search_arbitrary_u64);
-impl_int_mutator_constrained!(i8, u8, I8WithinRangeMutator, binary_search_arbitrary_u8);
-impl_int_mutator_constrained!(i16, u16, I16WithinRangeMutator, binary_search_arbitrary_u16);
-impl_int_mutator_constrained!(i32, u32, I32WithinRangeMutator, binary_search_arbitrary_u32);
-impl_int_mutator_constrained!(i64, u64, I64WithinRangeMutator, binary_search_arbitrary_u64);
+impl_int_mutator_constrained!(
+    u8,
+    u8,
+    U8WithinRangeMutator,
+    binary_search_arbitrary_u8,
+    interesting_values_u8,
+    arithmetic_mutation_u8
+);
+impl_int_mutator_constrained!(
+    u16,
+    u16,
+    U16WithinRangeMutator,
+    binary_search_arbitrary_u16,
+    interesting_values_u16,
+    arithmetic_mutation_u16
+);
+impl_int_mutator_constrained!(
+    u32,
+    u32,
+    U32WithinRangeMutator,
+    binary_search_arbitrary_u32,
+    interesting_values_u32,
+    arithmetic_mutation_u32
+);
+impl_int_mutator_constrained!(
+    u64,
+    u64,
+    U64WithinRangeMutator,
+    binary_search_arbitrary_u64,
+    interesting_values_u64,
+    arithmetic_mutation_u64
+);
+impl_int_mutator_constrained!(
+    i8,
+    u8,
+    I8WithinRangeMutator,
+    binary_search_arbitrary_u8,
+    interesting_values_i8,
+    arithmetic_mutation_i8
+);
+impl_int_mutator_constrained!(
+    i16,
+    u16,
+    I16WithinRangeMutator,
+    binary_search_arbitrary_u16,
+    interesting_values_i16,
+    arithmetic_mutation_i16
+);
+impl_int_mutator_constrained!(
+    i32,
+    u32,
+    I32WithinRangeMutator,
+    binary_search_arbitrary_u32,
+    interesting_values_i32,
+    arithmetic_mutation_i32
+);
+impl_int_mutator_constrained!(
+    i64,
+    u64,
+    I64WithinRangeMutator,
+    binary_search_arbitrary_u64,
+    interesting_values_i64,
+    arithmetic_mutation_i64
+);
 
 #[cfg(test)]
 mod tests {
-    use super::U8WithinRangeMutator;
+    use super::{I32WithinRangeMutator, U8WithinRangeMutator};
+    use crate::subvalue_provider::EmptySubValueProvider;
     use crate::Mutator;
 
     #[test]
@@ -221,4 +371,32 @@ mod tests {
         assert!(m.is_valid(&1));
         assert!(!m.is_valid(&2));
     }
+
+    #[test]
+    fn test_int_constrained_ordered_mutations() {
+        let m = I32WithinRangeMutator::new(-10..=1000).interesting_values([5000, 404]);
+        let mut value = 990;
+        let mut step = m.default_mutation_step(&value, &());
+        let mut mutations = vec![];
+        while let Some((token, _)) = m.ordered_mutate(&mut value, &mut (), &mut step, &EmptySubValueProvider, 32.0) {
+            assert!((-10..=1000).contains(&value));
+            mutations.push(value);
+            m.unmutate(&mut value, &mut (), token);
+        }
+        assert_eq!(&mutations[..4], &[991, 989, 992, 988]);
+        // 990 - 35 is the last arithmetic mutation, followed by the interesting values within the range,
+        // except 1000 and 999, which are arithmetic mutations of 990
+        let idx = mutations.iter().position(|&x| x == 955).unwrap();
+        assert_eq!(&mutations[idx + 1..idx + 9], &[404, -10, -9, 0, 1, -1, 2, -2]);
+        assert!(!mutations.contains(&5000));
+        for x in [512, 511, -8] {
+            assert!(mutations.contains(&x));
+        }
+        // every other value of the range is produced exactly once
+        assert_eq!(mutations.len(), 1010);
+        let mut distinct_mutations = mutations.clone();
+        distinct_mutations.sort_unstable();
+        distinct_mutations.dedup();
+        assert_eq!(distinct_mutations.len(), mutations.len());
+    }
 }
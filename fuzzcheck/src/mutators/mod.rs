@@ -7,7 +7,10 @@ This module provides the following mutators:
     * `bool` ([here](crate::mutators::bool::BoolMutator))
     * `char` ([here](crate::mutators::char::CharWithinRangeMutator) and [here](crate::mutators::character_classes::CharacterMutator))
     * integers ([here](crate::mutators::integer) and [here](crate::mutators::integer_within_range))
+    * floats ([here](crate::mutators::float) and [here](crate::mutators::float_within_range))
     * `Vec` ([here](crate::mutators::vector::VecMutator) and [here](crate::mutators::fixed_len_vector::FixedLenVecMutator))
+    * `VecDeque` ([here](crate::mutators::vec_deque::VecDequeMutator))
+    * `HashMap`, `BTreeMap`, `HashSet`, and `BTreeSet` ([here](crate::mutators::collections))
     * `Option` ([here](crate::mutators::option::OptionMutator))
     * `Result` ([here](crate::mutators::result::ResultMutator))
     * `Box` ([here](crate::mutators::boxed))
@@ -31,33 +34,43 @@ This module provides the following mutators:
     * [`Either<M1, M2>`](crate::mutators::either::Either) is the regular `Either` type, which also implements `Mutator<T>` if both `M1` and `M2` implement it too
     * [`RecursiveMutator` and `RecurToMutator`](crate::mutators::recursive) are wrappers allowing mutators to call themselves recursively, which is necessary to mutate recursive types.
     * [`MapMutator<..>`](crate::mutators::map::MapMutator) wraps a mutator and transforms the generated value using a user-provided function.
+    * [`RepairMutator<..>`](crate::mutators::repair::RepairMutator) wraps a mutator and repairs the generated values so that they satisfy an invariant.
+
+- a [conformance test](crate::mutators::conformance) to check that custom mutators follow the contract of the [`Mutator`] trait
+- a [benchmark](crate::mutators::benchmark) to measure the throughput of a mutator and the diversity of the values it produces
 */
 
 pub const CROSSOVER_RATE: u8 = 10;
 
 use std::any::{Any, TypeId};
 use std::marker::PhantomData;
-use std::ops::Range;
+use std::ops::{Bound, Range, RangeBounds, RangeInclusive};
 
 use ahash::AHashMap;
 
 use self::filter::FilterMutator;
 use self::map::MapMutator;
+use self::repair::RepairMutator;
 use crate::subvalue_provider::Generation;
 use crate::{Mutator, SubValueProvider};
 
 pub mod alternation;
 pub mod arc;
 pub mod array;
+pub mod benchmark;
 pub mod bool;
 pub mod boxed;
 pub mod char;
 pub mod character_classes;
+pub mod collections;
+pub mod conformance;
 pub mod cow;
 pub mod either;
 pub mod enums;
 pub mod filter;
 pub mod fixed_len_vector;
+pub mod float;
+pub mod float_within_range;
 #[cfg(feature = "grammar_mutator")]
 #[doc(cfg(feature = "grammar_mutator"))]
 pub mod grammar;
@@ -70,11 +83,13 @@ pub mod option;
 pub mod range;
 pub mod rc;
 pub mod recursive;
+pub mod repair;
 pub mod result;
 pub mod string;
 pub mod tuples;
 pub mod unique;
 pub mod unit;
+pub mod vec_deque;
 pub mod vector;
 pub mod vose_alias;
 
@@ -84,6 +99,41 @@ pub trait DefaultMutator: Clone + 'static {
     fn default_mutator() -> Self::Mutator;
 }
 
+/// A trait for giving a type a default [Mutator] whose values are within a range
+///
+/// It is used by the `#[fuzz(range = ..)]` attribute of the [procedural macros](fuzzcheck_mutators_derive::DefaultMutator),
+/// and implemented by the integer, float, and `char` types.
+pub trait DefaultMutatorWithinRange: Clone + 'static {
+    type Mutator: Mutator<Self>;
+    fn default_mutator_within_range<RB: RangeBounds<Self>>(range: RB) -> Self::Mutator;
+}
+
+/// A trait for giving a collection a default [Mutator] whose values have a length within a range
+///
+/// It is used by the `#[fuzz(len = ..)]` attribute of the [procedural macros](fuzzcheck_mutators_derive::DefaultMutator),
+/// and implemented by `Vec`, `VecDeque`, and `String`, whose length is counted in characters.
+pub trait DefaultMutatorWithLength: Clone + 'static {
+    type Mutator: Mutator<Self>;
+    fn default_mutator_with_length<RB: RangeBounds<usize>>(len_range: RB) -> Self::Mutator;
+}
+
+/// Convert a range of lengths to the inclusive range expected by the collection mutators
+#[no_coverage]
+pub(crate) fn inclusive_len_range<RB: RangeBounds<usize>>(len_range: RB) -> RangeInclusive<usize> {
+    let start = match len_range.start_bound() {
+        Bound::Included(b) => *b,
+        Bound::Excluded(b) => *b + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match len_range.end_bound() {
+        Bound::Included(b) => *b,
+        Bound::Excluded(b) => b.checked_sub(1).expect("the range of lengths must contain at least one length"),
+        Bound::Unbounded => usize::MAX,
+    };
+    assert!(start <= end, "the range of lengths must contain at least one length");
+    start..=end
+}
+
 #[derive(Clone)]
 pub struct CrossoverStep<T> {
     steps: AHashMap<usize, (Generation, usize)>,
@@ -144,6 +194,18 @@ where
     {
         FilterMutator { mutator: self, filter }
     }
+    /// Create a mutator which wraps `self` and modifies the values it produces using
+    /// the `repair` closure, such that they satisfy an invariant.
+    ///
+    /// The values must implement `PartialEq`, which is used to detect the values that
+    /// the `repair` closure changed.
+    #[no_coverage]
+    fn repair<F>(self, repair: F) -> RepairMutator<Self, F>
+    where
+        F: Fn(&mut T),
+    {
+        RepairMutator { mutator: self, repair }
+    }
     /// Create a mutator which wraps `self` and transforms the values generated by `self`
     /// using the `map` closure. The second closure, `parse`, should apply the opposite
     /// transformation.
@@ -450,45 +512,4 @@ pub mod testing_utilities {
         //     }
         // }
     }
-    // #[no_coverage]
-    // pub fn bench_mutator<T, M>(
-    //     m: M,
-    //     maximum_complexity_arbitrary: f64,
-    //     maximum_complexity_mutate: f64,
-    //     nbr_arbitraries: usize,
-    //     nbr_mutations: usize,
-    // ) where
-    //     M: Mutator<T>,
-    //     T: Clone + Debug + PartialEq + Eq + Hash + 'static,
-    //     M::Cache: Clone,
-    // {
-    //     let mut arbitrary_step = m.default_arbitrary_step();
-    //     for _i in 0..nbr_arbitraries {
-    //         if let Some((mut x, cplx)) = m.ordered_arbitrary(&mut arbitrary_step, maximum_complexity_arbitrary) {
-    //             let mut cache = m.validate_value(&x).unwrap();
-    //             let mut mutation_step = m.default_mutation_step(&x, &cache);
-    //             let other_cplx = m.complexity(&x, &cache);
-    //             for _j in 0..nbr_mutations {
-    //                 if let Some((token, _cplx)) =
-    //                     m.ordered_mutate(&mut x, &mut cache, &mut mutation_step, maximum_complexity_mutate)
-    //                 {
-    //                     m.unmutate(&mut x, &mut cache, token);
-    //                 } else {
-    //                     break;
-    //                 }
-    //             }
-    //         } else {
-    //             break;
-    //         }
-    //     }
-    //     for _i in 0..nbr_arbitraries {
-    //         let (mut x, cplx) = m.random_arbitrary(maximum_complexity_arbitrary);
-    //         let mut cache = m.validate_value(&x).unwrap();
-    //         let other_cplx = m.complexity(&x, &cache);
-    //         for _j in 0..nbr_mutations {
-    //             let (token, _cplx) = m.random_mutate(&mut x, &mut cache, maximum_complexity_mutate);
-    //             m.unmutate(&mut x, &mut cache, token);
-    //         }
-    //     }
-    // }
 }
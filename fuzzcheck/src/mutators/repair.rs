@@ -0,0 +1,259 @@
+use std::any::Any;
+
+use crate::Mutator;
+
+/// A mutator which wraps another mutator and repairs the values it produces with a function.
+///
+/// Unlike [`FilterMutator`](crate::mutators::filter::FilterMutator), which discards the values that do not
+/// satisfy an invariant, the `RepairMutator` modifies them so that they do. It is created by
+/// [`MutatorExt::repair`](crate::MutatorExt::repair) or by the `#[fuzz(invariant = repair_fn)]` attribute of
+/// the [procedural macros](fuzzcheck_mutators_derive::DefaultMutator).
+///
+/// The repaired value must still be a value that the wrapped mutator can produce. Otherwise, it is discarded.
+/// The repair function should also leave a value unchanged if it already satisfies the invariant. This is
+/// how [`validate_value`](Mutator::validate_value) recognizes the values that satisfy the invariant, such as
+/// the ones read from the corpus.
+///
+/// Since a mutation cannot predict what the repair function will change, each value is cloned before being
+/// repaired, which costs a time proportional to its size. When the repair function changes the value, its
+/// cache and complexity are then computed from scratch, which is as costly as [`validate_value`](Mutator::validate_value).
+/// Therefore, the repair function should only change the values that violate the invariant.
+///
+/// ```
+/// use fuzzcheck::{DefaultMutator, Mutator, MutatorExt};
+///
+/// let mutator = <(u8, u8)>::default_mutator().repair(|(start, end): &mut (u8, u8)| {
+///     if start > end {
+///         std::mem::swap(start, end);
+///     }
+/// });
+/// let ((start, end), _) = mutator.random_arbitrary(10.0);
+/// assert!(start <= end);
+/// ```
+pub struct RepairMutator<M, F> {
+    pub mutator: M,
+    pub repair: F,
+}
+
+#[doc(hidden)]
+pub struct UnmutateToken<T, C, U> {
+    inner: U,
+    /// The value and cache before they were repaired, or `None` if the repair function did not change the value
+    unrepaired: Option<(T, C)>,
+}
+
+impl<M, F> RepairMutator<M, F> {
+    /// Repair the value that was just mutated by the wrapped mutator and recompute its cache and complexity.
+    ///
+    /// If the repaired value is not valid, the mutation is reverted and `None` is returned.
+    #[no_coverage]
+    fn repair_mutated_value<T>(
+        &self,
+        value: &mut T,
+        cache: &mut M::Cache,
+        token: M::UnmutateToken,
+        cplx: f64,
+    ) -> Option<(UnmutateToken<T, M::Cache, M::UnmutateToken>, f64)>
+    where
+        T: Clone + PartialEq + 'static,
+        M: Mutator<T>,
+        F: Fn(&mut T),
+    {
+        let unrepaired_value = value.clone();
+        (self.repair)(value);
+        if *value == unrepaired_value {
+            // the cache and complexity computed by the wrapped mutator are still correct
+            return Some((
+                UnmutateToken {
+                    inner: token,
+                    unrepaired: None,
+                },
+                cplx,
+            ));
+        }
+        if let Some(repaired_cache) = self.mutator.validate_value(value) {
+            let cplx = self.mutator.complexity(value, &repaired_cache);
+            let unrepaired_cache = std::mem::replace(cache, repaired_cache);
+            Some((
+                UnmutateToken {
+                    inner: token,
+                    unrepaired: Some((unrepaired_value, unrepaired_cache)),
+                },
+                cplx,
+            ))
+        } else {
+            *value = unrepaired_value;
+            self.mutator.unmutate(value, cache, token);
+            None
+        }
+    }
+
+    /// Repair a newly generated value and compute its complexity, or return `None` if the repaired value is not valid
+    #[no_coverage]
+    fn repair_arbitrary_value<T>(&self, mut value: T, cplx: f64) -> Option<(T, f64)>
+    where
+        T: Clone + PartialEq + 'static,
+        M: Mutator<T>,
+        F: Fn(&mut T),
+    {
+        let unrepaired_value = value.clone();
+        (self.repair)(&mut value);
+        if value == unrepaired_value {
+            return Some((value, cplx));
+        }
+        let cache = self.mutator.validate_value(&value)?;
+        let cplx = self.mutator.complexity(&value, &cache);
+        Some((value, cplx))
+    }
+
+    /// Returns `true` if the repair function leaves the value unchanged, i.e. if it satisfies the invariant
+    #[no_coverage]
+    fn satisfies_invariant<T>(&self, value: &T) -> bool
+    where
+        T: Clone + PartialEq,
+        F: Fn(&mut T),
+    {
+        let mut repaired_value = value.clone();
+        (self.repair)(&mut repaired_value);
+        repaired_value == *value
+    }
+}
+
+impl<T, M, F> Mutator<T> for RepairMutator<M, F>
+where
+    M: Mutator<T>,
+    T: Clone + PartialEq + 'static,
+    F: Fn(&mut T),
+    Self: 'static,
+{
+    #[doc(hidden)]
+    type Cache = <M as Mutator<T>>::Cache;
+    #[doc(hidden)]
+    type MutationStep = <M as Mutator<T>>::MutationStep;
+    #[doc(hidden)]
+    type ArbitraryStep = <M as Mutator<T>>::ArbitraryStep;
+    #[doc(hidden)]
+    type UnmutateToken = UnmutateToken<T, <M as Mutator<T>>::Cache, <M as Mutator<T>>::UnmutateToken>;
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+        self.mutator.default_arbitrary_step()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn is_valid(&self, value: &T) -> bool {
+        self.mutator.is_valid(value) && self.satisfies_invariant(value)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn validate_value(&self, value: &T) -> Option<Self::Cache> {
+        if self.satisfies_invariant(value) {
+            self.mutator.validate_value(value)
+        } else {
+            None
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_mutation_step(&self, value: &T, cache: &Self::Cache) -> Self::MutationStep {
+        self.mutator.default_mutation_step(value, cache)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        self.mutator.max_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        self.mutator.global_search_space_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        self.mutator.min_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn complexity(&self, value: &T, cache: &Self::Cache) -> f64 {
+        self.mutator.complexity(value, cache)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(T, f64)> {
+        loop {
+            let (v, cplx) = self.mutator.ordered_arbitrary(step, max_cplx)?;
+            if let Some(x) = self.repair_arbitrary_value(v, cplx) {
+                return Some(x);
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_arbitrary(&self, max_cplx: f64) -> (T, f64) {
+        loop {
+            let (v, cplx) = self.mutator.random_arbitrary(max_cplx);
+            if let Some(x) = self.repair_arbitrary_value(v, cplx) {
+                return x;
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut T,
+        cache: &mut Self::Cache,
+        step: &mut Self::MutationStep,
+        subvalue_provider: &dyn crate::SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        loop {
+            let (t, cplx) = self
+                .mutator
+                .ordered_mutate(value, cache, step, subvalue_provider, max_cplx)?;
+            if let Some(x) = self.repair_mutated_value(value, cache, t, cplx) {
+                return Some(x);
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_mutate(&self, value: &mut T, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
+        loop {
+            let (t, cplx) = self.mutator.random_mutate(value, cache, max_cplx);
+            if let Some(x) = self.repair_mutated_value(value, cache, t, cplx) {
+                return x;
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn unmutate(&self, value: &mut T, cache: &mut Self::Cache, t: Self::UnmutateToken) {
+        let UnmutateToken { inner, unrepaired } = t;
+        if let Some((unrepaired_value, unrepaired_cache)) = unrepaired {
+            *value = unrepaired_value;
+            *cache = unrepaired_cache;
+        }
+        self.mutator.unmutate(value, cache, inner)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn visit_subvalues<'a>(&self, value: &'a T, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
+        self.mutator.visit_subvalues(value, cache, visit)
+    }
+}
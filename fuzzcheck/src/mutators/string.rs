@@ -1,8 +1,237 @@
-use crate::mutators::map::MapMutator;
-use crate::{DefaultMutator, Mutator};
+//! Mutators for `String` and `char` that work on unicode scalar values instead of bytes.
+//!
+//! The [`StringMutator`] manipulates the characters of the string using a [`VecMutator`]. The
+//! characters themselves are generated by a [`UnicodeCharMutator`], which favours characters that
+//! are likely to uncover bugs in text processing code: ASCII, control characters, combining marks,
+//! zero-width and bidirectional formatting characters, characters outside the basic multilingual plane, etc.
+use std::any::Any;
+use std::cmp::Ordering;
+use std::ops::{RangeBounds, RangeInclusive};
 
-/// The default mutator for strings. It is not very good and will be replaced by a different
-/// one in the future.
+use super::character_classes::CharacterMutator;
+use super::vector::VecMutator;
+use super::vose_alias::VoseAlias;
+use super::{inclusive_len_range, CrossoverStep, DefaultMutatorWithLength};
+use crate::{DefaultMutator, Mutator, CROSSOVER_RATE};
+
+/// A mutator for `char` which chooses characters from a list of weighted character classes.
+///
+/// ```
+/// use fuzzcheck::mutators::string::UnicodeCharMutator;
+///
+/// // mostly lowercase letters, sometimes a digit
+/// let m = UnicodeCharMutator::new(vec![(vec!['a'..='z'], 0.9), (vec!['0'..='9'], 0.1)]);
+/// // the default character classes, which contain every `char`
+/// let m = UnicodeCharMutator::default();
+/// ```
+pub struct UnicodeCharMutator {
+    classes: Vec<CharacterMutator>,
+    alias: VoseAlias,
+    search_space_complexity: f64,
+    min_cplx: f64,
+    max_cplx: f64,
+}
+
+impl UnicodeCharMutator {
+    /// Create a mutator from a list of character classes, each made of a list of ranges and
+    /// associated with the probability that a new character is chosen from it.
+    ///
+    /// The weights do not need to add up to 1.
+    #[no_coverage]
+    pub fn new(classes: Vec<(Vec<RangeInclusive<char>>, f64)>) -> Self {
+        assert!(!classes.is_empty());
+        let (classes, weights): (Vec<_>, Vec<_>) = classes
+            .into_iter()
+            .map(
+                #[no_coverage]
+                |(ranges, weight)| (CharacterMutator::new(ranges), weight),
+            )
+            .unzip();
+        let search_space_complexity = classes
+            .iter()
+            .map(
+                #[no_coverage]
+                |m| m.global_search_space_complexity(),
+            )
+            .max_by(
+                #[no_coverage]
+                |x, y| x.partial_cmp(y).unwrap_or(Ordering::Equal),
+            )
+            .unwrap();
+        let min_cplx = classes.iter().fold(
+            f64::INFINITY,
+            #[no_coverage]
+            |cplx, m| cplx.min(m.min_complexity()),
+        );
+        let max_cplx = classes.iter().fold(
+            0.0f64,
+            #[no_coverage]
+            |cplx, m| cplx.max(m.max_complexity()),
+        );
+        Self {
+            classes,
+            alias: VoseAlias::new(weights),
+            search_space_complexity,
+            min_cplx,
+            max_cplx,
+        }
+    }
+
+    #[no_coverage]
+    fn random_char(&self, max_cplx: f64) -> (char, f64) {
+        let class = &self.classes[self.alias.sample()];
+        class.random_arbitrary(max_cplx)
+    }
+}
+
+impl Default for UnicodeCharMutator {
+    /// The default character classes. Every `char` can be generated, but ASCII characters are the most likely.
+    #[no_coverage]
+    fn default() -> Self {
+        Self::new(vec![
+            // printable ASCII
+            (vec![' '..='~'], 0.55),
+            // ASCII control characters
+            (vec!['\0'..='\u{1f}', '\u{7f}'..='\u{7f}'], 0.05),
+            // latin-1 supplement and latin extended
+            (vec!['\u{80}'..='\u{24f}'], 0.08),
+            // combining diacritical marks
+            (vec!['\u{300}'..='\u{36f}'], 0.04),
+            // the rest of the basic multilingual plane
+            (vec!['\u{370}'..='\u{d7ff}', '\u{e000}'..='\u{ffff}'], 0.12),
+            // zero-width, bidirectional formatting, byte order mark, and replacement characters
+            (
+                vec![
+                    '\u{200b}'..='\u{200f}',
+                    '\u{202a}'..='\u{202e}',
+                    '\u{2060}'..='\u{2064}',
+                    '\u{feff}'..='\u{feff}',
+                    '\u{fffd}'..='\u{fffd}',
+                ],
+                0.06,
+            ),
+            // emojis and pictographs, which are outside the basic multilingual plane
+            (vec!['\u{1f300}'..='\u{1faff}'], 0.05),
+            // any character
+            (vec!['\0'..=char::MAX], 0.05),
+        ])
+    }
+}
+
+impl Mutator<char> for UnicodeCharMutator {
+    #[doc(hidden)]
+    type Cache = ();
+    #[doc(hidden)]
+    type MutationStep = ();
+    #[doc(hidden)]
+    type ArbitraryStep = ();
+    #[doc(hidden)]
+    type UnmutateToken = char;
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {}
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn is_valid(&self, value: &char) -> bool {
+        self.classes.iter().any(
+            #[no_coverage]
+            |m| m.is_valid(value),
+        )
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn validate_value(&self, value: &char) -> Option<Self::Cache> {
+        self.is_valid(value).then_some(())
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_mutation_step(&self, _value: &char, _cache: &Self::Cache) -> Self::MutationStep {}
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        self.search_space_complexity
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        self.max_cplx
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        self.min_cplx
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn complexity(&self, value: &char, cache: &Self::Cache) -> f64 {
+        // the complexity of a character does not depend on its class
+        self.classes[0].complexity(value, cache)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_arbitrary(&self, _step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(char, f64)> {
+        if max_cplx < self.min_complexity() {
+            return None;
+        }
+        Some(self.random_char(max_cplx))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_arbitrary(&self, max_cplx: f64) -> (char, f64) {
+        self.random_char(max_cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut char,
+        cache: &mut Self::Cache,
+        _step: &mut Self::MutationStep,
+        _subvalue_provider: &dyn crate::SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        if max_cplx < self.min_complexity() {
+            return None;
+        }
+        Some(self.random_mutate(value, cache, max_cplx))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_mutate(&self, value: &mut char, _cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
+        let (c, cplx) = self.random_char(max_cplx);
+        (std::mem::replace(value, c), cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn unmutate(&self, value: &mut char, _cache: &mut Self::Cache, t: Self::UnmutateToken) {
+        *value = t;
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn visit_subvalues<'a>(&self, _value: &'a char, _cache: &'a Self::Cache, _visit: &mut dyn FnMut(&'a dyn Any, f64)) {
+    }
+}
+
+/// The default mutator for strings.
+///
+/// It mutates the characters of the string, never splitting a character in the middle of its
+/// UTF-8 encoding. It also splices substrings of other strings given by the [`SubValueProvider`](crate::SubValueProvider),
+/// which it finds through the characters of each string, given as a `Vec<char>` subvalue.
+/// The complexity of a string is `1.0` plus the sum of the complexities of its characters.
 ///
 /// Construct it with:
 /// ```rust
@@ -13,25 +242,74 @@ use crate::{DefaultMutator, Mutator};
 /// use fuzzcheck::mutators::string::string_mutator;
 ///
 /// let m = string_mutator();
+/// // or, to choose the characters and the range of lengths of the string:
+/// use fuzzcheck::mutators::string::{StringMutator, UnicodeCharMutator};
+///
+/// let m = StringMutator::new(UnicodeCharMutator::new(vec![(vec!['a'..='z'], 1.0)]), 1..=10);
 /// ```
-pub type StringMutator = impl Mutator<String>;
+pub struct StringMutator {
+    mutator: VecMutator<char, UnicodeCharMutator>,
+    rng: fastrand::Rng,
+}
+
+impl StringMutator {
+    /// Create a string mutator whose characters are generated by `char_mutator` and whose length,
+    /// counted in characters, is within `len_range`
+    #[no_coverage]
+    pub fn new(char_mutator: UnicodeCharMutator, len_range: RangeInclusive<usize>) -> Self {
+        Self {
+            mutator: VecMutator::new(char_mutator, len_range, true),
+            rng: fastrand::Rng::new(),
+        }
+    }
+
+    /// Insert a substring of a string given by the subvalue provider, possibly replacing some characters
+    #[no_coverage]
+    fn splice(
+        &self,
+        value: &mut String,
+        cache: &mut <Self as Mutator<String>>::Cache,
+        step: &mut <Self as Mutator<String>>::MutationStep,
+        subvalue_provider: &dyn crate::SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(<Self as Mutator<String>>::UnmutateToken, f64)> {
+        let (subvalue, _) = step.crossover_step.get_next_subvalue(subvalue_provider, max_cplx)?;
+        if subvalue.is_empty() {
+            return None;
+        }
+        let start = self.rng.usize(..subvalue.len());
+        let end = self.rng.usize(start + 1..=subvalue.len());
+
+        let mut chars = cache.chars.clone();
+        let idx = self.rng.usize(..=chars.len());
+        let nbr_replaced = if self.rng.bool() {
+            0
+        } else {
+            self.rng.usize(..=chars.len() - idx)
+        };
+        chars.splice(idx..idx + nbr_replaced, subvalue[start..end].iter().copied());
+
+        let inner = self.mutator.validate_value(&chars)?;
+        let cplx = self.mutator.complexity(&chars, &inner);
+        if cplx > max_cplx {
+            return None;
+        }
+        let new_value = chars.iter().collect();
+        Some((
+            UnmutateToken::Replace {
+                value: std::mem::replace(value, new_value),
+                cache: std::mem::replace(cache, StringMutatorCache { chars, inner }),
+            },
+            cplx,
+        ))
+    }
+}
 
 #[no_coverage]
 pub fn string_mutator() -> StringMutator {
-    MapMutator::new(
-        // the base mutator produces values of type Vector<u8>
-        <Vec<u8>>::default_mutator(),
-        // the parse function: given a string, how can I get a vector?
-        #[no_coverage]
-        |string: &String| Some(string.as_bytes().to_vec()),
-        // the map function: how can I get a string from a vector?
-        #[no_coverage]
-        |xs| String::from_utf8_lossy(xs).to_string(),
-        // the complexity function
-        #[no_coverage]
-        |value, _cplx| (value.as_bytes().len() * 8) as f64,
-    )
+    StringMutator::new(UnicodeCharMutator::default(), 0..=usize::MAX)
 }
+
 impl DefaultMutator for String {
     type Mutator = StringMutator;
 
@@ -40,3 +318,246 @@ impl DefaultMutator for String {
         string_mutator()
     }
 }
+
+impl DefaultMutatorWithLength for String {
+    type Mutator = StringMutator;
+
+    #[no_coverage]
+    fn default_mutator_with_length<RB: RangeBounds<usize>>(len_range: RB) -> Self::Mutator {
+        StringMutator::new(UnicodeCharMutator::default(), inclusive_len_range(len_range))
+    }
+}
+
+/// The cache of the [`StringMutator`], which contains the characters of the string
+#[derive(Clone)]
+pub struct StringMutatorCache {
+    chars: Vec<char>,
+    inner: <VecMutator<char, UnicodeCharMutator> as Mutator<Vec<char>>>::Cache,
+}
+
+#[derive(Clone)]
+pub struct StringMutationStep {
+    inner: <VecMutator<char, UnicodeCharMutator> as Mutator<Vec<char>>>::MutationStep,
+    crossover_step: CrossoverStep<Vec<char>>,
+}
+
+pub enum UnmutateToken {
+    Inner(<VecMutator<char, UnicodeCharMutator> as Mutator<Vec<char>>>::UnmutateToken),
+    Replace { value: String, cache: StringMutatorCache },
+}
+
+impl Mutator<String> for StringMutator {
+    #[doc(hidden)]
+    type Cache = StringMutatorCache;
+    #[doc(hidden)]
+    type MutationStep = StringMutationStep;
+    #[doc(hidden)]
+    type ArbitraryStep = <VecMutator<char, UnicodeCharMutator> as Mutator<Vec<char>>>::ArbitraryStep;
+    #[doc(hidden)]
+    type UnmutateToken = UnmutateToken;
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+        self.mutator.default_arbitrary_step()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn is_valid(&self, value: &String) -> bool {
+        self.mutator.is_valid(&value.chars().collect())
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn validate_value(&self, value: &String) -> Option<Self::Cache> {
+        let chars = value.chars().collect::<Vec<_>>();
+        let inner = self.mutator.validate_value(&chars)?;
+        Some(StringMutatorCache { chars, inner })
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_mutation_step(&self, _value: &String, cache: &Self::Cache) -> Self::MutationStep {
+        StringMutationStep {
+            inner: self.mutator.default_mutation_step(&cache.chars, &cache.inner),
+            crossover_step: CrossoverStep::default(),
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        self.mutator.global_search_space_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        self.mutator.max_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        self.mutator.min_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn complexity(&self, _value: &String, cache: &Self::Cache) -> f64 {
+        self.mutator.complexity(&cache.chars, &cache.inner)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(String, f64)> {
+        let (chars, cplx) = self.mutator.ordered_arbitrary(step, max_cplx)?;
+        Some((chars.into_iter().collect(), cplx))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_arbitrary(&self, max_cplx: f64) -> (String, f64) {
+        let (chars, cplx) = self.mutator.random_arbitrary(max_cplx);
+        (chars.into_iter().collect(), cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut String,
+        cache: &mut Self::Cache,
+        step: &mut Self::MutationStep,
+        subvalue_provider: &dyn crate::SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        if max_cplx < self.min_complexity() {
+            return None;
+        }
+        if self.rng.u8(..CROSSOVER_RATE) == 0 {
+            if let Some(result) = self.splice(value, cache, step, subvalue_provider, max_cplx) {
+                return Some(result);
+            }
+        }
+        let (token, cplx) = self.mutator.ordered_mutate(
+            &mut cache.chars,
+            &mut cache.inner,
+            &mut step.inner,
+            subvalue_provider,
+            max_cplx,
+        )?;
+        *value = cache.chars.iter().collect();
+        Some((UnmutateToken::Inner(token), cplx))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_mutate(&self, value: &mut String, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
+        let (token, cplx) = self.mutator.random_mutate(&mut cache.chars, &mut cache.inner, max_cplx);
+        *value = cache.chars.iter().collect();
+        (UnmutateToken::Inner(token), cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn unmutate(&self, value: &mut String, cache: &mut Self::Cache, t: Self::UnmutateToken) {
+        match t {
+            UnmutateToken::Inner(t) => {
+                self.mutator.unmutate(&mut cache.chars, &mut cache.inner, t);
+                *value = cache.chars.iter().collect();
+            }
+            UnmutateToken::Replace {
+                value: old_value,
+                cache: old_cache,
+            } => {
+                *value = old_value;
+                *cache = old_cache;
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn visit_subvalues<'a>(&self, _value: &'a String, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
+        // the characters of the string, instead of the string itself, such that the string is not visited twice
+        // when its parent visits it too, while the strings given to the top-level mutator can still be spliced
+        visit(&cache.chars, self.mutator.complexity(&cache.chars, &cache.inner));
+        self.mutator.visit_subvalues(&cache.chars, &cache.inner, visit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnmutateToken;
+    use crate::mutators::testing_utilities::test_mutator;
+    use crate::subvalue_provider::{CrossoverSubValueProvider, EmptySubValueProvider, Generation, SubValueProviderId};
+    use crate::{DefaultMutator, Mutator};
+
+    #[test]
+    fn test_string_mutator() {
+        let m = String::default_mutator();
+        test_mutator(m, 500., 500., false, true, 100, 100);
+    }
+
+    #[test]
+    fn test_string_mutator_round_trip() {
+        let m = String::default_mutator();
+        for s in [
+            "",
+            "hello",
+            "\0\u{7f}",
+            "e\u{301}",
+            "\u{202e}abc\u{200b}",
+            "日本語",
+            "🦀🏳️‍🌈",
+        ] {
+            let mut value = s.to_owned();
+            let mut cache = m.validate_value(&value).unwrap();
+            let cplx = m.complexity(&value, &cache);
+            let mut step = m.default_mutation_step(&value, &cache);
+            for _ in 0..100 {
+                let (token, _) = m
+                    .ordered_mutate(&mut value, &mut cache, &mut step, &EmptySubValueProvider, 1000.0)
+                    .unwrap();
+                m.unmutate(&mut value, &mut cache, token);
+                assert_eq!(value, s);
+                assert_eq!(m.complexity(&value, &cache), cplx);
+            }
+        }
+    }
+
+    #[test]
+    fn test_string_mutator_splices_subvalues() {
+        let m = String::default_mutator();
+        // the string given to the fuzz test, which is not inside a collection or a tuple
+        let other = "fuzzcheck".to_owned();
+        let other_cache = m.validate_value(&other).unwrap();
+        let provider = CrossoverSubValueProvider::new(
+            SubValueProviderId {
+                idx: 0,
+                generation: Generation(0),
+            },
+            &other,
+            &other_cache,
+            &m,
+        );
+        let mut value = "0123456789".to_owned();
+        let mut cache = m.validate_value(&value).unwrap();
+        let mut step = m.default_mutation_step(&value, &cache);
+        let mut nbr_splices = 0;
+        for _ in 0..1000 {
+            let (token, cplx) = m
+                .ordered_mutate(&mut value, &mut cache, &mut step, &provider, 1000.0)
+                .unwrap();
+            assert_eq!(cplx, m.complexity(&value, &m.validate_value(&value).unwrap()));
+            if matches!(token, UnmutateToken::Replace { .. }) {
+                assert!(value.chars().any(|c| "fuzchek".contains(c)), "{}", value);
+                nbr_splices += 1;
+            }
+            m.unmutate(&mut value, &mut cache, token);
+        }
+        assert!(nbr_splices > 0);
+    }
+}
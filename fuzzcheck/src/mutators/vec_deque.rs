@@ -0,0 +1,559 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::ops::{RangeBounds, RangeInclusive};
+
+use super::vector::{VecArbitraryStep, VecMutator};
+use crate::mutators::vose_alias::VoseAlias;
+use crate::mutators::{inclusive_len_range, DefaultMutatorWithLength};
+use crate::{DefaultMutator, Mutator};
+
+/// The maximum number of operations tried by a single mutation before giving up
+const MAX_NBR_TRIES: usize = 16;
+
+/// Default mutator of `VecDeque<T>`
+///
+/// It inserts, removes, and mutates the elements of the deque in place. New deques are generated
+/// by a [`VecMutator`], and their complexity is the same as the complexity of the equivalent `Vec`.
+pub struct VecDequeMutator<T, M>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    mutator: VecMutator<T, M>,
+    len_range: RangeInclusive<usize>,
+    rng: fastrand::Rng,
+}
+
+impl<T, M> VecDequeMutator<T, M>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    /// See [`VecMutator::new`]
+    #[no_coverage]
+    pub fn new(m: M, len_range: RangeInclusive<usize>, inherent_complexity: bool) -> Self {
+        Self {
+            mutator: VecMutator::new(m, len_range.clone(), inherent_complexity),
+            len_range,
+            rng: fastrand::Rng::new(),
+        }
+    }
+}
+
+impl<T> DefaultMutator for VecDeque<T>
+where
+    T: DefaultMutator + 'static,
+{
+    type Mutator = VecDequeMutator<T, T::Mutator>;
+    #[no_coverage]
+    fn default_mutator() -> Self::Mutator {
+        VecDequeMutator::new(T::default_mutator(), 0..=usize::MAX, true)
+    }
+}
+impl<T> DefaultMutatorWithLength for VecDeque<T>
+where
+    T: DefaultMutator + 'static,
+{
+    type Mutator = VecDequeMutator<T, T::Mutator>;
+    #[no_coverage]
+    fn default_mutator_with_length<RB: RangeBounds<usize>>(len_range: RB) -> Self::Mutator {
+        VecDequeMutator::new(T::default_mutator(), inclusive_len_range(len_range), true)
+    }
+}
+
+/// The caches of the elements, in the same order as the elements of the deque
+pub struct Cache<C> {
+    inner: VecDeque<C>,
+    sum_cplx: f64,
+}
+impl<C: Clone> Clone for Cache<C> {
+    #[no_coverage]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            sum_cplx: self.sum_cplx,
+        }
+    }
+}
+
+/// The mutation step of the deque.
+///
+/// The steps of the elements are in the same order as the elements of the deque. The operations whose
+/// ordered mutations are exhausted are removed, such that the ordered mutations of the deque end.
+pub struct MutationStep<S, A> {
+    inner: Vec<S>,
+    operations: Vec<Operation<A>>,
+    weights: Vec<f64>,
+    /// `None` when all the operations are exhausted
+    sampling: Option<VoseAlias>,
+}
+impl<S: Clone, A: Clone> Clone for MutationStep<S, A> {
+    #[no_coverage]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            operations: self.operations.clone(),
+            weights: self.weights.clone(),
+            sampling: self.sampling.clone(),
+        }
+    }
+}
+
+/// An operation performed by the ordered mutations of the deque
+#[derive(Clone)]
+enum Operation<A> {
+    /// Insert the elements given by the arbitrary step, each one at the next index
+    Insert { arbitrary_step: A, next_idx: usize },
+    /// Remove each element, in order
+    Remove { next_idx: usize },
+    /// Mutate the elements at the given indices, in turn
+    MutateElement { indices: Vec<usize>, next: usize },
+}
+
+/// Why an operation on the deque failed
+enum OperationError {
+    /// The ordered mutations of the operation, or of the element it was applied to, are exhausted
+    Exhausted,
+    /// The operation was not applied, e.g. because the new element is too complex
+    Rejected,
+}
+
+/// Each token stores the sum of the complexities of the elements before the mutation
+pub enum UnmutateToken<T, C, U> {
+    Insert {
+        idx: usize,
+        sum_cplx: f64,
+    },
+    Remove {
+        idx: usize,
+        element: T,
+        element_cache: C,
+        sum_cplx: f64,
+    },
+    MutateElement {
+        idx: usize,
+        token: U,
+        sum_cplx: f64,
+    },
+    Replace {
+        value: VecDeque<T>,
+        cache: Cache<C>,
+    },
+}
+
+type Token<T, M> = UnmutateToken<T, <M as Mutator<T>>::Cache, <M as Mutator<T>>::UnmutateToken>;
+
+impl<T, M> VecDequeMutator<T, M>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    /// Insert a new element at index `idx`, given by the arbitrary step if there is one
+    #[no_coverage]
+    fn insert(
+        &self,
+        value: &mut VecDeque<T>,
+        cache: &mut Cache<M::Cache>,
+        idx: usize,
+        arbitrary_step: Option<&mut M::ArbitraryStep>,
+        max_cplx: f64,
+    ) -> Result<(Token<T, M>, f64), OperationError> {
+        let m = self.mutator.element_mutator();
+        if value.len() >= *self.len_range.end() {
+            return Err(OperationError::Rejected);
+        }
+        let max_element_cplx = max_cplx - self.mutator.complexity_from_inner(cache.sum_cplx, value.len() + 1);
+        if max_element_cplx < m.min_complexity() {
+            return Err(OperationError::Rejected);
+        }
+        let (element, element_cplx) = if let Some(step) = arbitrary_step {
+            m.ordered_arbitrary(step, max_element_cplx)
+                .ok_or(OperationError::Exhausted)?
+        } else {
+            m.random_arbitrary(max_element_cplx)
+        };
+        let element_cache = m.validate_value(&element).ok_or(OperationError::Rejected)?;
+        let sum_cplx = cache.sum_cplx;
+        value.insert(idx, element);
+        cache.inner.insert(idx, element_cache);
+        cache.sum_cplx += element_cplx;
+        let cplx = self.mutator.complexity_from_inner(cache.sum_cplx, value.len());
+        Ok((UnmutateToken::Insert { idx, sum_cplx }, cplx))
+    }
+
+    #[no_coverage]
+    fn remove(&self, value: &mut VecDeque<T>, cache: &mut Cache<M::Cache>, idx: usize) -> (Token<T, M>, f64) {
+        let sum_cplx = cache.sum_cplx;
+        let element = value.remove(idx).unwrap();
+        let element_cache = cache.inner.remove(idx).unwrap();
+        cache.sum_cplx -= self.mutator.element_mutator().complexity(&element, &element_cache);
+        let cplx = self.mutator.complexity_from_inner(cache.sum_cplx, value.len());
+        (
+            UnmutateToken::Remove {
+                idx,
+                element,
+                element_cache,
+                sum_cplx,
+            },
+            cplx,
+        )
+    }
+
+    /// Mutate the element at index `idx`, with an ordered mutation if a step is given
+    #[no_coverage]
+    fn mutate_element(
+        &self,
+        value: &mut VecDeque<T>,
+        cache: &mut Cache<M::Cache>,
+        idx: usize,
+        step: Option<(&mut M::MutationStep, &dyn crate::SubValueProvider)>,
+        max_cplx: f64,
+    ) -> Result<(Token<T, M>, f64), OperationError> {
+        let m = self.mutator.element_mutator();
+        let len = value.len();
+        let element = &mut value[idx];
+        let element_cache = &mut cache.inner[idx];
+        let old_el_cplx = m.complexity(element, element_cache);
+        let max_el_cplx = max_cplx - self.mutator.complexity_from_inner(cache.sum_cplx - old_el_cplx, len);
+
+        let (token, element_cplx) = if let Some((step, subvalue_provider)) = step {
+            m.ordered_mutate(element, element_cache, step, subvalue_provider, max_el_cplx)
+                .ok_or(OperationError::Exhausted)?
+        } else {
+            m.random_mutate(element, element_cache, max_el_cplx)
+        };
+        let sum_cplx = cache.sum_cplx;
+        cache.sum_cplx += element_cplx - old_el_cplx;
+        let cplx = self.mutator.complexity_from_inner(cache.sum_cplx, len);
+        Ok((UnmutateToken::MutateElement { idx, token, sum_cplx }, cplx))
+    }
+}
+
+impl<T, M> Mutator<VecDeque<T>> for VecDequeMutator<T, M>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    #[doc(hidden)]
+    type Cache = Cache<M::Cache>;
+    #[doc(hidden)]
+    type MutationStep = MutationStep<M::MutationStep, M::ArbitraryStep>;
+    #[doc(hidden)]
+    type ArbitraryStep = VecArbitraryStep;
+    #[doc(hidden)]
+    type UnmutateToken = Token<T, M>;
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+        self.mutator.default_arbitrary_step()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn is_valid(&self, value: &VecDeque<T>) -> bool {
+        let m = self.mutator.element_mutator();
+        self.len_range.contains(&value.len())
+            && value.iter().all(
+                #[no_coverage]
+                |element| m.is_valid(element),
+            )
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn validate_value(&self, value: &VecDeque<T>) -> Option<Self::Cache> {
+        if !self.len_range.contains(&value.len()) {
+            return None;
+        }
+        let m = self.mutator.element_mutator();
+        let mut inner = VecDeque::with_capacity(value.len());
+        let mut sum_cplx = 0.0;
+        for element in value.iter() {
+            let element_cache = m.validate_value(element)?;
+            sum_cplx += m.complexity(element, &element_cache);
+            inner.push_back(element_cache);
+        }
+        Some(Cache { inner, sum_cplx })
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_mutation_step(&self, value: &VecDeque<T>, cache: &Self::Cache) -> Self::MutationStep {
+        let m = self.mutator.element_mutator();
+        let inner = value
+            .iter()
+            .zip(cache.inner.iter())
+            .map(
+                #[no_coverage]
+                |(element, element_cache)| m.default_mutation_step(element, element_cache),
+            )
+            .collect();
+        // the weights of the operations are the same as the probabilities of the random mutations
+        let mut operations = vec![];
+        if value.len() < *self.len_range.end() {
+            let arbitrary_step = m.default_arbitrary_step();
+            let insert = Operation::Insert {
+                arbitrary_step,
+                next_idx: 0,
+            };
+            operations.push((insert, 2.0));
+        }
+        if value.len() > *self.len_range.start() {
+            operations.push((Operation::Remove { next_idx: 0 }, 1.0));
+        }
+        if !value.is_empty() && m.max_complexity() > 0.0 {
+            let indices = (0..value.len()).collect();
+            operations.push((Operation::MutateElement { indices, next: 0 }, 5.0));
+        }
+        let (operations, weights): (Vec<_>, Vec<_>) = operations.into_iter().unzip();
+        let sampling = if weights.is_empty() {
+            None
+        } else {
+            Some(VoseAlias::new(weights.clone()))
+        };
+        MutationStep {
+            inner,
+            operations,
+            weights,
+            sampling,
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        self.mutator.global_search_space_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        self.mutator.max_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        self.mutator.min_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn complexity(&self, value: &VecDeque<T>, cache: &Self::Cache) -> f64 {
+        self.mutator.complexity_from_inner(cache.sum_cplx, value.len())
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(VecDeque<T>, f64)> {
+        let (value, cplx) = self.mutator.ordered_arbitrary(step, max_cplx)?;
+        Some((VecDeque::from(value), cplx))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_arbitrary(&self, max_cplx: f64) -> (VecDeque<T>, f64) {
+        let (value, cplx) = self.mutator.random_arbitrary(max_cplx);
+        (VecDeque::from(value), cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut VecDeque<T>,
+        cache: &mut Self::Cache,
+        step: &mut Self::MutationStep,
+        subvalue_provider: &dyn crate::SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        if max_cplx < self.min_complexity() {
+            return None;
+        }
+        for _ in 0..MAX_NBR_TRIES {
+            let op_idx = step.sampling.as_ref()?.sample();
+            let len = value.len();
+            let result = match &mut step.operations[op_idx] {
+                Operation::Insert {
+                    arbitrary_step,
+                    next_idx,
+                } => {
+                    let idx = *next_idx % (len + 1);
+                    *next_idx = idx + 1;
+                    self.insert(value, cache, idx, Some(arbitrary_step), max_cplx)
+                }
+                Operation::Remove { next_idx } => {
+                    if *next_idx < len {
+                        *next_idx += 1;
+                        Ok(self.remove(value, cache, *next_idx - 1))
+                    } else {
+                        Err(OperationError::Exhausted)
+                    }
+                }
+                Operation::MutateElement { indices, next } => {
+                    let pos = *next % indices.len();
+                    let idx = indices[pos];
+                    let element_step = &mut step.inner[idx];
+                    let result =
+                        self.mutate_element(value, cache, idx, Some((element_step, subvalue_provider)), max_cplx);
+                    if let Err(OperationError::Exhausted) = result {
+                        // the order in which the elements are mutated does not matter
+                        indices.swap_remove(pos);
+                        *next = pos;
+                    } else {
+                        *next = pos + 1;
+                    }
+                    result
+                }
+            };
+            match result {
+                Ok(result) => return Some(result),
+                Err(OperationError::Rejected) => {}
+                Err(OperationError::Exhausted) => {
+                    let is_exhausted = match &step.operations[op_idx] {
+                        Operation::Insert { .. } | Operation::Remove { .. } => true,
+                        Operation::MutateElement { indices, .. } => indices.is_empty(),
+                    };
+                    if is_exhausted {
+                        step.operations.remove(op_idx);
+                        step.weights.remove(op_idx);
+                        step.sampling = if step.weights.is_empty() {
+                            None
+                        } else {
+                            Some(VoseAlias::new(step.weights.clone()))
+                        };
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_mutate(
+        &self,
+        value: &mut VecDeque<T>,
+        cache: &mut Self::Cache,
+        max_cplx: f64,
+    ) -> (Self::UnmutateToken, f64) {
+        let can_mutate_elements = self.mutator.element_mutator().max_complexity() > 0.0;
+        for _ in 0..MAX_NBR_TRIES {
+            let len = value.len();
+            let result = match self.rng.u8(..8) {
+                0 | 1 => self.insert(value, cache, self.rng.usize(..=len), None, max_cplx),
+                2 if len > *self.len_range.start() => Ok(self.remove(value, cache, self.rng.usize(..len))),
+                _ if len > 0 && can_mutate_elements => {
+                    self.mutate_element(value, cache, self.rng.usize(..len), None, max_cplx)
+                }
+                _ => continue,
+            };
+            if let Ok(result) = result {
+                return result;
+            }
+        }
+        let (new_value, cplx) = self.random_arbitrary(max_cplx);
+        let new_cache = self.validate_value(&new_value).unwrap();
+        (
+            UnmutateToken::Replace {
+                value: std::mem::replace(value, new_value),
+                cache: std::mem::replace(cache, new_cache),
+            },
+            cplx,
+        )
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn unmutate(&self, value: &mut VecDeque<T>, cache: &mut Self::Cache, t: Self::UnmutateToken) {
+        match t {
+            UnmutateToken::Insert { idx, sum_cplx } => {
+                value.remove(idx);
+                cache.inner.remove(idx);
+                cache.sum_cplx = sum_cplx;
+            }
+            UnmutateToken::Remove {
+                idx,
+                element,
+                element_cache,
+                sum_cplx,
+            } => {
+                value.insert(idx, element);
+                cache.inner.insert(idx, element_cache);
+                cache.sum_cplx = sum_cplx;
+            }
+            UnmutateToken::MutateElement { idx, token, sum_cplx } => {
+                self.mutator
+                    .element_mutator()
+                    .unmutate(&mut value[idx], &mut cache.inner[idx], token);
+                cache.sum_cplx = sum_cplx;
+            }
+            UnmutateToken::Replace {
+                value: old_value,
+                cache: old_cache,
+            } => {
+                *value = old_value;
+                *cache = old_cache;
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn visit_subvalues<'a>(
+        &self,
+        value: &'a VecDeque<T>,
+        cache: &'a Self::Cache,
+        visit: &mut dyn FnMut(&'a dyn Any, f64),
+    ) {
+        let m = self.mutator.element_mutator();
+        for (el, el_cache) in value.iter().zip(cache.inner.iter()) {
+            visit(el, m.complexity(el, el_cache));
+        }
+        for (el, el_cache) in value.iter().zip(cache.inner.iter()) {
+            m.visit_subvalues(el, el_cache, visit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crate::mutators::testing_utilities::test_mutator;
+    use crate::subvalue_provider::EmptySubValueProvider;
+    use crate::{DefaultMutator, Mutator};
+
+    #[test]
+    fn test_vec_deque_mutator() {
+        let m = <VecDeque<u8>>::default_mutator();
+        test_mutator(m, 500., 500., false, true, 100, 100);
+    }
+
+    #[test]
+    fn test_mutations_of_a_deque_wrapping_around_its_buffer() {
+        let m = <VecDeque<u16>>::default_mutator();
+        let mut value = VecDeque::with_capacity(8);
+        value.extend([3, 4, 5]);
+        value.push_front(2);
+        value.push_front(1);
+        assert!(!value.as_slices().1.is_empty());
+        let original = value.clone();
+        let mut cache = m.validate_value(&value).unwrap();
+        let mut step = m.default_mutation_step(&value, &cache);
+        for i in 0..1000 {
+            let ordered = if i % 2 == 0 {
+                m.ordered_mutate(&mut value, &mut cache, &mut step, &EmptySubValueProvider, 1000.0)
+            } else {
+                None
+            };
+            let (token, cplx) = ordered.unwrap_or_else(|| m.random_mutate(&mut value, &mut cache, 1000.0));
+            let fresh_cache = m.validate_value(&value).unwrap();
+            assert!((cplx - m.complexity(&value, &fresh_cache)).abs() < 0.01);
+            m.unmutate(&mut value, &mut cache, token);
+            assert_eq!(value, original);
+            assert!((cache.sum_cplx - m.validate_value(&value).unwrap().sum_cplx).abs() < 0.01);
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use std::any::Any;
 use std::cmp;
 use std::marker::PhantomData;
-use std::ops::RangeInclusive;
+use std::ops::{RangeBounds, RangeInclusive};
 
 use self::vec_mutation::{RevertVectorMutation, VectorMutation, VectorMutationRandomStep, VectorMutationStep};
 use crate::mutators::mutations::{Mutation, RevertMutation};
+use crate::mutators::{inclusive_len_range, DefaultMutatorWithLength};
 use crate::subvalue_provider::EmptySubValueProvider;
 use crate::{DefaultMutator, Mutator};
 
@@ -31,6 +32,37 @@ where
         VecMutator::new(T::default_mutator(), 0..=usize::MAX, true)
     }
 }
+impl<T> DefaultMutatorWithLength for Vec<T>
+where
+    T: DefaultMutator + 'static,
+{
+    type Mutator = VecMutator<T, T::Mutator>;
+    #[no_coverage]
+    fn default_mutator_with_length<RB: RangeBounds<usize>>(len_range: RB) -> Self::Mutator {
+        VecMutator::new(T::default_mutator(), inclusive_len_range(len_range), true)
+    }
+}
+
+/// The distribution of the lengths of the vectors generated randomly by a [`VecMutator`].
+///
+/// The length is always limited by the range of lengths of the mutator and by the maximum complexity of the vector.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LengthDistribution {
+    /// All lengths are equally likely
+    Uniform,
+    /// Starting from the minimum length, each additional element is added with the given probability,
+    /// which must be within `0.0 .. 1.0`. Short vectors are therefore much more likely than long ones.
+    Geometric(f64),
+    /// The probability of each length is proportional to the weight at its index, and lengths beyond
+    /// the last weight are never chosen
+    Weights(Vec<f64>),
+}
+impl Default for LengthDistribution {
+    #[no_coverage]
+    fn default() -> Self {
+        Self::Uniform
+    }
+}
 
 #[derive(Clone)]
 pub enum VecArbitraryStep {
@@ -69,6 +101,7 @@ where
 {
     m: M,
     len_range: RangeInclusive<usize>,
+    length_distribution: LengthDistribution,
     rng: fastrand::Rng,
     mutations: VectorMutation,
     inherent_complexity: bool,
@@ -85,6 +118,7 @@ where
         Self {
             m,
             len_range,
+            length_distribution: LengthDistribution::Uniform,
             rng: fastrand::Rng::new(),
             mutations: VectorMutation::default(),
             inherent_complexity,
@@ -92,8 +126,29 @@ where
         }
     }
 
+    /// Choose the lengths of the randomly generated vectors according to the given distribution
+    #[no_coverage]
+    pub fn with_length_distribution(mut self, length_distribution: LengthDistribution) -> Self {
+        match &length_distribution {
+            LengthDistribution::Uniform => {}
+            LengthDistribution::Geometric(p) => assert!((0.0..1.0).contains(p)),
+            LengthDistribution::Weights(weights) => assert!(weights.iter().all(
+                #[no_coverage]
+                |w| w.is_finite() && *w >= 0.0
+            )),
+        }
+        self.length_distribution = length_distribution;
+        self
+    }
+
+    /// The mutator of the elements of the vector
+    #[no_coverage]
+    pub fn element_mutator(&self) -> &M {
+        &self.m
+    }
+
     #[no_coverage]
-    fn complexity_from_inner(&self, cplx: f64, len: usize) -> f64 {
+    pub(crate) fn complexity_from_inner(&self, cplx: f64, len: usize) -> f64 {
         if self.inherent_complexity {
             1.0 + if len == 0 || self.m.min_complexity() > 0.0 {
                 cplx
@@ -195,7 +250,7 @@ where
         if self.m.global_search_space_complexity() == 0.0 {
             super::size_to_cplxity(self.len_range.end() - self.len_range.start() + 1)
         } else {
-            self.m.global_search_space_complexity() * ((self.len_range.end() - self.len_range.start()) as f64)
+            self.m.global_search_space_complexity() * ((self.len_range.end() - self.len_range.start()) as f64 + 1.0)
         }
     }
     #[doc(hidden)]
@@ -243,15 +298,23 @@ where
                 }
             }
             VecArbitraryStep::Normal { make_empty } => {
-                if !*make_empty || max_cplx <= 1.0 {
+                if !self.len_range.contains(&0) {
+                    Some(self.random_arbitrary(max_cplx))
+                } else if !*make_empty {
                     *make_empty = true;
-                    if self.len_range.contains(&0) {
-                        Some((<_>::default(), self.complexity_from_inner(0.0, 0)))
-                    } else {
-                        Some(self.random_arbitrary(max_cplx))
-                    }
+                    Some((<_>::default(), self.complexity_from_inner(0.0, 0)))
+                } else if *self.len_range.end() == 0 || max_cplx <= 1.0 {
+                    // the empty vector, which was already produced, is the only possible value
+                    None
                 } else {
-                    Some(self.random_arbitrary(max_cplx))
+                    // do not produce the empty vector a second time
+                    for _ in 0..100 {
+                        let (v, cplx) = self.random_arbitrary(max_cplx);
+                        if !v.is_empty() {
+                            return Some((v, cplx));
+                        }
+                    }
+                    None
                 }
             }
         }
@@ -276,7 +339,7 @@ where
         let target_cplx = crate::mutators::gen_f64(&self.rng, min_cplx..max_cplx);
         let len_range = self.choose_slice_length(target_cplx);
         let upperbound_max_len = std::cmp::min(*len_range.end(), (max_cplx / self.m.min_complexity()).ceil() as usize);
-        let target_len = self.rng.usize(0..=upperbound_max_len);
+        let target_len = self.choose_length(upperbound_max_len);
 
         let (v, inner_cplx) =
             self.new_input_with_length_and_complexity(*self.len_range.start(), target_len, target_cplx);
@@ -369,6 +432,38 @@ where
         min_len..=max_len
     }
 
+    /// Choose the length of a new vector, which is at most `max_len`, according to the length distribution
+    #[no_coverage]
+    fn choose_length(&self, max_len: usize) -> usize {
+        let min_len = cmp::min(*self.len_range.start(), max_len);
+        match &self.length_distribution {
+            LengthDistribution::Uniform => self.rng.usize(0..=max_len),
+            LengthDistribution::Geometric(p) => {
+                let mut len = min_len;
+                while len < max_len && self.rng.f64() < *p {
+                    len += 1;
+                }
+                len
+            }
+            LengthDistribution::Weights(weights) => {
+                let weights = weights.get(min_len..=cmp::min(max_len, weights.len().saturating_sub(1)));
+                let weights = weights.unwrap_or(&[]);
+                let sum = weights.iter().sum::<f64>();
+                if sum <= 0.0 {
+                    return min_len;
+                }
+                let mut x = self.rng.f64() * sum;
+                for (i, weight) in weights.iter().enumerate() {
+                    if x < *weight {
+                        return min_len + i;
+                    }
+                    x -= weight;
+                }
+                min_len + weights.len() - 1
+            }
+        }
+    }
+
     #[no_coverage]
     fn new_input_with_length_and_complexity(
         &self,
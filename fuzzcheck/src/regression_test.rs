@@ -0,0 +1,194 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::panic::Location;
+use std::path::{Path, PathBuf};
+
+/// The fuzz-tested function and the source file where the fuzz test was built.
+///
+/// It is used by `cargo fuzzcheck --command reproduce --emit-test` to write a regression
+/// test calling the function, in a file next to the fuzz test.
+#[derive(Clone, Copy)]
+pub(crate) struct FuzzTestLocation {
+    /// The path of the tested function, or `None` if it cannot be named,
+    /// e.g. because it is a closure
+    pub function: Option<&'static str>,
+    /// The source file containing the call to [`fuzz_test`](crate::fuzz_test)
+    pub file: &'static str,
+}
+
+impl FuzzTestLocation {
+    #[track_caller]
+    #[no_coverage]
+    pub fn new<F>(nameable: bool) -> Self {
+        let path = std::any::type_name::<F>();
+        let is_path = !path.is_empty()
+            && path.split("::").all(
+                #[no_coverage]
+                |segment| {
+                    segment.starts_with(
+                        #[no_coverage]
+                        |c: char| c.is_alphabetic() || c == '_',
+                    ) && segment.chars().all(
+                        #[no_coverage]
+                        |c| c.is_alphanumeric() || c == '_',
+                    )
+                },
+            );
+        Self {
+            function: if nameable && is_path { Some(path) } else { None },
+            file: Location::caller().file(),
+        }
+    }
+
+    /// The file in which regression tests are written: `fuzzcheck_regressions.rs`, in the folder
+    /// where the child modules of the fuzz test's module are declared, such that it can be added to the
+    /// module containing the fuzz test with `#[cfg(test)] mod fuzzcheck_regressions;`.
+    ///
+    /// The path of the fuzz test is relative to the workspace root, while the fuzz test is run
+    /// from the root of its package, so the file is searched in the ancestors of the current directory.
+    #[no_coverage]
+    pub fn regressions_file(&self) -> Option<PathBuf> {
+        let file = Path::new(self.file);
+        let file = if file.is_absolute() {
+            file.to_path_buf()
+        } else {
+            let current_dir = std::env::current_dir().ok()?;
+            current_dir
+                .ancestors()
+                .map(
+                    #[no_coverage]
+                    |dir| dir.join(file),
+                )
+                .find(
+                    #[no_coverage]
+                    |path| path.is_file(),
+                )?
+        };
+        Some(module_directory(&file)?.join("fuzzcheck_regressions.rs"))
+    }
+}
+
+/// The folder containing the files of the child modules of the module defined in `file`.
+///
+/// It is the folder of `file` if it is a `mod.rs` file or the root of a crate, that is, `lib.rs`, `main.rs`,
+/// or a file placed directly inside `src/bin`, `tests`, `examples`, or `benches`. Otherwise, it is the folder
+/// named after the module, e.g. `src/foo` for `src/foo.rs`.
+#[no_coverage]
+fn module_directory(file: &Path) -> Option<PathBuf> {
+    let parent = file.parent()?;
+    let stem = file.file_stem()?.to_str()?;
+    let parent_name = parent.file_name().and_then(
+        #[no_coverage]
+        |name| name.to_str(),
+    );
+    let is_crate_root_folder = matches!(parent_name, Some("bin" | "tests" | "examples" | "benches"));
+    if matches!(stem, "mod" | "lib" | "main") || is_crate_root_folder {
+        Some(parent.to_path_buf())
+    } else {
+        Some(parent.join(stem))
+    }
+}
+
+/// Returns the source code of a regression test constructing the value described by `expr`
+/// and calling the tested function, given by its path, on it.
+#[no_coverage]
+pub(crate) fn regression_test_source(function: &str, name: &str, input_file: &Path, expr: &str) -> String {
+    format!(
+        "
+// Generated by `cargo fuzzcheck --command reproduce --emit-test` from {input_file}
+#[test]
+fn {name}() {{
+    let value = {expr};
+    fuzzcheck::reproduce({function}, value);
+}}
+",
+        input_file = input_file.display(),
+        name = name,
+        expr = expr,
+        function = function,
+    )
+}
+
+/// Appends the regression test called `name` to the `fuzzcheck_regressions.rs` file located next to the fuzz test.
+///
+/// Returns the path of the file, and whether the test was written. It is not written if the file already
+/// contains a test with the same name, which happens when the same test case is reproduced twice.
+#[no_coverage]
+pub(crate) fn write_regression_test(
+    location: &FuzzTestLocation,
+    name: &str,
+    source: &str,
+) -> std::io::Result<(PathBuf, bool)> {
+    let path = location.regressions_file().ok_or_else(
+        #[no_coverage]
+        || {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("could not find the source file of the fuzz test: {}", location.file),
+            )
+        },
+    )?;
+    let is_written = append_regression_test(&path, name, source)?;
+    Ok((path, is_written))
+}
+
+/// Appends the regression test called `name` to the file, unless the file already contains it.
+///
+/// When the file is created, it starts with `extern crate self as <crate>`, such that the paths
+/// of the tested function and of the types, which start with the name of their crate, can also
+/// be used inside the crate that contains the fuzz test.
+#[no_coverage]
+fn append_regression_test(path: &Path, name: &str, source: &str) -> std::io::Result<bool> {
+    if let Some(folder) = path.parent() {
+        std::fs::create_dir_all(folder)?;
+    }
+    match std::fs::read_to_string(path) {
+        Ok(content) if content.contains(&format!("fn {}()", name)) => return Ok(false),
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() == 0 {
+        if let Some(krate) = current_crate_name() {
+            writeln!(file, "#[allow(unused_extern_crates)]\nextern crate self as {};", krate)?;
+        }
+    }
+    file.write_all(source.as_bytes())?;
+    Ok(true)
+}
+
+/// The name of the crate containing the fuzz test, deduced from the name of the test executable,
+/// which cargo builds as `<crate_name>-<hash>`.
+#[no_coverage]
+fn current_crate_name() -> Option<String> {
+    let exe = std::env::current_exe().ok()?;
+    let name = exe.file_stem()?.to_str()?;
+    let (krate, _hash) = name.rsplit_once('-')?;
+    Some(krate.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{append_regression_test, regression_test_source};
+
+    #[test]
+    fn test_emitting_the_same_regression_test_twice() {
+        let folder = std::env::temp_dir().join(format!("fuzzcheck_regression_test_{}", std::process::id()));
+        let path = folder.join("fuzzcheck_regressions.rs");
+        let input_file = Path::new("fuzz/artifacts/1234.json");
+        let first = regression_test_source("krate::test", "fuzzcheck_regression_1234", input_file, "1u8");
+        let second = regression_test_source("krate::test", "fuzzcheck_regression_5678", input_file, "2u8");
+
+        assert!(append_regression_test(&path, "fuzzcheck_regression_1234", &first).unwrap());
+        assert!(!append_regression_test(&path, "fuzzcheck_regression_1234", &first).unwrap());
+        assert!(append_regression_test(&path, "fuzzcheck_regression_5678", &second).unwrap());
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(content.matches("fn fuzzcheck_regression_1234()").count(), 1);
+        assert_eq!(content.matches("fn fuzzcheck_regression_5678()").count(), 1);
+    }
+}
@@ -0,0 +1,441 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::os::raw::c_char;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use fuzzcheck_common::arg::Sanitizer;
+
+use crate::sensors_and_pools::TestFailure;
+
+/// The structured content of a report written by a sanitizer (ASan, LSan, MSan, TSan).
+///
+/// It is part of the [`TestFailure`] created when a sanitizer detects an error.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SanitizerReport {
+    /// The sanitizer that detected the error
+    pub sanitizer: Sanitizer,
+    /// The kind of error, e.g. `heap-buffer-overflow` or `use-of-uninitialized-value`
+    pub kind: String,
+    /// The name of the function at the top of the stack trace, if any
+    ///
+    /// Frames belonging to the sanitizer runtime or to the allocator are skipped, and the hash
+    /// added by rustc at the end of symbol names is removed so that it stays the same across builds.
+    pub top_frame: Option<String>,
+}
+
+impl SanitizerReport {
+    /// Parse the report of the given sanitizer.
+    ///
+    /// Returns `None` if the text does not contain the header of a report, such as:
+    /// ```text
+    /// ==1234==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000011 ...
+    /// ```
+    #[no_coverage]
+    pub fn parse(sanitizer: Sanitizer, report: &str) -> Option<Self> {
+        let header = format!("{}: ", sanitizer.report_name());
+        let mut lines = report.lines();
+        let kind = lines.by_ref().find_map(
+            #[no_coverage]
+            |line| {
+                let rest = &line[line.find(&header)? + header.len()..];
+                if !(line.contains("ERROR: ") || line.contains("WARNING: ")) {
+                    return None;
+                }
+                let kind = if sanitizer == Sanitizer::Leak || rest.starts_with("detected memory leaks") {
+                    "memory-leak"
+                } else {
+                    rest.split_whitespace().next()?
+                };
+                Some(kind.to_owned())
+            },
+        )?;
+        let top_frame = lines.find_map(
+            #[no_coverage]
+            |line| {
+                let function = stack_frame_function(line)?;
+                if is_runtime_frame(function) {
+                    None
+                } else {
+                    Some(function.to_owned())
+                }
+            },
+        );
+        Some(Self {
+            sanitizer,
+            kind,
+            top_frame,
+        })
+    }
+
+    /// Converts the report to a test failure, whose identifier only depends on the sanitizer,
+    /// the kind of error, and the top frame of the stack trace.
+    #[no_coverage]
+    pub fn into_test_failure(self) -> TestFailure {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        let display = if let Some(top_frame) = &self.top_frame {
+            format!("{}: {} in {}", self.sanitizer.report_name(), self.kind, top_frame)
+        } else {
+            format!("{}: {}", self.sanitizer.report_name(), self.kind)
+        };
+        TestFailure {
+            display,
+            id: hasher.finish(),
+            sanitizer_report: Some(self),
+        }
+    }
+}
+
+/// Returns the function name of a stack frame line, e.g.:
+/// ```text
+///     #1 0x55d0b5b7c6a1 in my_crate::parse::h9a3b2c1d4e5f6a7b /src/parse.rs:12:5
+/// ```
+#[no_coverage]
+fn stack_frame_function(line: &str) -> Option<&str> {
+    let mut words = line.split_whitespace();
+    let index = words.next()?;
+    if !index.starts_with('#') || index[1..].parse::<usize>().is_err() {
+        return None;
+    }
+    let _address = words.next()?;
+    if words.next()? != "in" {
+        return None;
+    }
+    let function = words.next()?;
+    // e.g. my_crate::parse::h9a3b2c1d4e5f6a7b
+    if let Some((path, hash)) = function.rsplit_once("::h") {
+        if hash.len() == 16
+            && hash.bytes().all(
+                #[no_coverage]
+                |b| b.is_ascii_hexdigit(),
+            )
+        {
+            return Some(path);
+        }
+    }
+    Some(function)
+}
+
+#[no_coverage]
+fn is_runtime_frame(function: &str) -> bool {
+    function.starts_with("__")
+        || matches!(
+            function,
+            "malloc" | "calloc" | "realloc" | "free" | "memcpy" | "memmove" | "memset" | "memcmp" | "strlen"
+        )
+        || function.starts_with("alloc::alloc::")
+}
+
+/// Reads the reports written by the sanitizer runtime.
+///
+/// The reports of the current process are written to the file `<log_path>.<pid>`, where `log_path` is
+/// given in the options of the sanitizer. The file is created when the fuzzer starts, and the sanitizer
+/// runtime is told to write into it with `__sanitizer_set_report_fd`, so that each report is written as
+/// soon as the error is found. Only the part of the file that was written since the last call to
+/// [`self.read_new_report()`](SanitizerReports::read_new_report) is parsed.
+pub(crate) struct SanitizerReports {
+    sanitizer: Sanitizer,
+    /// The file written by the sanitizer runtime, kept open for as long as the runtime writes into it
+    _report_file: Option<File>,
+    /// The same file, opened a second time to read it from the position of the last report
+    file: Option<File>,
+    /// Number of test cases between two leak checks, see [`Arguments::leak_check_interval`](fuzzcheck_common::arg::Arguments)
+    leak_check_interval: Option<usize>,
+}
+
+impl SanitizerReports {
+    #[no_coverage]
+    pub fn new(sanitizer: Sanitizer, leak_check_interval: Option<usize>) -> Self {
+        let path = std::env::var(sanitizer.options_env_var()).ok().and_then(
+            #[no_coverage]
+            |options| {
+                let log_path = options
+                    .split(':')
+                    .rev()
+                    .find_map(
+                        #[no_coverage]
+                        |option| option.strip_prefix("log_path="),
+                    )?
+                    .to_owned();
+                Some(PathBuf::from(format!("{}.{}", log_path, std::process::id())))
+            },
+        );
+        let files = path.and_then(
+            #[no_coverage]
+            |path| {
+                let report_file = File::create(&path).ok()?;
+                let file = File::open(&path).ok()?;
+                Some((report_file, file))
+            },
+        );
+        let (report_file, file) = if let Some((report_file, file)) = files {
+            set_report_file(&report_file);
+            (Some(report_file), Some(file))
+        } else {
+            (None, None)
+        };
+        Self {
+            sanitizer,
+            _report_file: report_file,
+            file,
+            leak_check_interval,
+        }
+    }
+
+    /// Returns the raw text and the parsed content of the report written since the last call, if any.
+    #[no_coverage]
+    pub fn read_new_report(&mut self) -> Option<(String, Option<SanitizerReport>)> {
+        let file = self.file.as_mut()?;
+        let mut text = String::new();
+        file.read_to_string(&mut text).ok()?;
+        if text.is_empty() {
+            return None;
+        }
+        let report = SanitizerReport::parse(self.sanitizer, &text);
+        Some((text, report))
+    }
+
+    /// Returns the error that the sanitizer found while running the test case that just finished, if any.
+    ///
+    /// AddressSanitizer and MemorySanitizer recover from the errors they find. AddressSanitizer calls
+    /// back into fuzzcheck when it reports an error, so its report file is only read after the test cases
+    /// that caused one. MemorySanitizer has no such callback, so the new content of its report file, which
+    /// is already open, is read after each test case. Checking for leaks scans the whole memory of the
+    /// process, so it is only done every `leak_check_interval` test cases. `nbr_previous_runs` is the
+    /// number of test cases that ran before the one that just finished.
+    #[no_coverage]
+    pub fn test_failure(&mut self, nbr_previous_runs: usize) -> Option<TestFailure> {
+        match self.sanitizer {
+            Sanitizer::Address => {
+                if !DID_REPORT_ERROR.swap(false, Ordering::Relaxed) {
+                    return None;
+                }
+            }
+            Sanitizer::Memory => {}
+            Sanitizer::Leak => {
+                let interval = self.leak_check_interval?;
+                if (nbr_previous_runs + 1) % interval != 0 || !detect_leaks() {
+                    return None;
+                }
+            }
+            // ThreadSanitizer aborts the process when it finds a data race
+            Sanitizer::Thread => return None,
+        }
+        let report = match self.read_new_report() {
+            Some((_, report)) => report,
+            // an error was detected, but its report could not be read
+            None if self.sanitizer != Sanitizer::Memory => None,
+            None => return None,
+        };
+        let report = report.unwrap_or_else(
+            #[no_coverage]
+            || SanitizerReport {
+                sanitizer: self.sanitizer,
+                kind: if self.sanitizer == Sanitizer::Leak {
+                    "memory-leak".to_owned()
+                } else {
+                    "unknown-error".to_owned()
+                },
+                top_frame: None,
+            },
+        );
+        Some(report.into_test_failure())
+    }
+}
+
+/// Set each time AddressSanitizer reports an error, and reset when the error is turned into a test failure
+static DID_REPORT_ERROR: AtomicBool = AtomicBool::new(false);
+
+/// The callback given to `__asan_set_error_report_callback`, called at the end of each report.
+///
+/// It runs inside the sanitizer runtime, possibly while the allocator is locked, so it only sets a flag.
+#[cfg_attr(not(sanitize = "address"), allow(dead_code))]
+#[no_coverage]
+extern "C" fn on_error_report(_report: *const c_char) {
+    DID_REPORT_ERROR.store(true, Ordering::Relaxed);
+}
+
+#[cfg(any(sanitize = "address", sanitize = "memory", sanitize = "leak", sanitize = "thread"))]
+extern "C" {
+    fn __sanitizer_set_report_fd(fd: *mut std::os::raw::c_void);
+}
+#[cfg(sanitize = "address")]
+extern "C" {
+    fn __asan_set_error_report_callback(callback: Option<extern "C" fn(*const c_char)>);
+}
+
+/// Make the sanitizer runtime write its reports into the file, and call back into fuzzcheck after each of them.
+///
+/// It does nothing when the crate was not compiled with a sanitizer.
+#[cfg_attr(
+    not(any(sanitize = "address", sanitize = "memory", sanitize = "leak", sanitize = "thread")),
+    allow(unused_variables)
+)]
+#[no_coverage]
+fn set_report_file(file: &File) {
+    #[cfg(any(sanitize = "address", sanitize = "memory", sanitize = "leak", sanitize = "thread"))]
+    unsafe {
+        use std::os::unix::io::AsRawFd;
+        // the runtime takes the file descriptor as a pointer-sized integer
+        __sanitizer_set_report_fd(file.as_raw_fd() as usize as *mut std::os::raw::c_void);
+    }
+    #[cfg(sanitize = "address")]
+    unsafe {
+        __asan_set_error_report_callback(Some(on_error_report));
+    }
+}
+
+#[cfg(any(sanitize = "leak", sanitize = "address"))]
+extern "C" {
+    fn __lsan_do_recoverable_leak_check() -> std::os::raw::c_int;
+}
+
+/// Runs a leak check, returning true if new leaks were found since the last check.
+///
+/// It only works when the crate was compiled with LeakSanitizer or AddressSanitizer.
+#[no_coverage]
+fn detect_leaks() -> bool {
+    #[cfg(any(sanitize = "leak", sanitize = "address"))]
+    {
+        unsafe { __lsan_do_recoverable_leak_check() != 0 }
+    }
+    #[cfg(not(any(sanitize = "leak", sanitize = "address")))]
+    {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use fuzzcheck_common::arg::Sanitizer;
+
+    use super::{on_error_report, SanitizerReport, SanitizerReports};
+
+    const ASAN_REPORT: &str = "=================================================================
+==4242==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000011 at pc 0x55d0b5b7c6a1 bp 0x7ffc5f0e6b30 sp 0x7ffc5f0e6b28
+READ of size 1 at 0x602000000011 thread T0
+    #0 0x55d0b5b7c6a0 in __asan_memcpy (/target/fuzz-abc+0x1a06a0)
+    #1 0x55d0b5b7c6a1 in my_crate::parse::h9a3b2c1d4e5f6a7b /src/parse.rs:12:5
+    #2 0x55d0b5b7d001 in my_crate::tests::fuzz::{{closure}} /src/lib.rs:40:9
+
+0x602000000011 is located 0 bytes to the right of 1-byte region [0x602000000010,0x602000000011)
+allocated by thread T0 here:
+    #0 0x55d0b5b1a2b3 in malloc (/target/fuzz-abc+0x1a2b3)
+SUMMARY: AddressSanitizer: heap-buffer-overflow /src/parse.rs:12:5 in my_crate::parse::h9a3b2c1d4e5f6a7b
+";
+
+    const MSAN_REPORT: &str = "==77==WARNING: MemorySanitizer: use-of-uninitialized-value
+    #0 0x4a3b2c in my_crate::decode::h0123456789abcdef /src/decode.rs:3:8
+    #1 0x4a3c00 in main /src/main.rs:2:1
+";
+
+    const LSAN_REPORT: &str = "
+=================================================================
+==9==ERROR: LeakSanitizer: detected memory leaks
+
+Direct leak of 40 byte(s) in 1 object(s) allocated from:
+    #0 0x5566 in malloc
+    #1 0x5577 in alloc::alloc::alloc::h42
+    #2 0x5588 in my_crate::leak::h000000000000abcd /src/leak.rs:7:13
+";
+
+    #[test]
+    fn test_parse_sanitizer_reports() {
+        let report = SanitizerReport::parse(Sanitizer::Address, ASAN_REPORT).unwrap();
+        assert_eq!(report.kind, "heap-buffer-overflow");
+        assert_eq!(report.top_frame.as_deref(), Some("my_crate::parse"));
+
+        let report = SanitizerReport::parse(Sanitizer::Memory, MSAN_REPORT).unwrap();
+        assert_eq!(report.kind, "use-of-uninitialized-value");
+        assert_eq!(report.top_frame.as_deref(), Some("my_crate::decode"));
+
+        let report = SanitizerReport::parse(Sanitizer::Leak, LSAN_REPORT).unwrap();
+        assert_eq!(report.kind, "memory-leak");
+        assert_eq!(report.top_frame.as_deref(), Some("my_crate::leak"));
+
+        assert!(SanitizerReport::parse(Sanitizer::Memory, ASAN_REPORT).is_none());
+        assert!(SanitizerReport::parse(Sanitizer::Thread, "thread 'main' panicked at 'oops'").is_none());
+    }
+
+    #[test]
+    fn test_sanitizer_failures_are_bucketed() {
+        let first = SanitizerReport::parse(Sanitizer::Address, ASAN_REPORT).unwrap();
+        let second = SanitizerReport::parse(
+            Sanitizer::Address,
+            &ASAN_REPORT.replace("0x602000000011", "0x603000000042"),
+        )
+        .unwrap();
+        let other = SanitizerReport::parse(Sanitizer::Memory, MSAN_REPORT).unwrap();
+
+        let first = first.into_test_failure();
+        assert_eq!(first.id, second.into_test_failure().id);
+        assert_ne!(first.id, other.into_test_failure().id);
+        assert_eq!(
+            first.display,
+            "AddressSanitizer: heap-buffer-overflow in my_crate::parse"
+        );
+    }
+
+    #[test]
+    fn test_recovered_errors_are_test_failures() {
+        let path = std::env::temp_dir().join(format!("fuzzcheck_sanitizer_report_test.{}", std::process::id()));
+        let mut log = std::fs::File::create(&path).unwrap();
+        let mut reports = SanitizerReports {
+            sanitizer: Sanitizer::Address,
+            _report_file: None,
+            file: Some(std::fs::File::open(&path).unwrap()),
+            leak_check_interval: None,
+        };
+        assert!(reports.test_failure(0).is_none());
+
+        // the report file is only read after the sanitizer calls back into fuzzcheck
+        log.write_all(ASAN_REPORT.as_bytes()).unwrap();
+        assert!(reports.test_failure(1).is_none());
+        on_error_report(std::ptr::null());
+        let failure = reports.test_failure(2).unwrap();
+        assert_eq!(
+            failure.display,
+            "AddressSanitizer: heap-buffer-overflow in my_crate::parse"
+        );
+        // the same report is not read twice
+        assert!(reports.test_failure(3).is_none());
+
+        log.write_all(ASAN_REPORT.as_bytes()).unwrap();
+        on_error_report(std::ptr::null());
+        assert_eq!(reports.test_failure(4).unwrap().id, failure.id);
+
+        // MemorySanitizer reports are read after each test case
+        let msan_path = path.with_extension(format!("msan.{}", std::process::id()));
+        let mut msan_log = std::fs::File::create(&msan_path).unwrap();
+        let mut memory_reports = SanitizerReports {
+            sanitizer: Sanitizer::Memory,
+            _report_file: None,
+            file: Some(std::fs::File::open(&msan_path).unwrap()),
+            leak_check_interval: None,
+        };
+        assert!(memory_reports.test_failure(0).is_none());
+        msan_log.write_all(MSAN_REPORT.as_bytes()).unwrap();
+        assert_eq!(
+            memory_reports.test_failure(1).unwrap().display,
+            "MemorySanitizer: use-of-uninitialized-value in my_crate::decode"
+        );
+        assert!(memory_reports.test_failure(2).is_none());
+        std::fs::remove_file(msan_path).unwrap();
+
+        let mut leak_reports = SanitizerReports {
+            sanitizer: Sanitizer::Leak,
+            _report_file: None,
+            file: Some(std::fs::File::open(&path).unwrap()),
+            leak_check_interval: None,
+        };
+        // leaks are not checked without an interval
+        assert!(leak_reports.test_failure(0).is_none());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
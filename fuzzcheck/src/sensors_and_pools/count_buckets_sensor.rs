@@ -0,0 +1,254 @@
+use crate::sensors_and_pools::WrapperSensor;
+use crate::{SaveToStatsFolder, Sensor};
+
+/// Describes how the raw values of counters are grouped into buckets by a [`CountBucketsSensor`].
+///
+/// Each bucket is identified by a number, starting at 1 for the bucket containing the smallest values.
+/// A counter with a value of 0 does not belong to any bucket.
+///
+/// ```
+/// use fuzzcheck::sensors_and_pools::CountBuckets;
+///
+/// let buckets = CountBuckets::log2();
+/// assert_eq!(buckets.bucket(1), 1);
+/// assert_eq!(buckets.bucket(3), 3);
+/// assert_eq!(buckets.bucket(5), 4);
+/// assert_eq!(buckets.bucket(15), 5);
+///
+/// // two buckets: 1-9 and 10+
+/// let buckets = CountBuckets::with_lower_bounds(vec![1, 10]);
+/// assert_eq!(buckets.bucket(9), 1);
+/// assert_eq!(buckets.bucket(1_000), 2);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum CountBuckets {
+    /// Log-scale buckets: `1`, `2`, `3`, `4-7`, `8-15`, `16-31`, etc.
+    Log2,
+    /// The buckets used by AFL: `1`, `2`, `3`, `4-7`, `8-15`, `16-31`, `32-127`, `128+`
+    Afl,
+    /// User-defined buckets, given by their sorted lower bounds
+    LowerBounds(Vec<u64>),
+}
+
+impl CountBuckets {
+    /// Log-scale buckets: `1`, `2`, `3`, `4-7`, `8-15`, `16-31`, etc.
+    #[no_coverage]
+    pub fn log2() -> Self {
+        Self::Log2
+    }
+    /// The buckets used by AFL: `1`, `2`, `3`, `4-7`, `8-15`, `16-31`, `32-127`, `128+`
+    #[no_coverage]
+    pub fn afl() -> Self {
+        Self::Afl
+    }
+    /// User-defined buckets, given by their lower bounds.
+    ///
+    /// For example, `vec![1, 5, 100]` describes the buckets `1-4`, `5-99`, and `100+`.
+    /// Values smaller than the first lower bound do not belong to any bucket and
+    /// are ignored by the [`CountBucketsSensor`].
+    ///
+    /// ## Panics
+    /// Panics if the lower bounds are not sorted in increasing order or contain 0.
+    #[no_coverage]
+    pub fn with_lower_bounds(lower_bounds: Vec<u64>) -> Self {
+        assert!(
+            lower_bounds.windows(2).all(
+                #[no_coverage]
+                |w| w[0] < w[1]
+            ),
+            "the lower bounds of the count buckets must be sorted in strictly increasing order"
+        );
+        assert!(
+            !lower_bounds.contains(&0),
+            "the lower bounds of the count buckets must be greater than 0"
+        );
+        Self::LowerBounds(lower_bounds)
+    }
+
+    /// Returns the bucket of the given counter value, or 0 if it doesn't belong to any bucket
+    #[no_coverage]
+    pub fn bucket(&self, count: u64) -> u64 {
+        if count == 0 {
+            return 0;
+        }
+        match self {
+            CountBuckets::Log2 => {
+                if count <= 3 {
+                    count
+                } else {
+                    // 4-7 -> 4, 8-15 -> 5, etc.
+                    (u64::BITS - count.leading_zeros()) as u64 + 1
+                }
+            }
+            CountBuckets::Afl => match count {
+                1..=3 => count,
+                4..=7 => 4,
+                8..=15 => 5,
+                16..=31 => 6,
+                32..=127 => 7,
+                _ => 8,
+            },
+            CountBuckets::LowerBounds(lower_bounds) => lower_bounds.partition_point(
+                #[no_coverage]
+                |&lower_bound| lower_bound <= count,
+            ) as u64,
+        }
+    }
+}
+
+impl Default for CountBuckets {
+    #[no_coverage]
+    fn default() -> Self {
+        Self::Log2
+    }
+}
+
+/// The result of [`sensor.count_buckets(..)`](crate::SensorExt::count_buckets)
+///
+/// A sensor adapter which replaces the raw value of each counter of the wrapped sensor by the
+/// number of the [bucket](CountBuckets) it falls into. Counters that do not fall into any bucket
+/// are removed from the observations.
+///
+/// The observations of the wrapped sensor must be a list of `(counter_index, count)`, such as the observations
+/// of the [`CodeCoverageSensor`](crate::sensors_and_pools::CodeCoverageSensor). The observations of the
+/// `CountBucketsSensor` have the same shape and the same counter indices.
+///
+/// It is best paired with a [`UniqueValuesPool`](crate::sensors_and_pools::UniqueValuesPool), which keeps one test
+/// case for each bucket of each counter. This rewards test cases that execute a loop a new number of times,
+/// at a coarse granularity, instead of every test case that executes it once more than the previous one.
+/// ```no_run
+/// use fuzzcheck::SensorExt;
+/// use fuzzcheck::sensors_and_pools::{CodeCoverageSensor, CountBuckets, UniqueValuesPool};
+///
+/// let sensor = CodeCoverageSensor::observing_only_files_from_current_dir();
+/// let nbr_counters = sensor.count_instrumented;
+/// let sensor = sensor.count_buckets(CountBuckets::log2());
+/// let pool = UniqueValuesPool::<u64>::new("cov_hits_buckets", nbr_counters);
+/// ```
+pub struct CountBucketsSensor<S>
+where
+    S: Sensor,
+{
+    sensor: S,
+    buckets: CountBuckets,
+}
+
+impl<S> CountBucketsSensor<S>
+where
+    S: Sensor,
+{
+    #[no_coverage]
+    pub fn new(sensor: S, buckets: CountBuckets) -> Self {
+        Self { sensor, buckets }
+    }
+}
+impl<S> SaveToStatsFolder for CountBucketsSensor<S>
+where
+    S: Sensor,
+{
+    #[no_coverage]
+    fn save_to_stats_folder(&self) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+        self.sensor.save_to_stats_folder()
+    }
+}
+impl<S> Sensor for CountBucketsSensor<S>
+where
+    S: Sensor,
+    for<'a> &'a S::Observations: IntoIterator<Item = &'a (usize, u64)>,
+{
+    type Observations = Vec<(usize, u64)>;
+
+    #[no_coverage]
+    fn start_recording(&mut self) {
+        self.sensor.start_recording();
+    }
+
+    #[no_coverage]
+    fn stop_recording(&mut self) {
+        self.sensor.stop_recording();
+    }
+
+    #[no_coverage]
+    fn get_observations(&mut self) -> Self::Observations {
+        let observations = self.sensor.get_observations();
+        let mut result = vec![];
+        for &(index, count) in observations.into_iter() {
+            let bucket = self.buckets.bucket(count);
+            if bucket != 0 {
+                result.push((index, bucket));
+            }
+        }
+        result
+    }
+}
+impl<S> WrapperSensor for CountBucketsSensor<S>
+where
+    S: Sensor,
+    for<'a> &'a S::Observations: IntoIterator<Item = &'a (usize, u64)>,
+{
+    type Wrapped = S;
+    #[no_coverage]
+    fn wrapped(&self) -> &S {
+        &self.sensor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CountBuckets;
+    use crate::sensors_and_pools::UniqueValuesPool;
+    use crate::traits::{CompatibleWithObservations, Pool};
+    use crate::PoolStorageIndex;
+
+    #[test]
+    fn test_log2_buckets() {
+        let buckets = CountBuckets::log2();
+        let expected = [
+            (0, 0),
+            (1, 1),
+            (2, 2),
+            (3, 3),
+            (4, 4),
+            (7, 4),
+            (8, 5),
+            (15, 5),
+            (16, 6),
+            (127, 8),
+            (128, 9),
+            (u64::MAX, 65),
+        ];
+        for (count, bucket) in expected {
+            assert_eq!(buckets.bucket(count), bucket, "count: {}", count);
+        }
+    }
+
+    #[test]
+    fn test_afl_and_custom_buckets() {
+        let buckets = CountBuckets::afl();
+        assert_eq!(buckets.bucket(31), 6);
+        assert_eq!(buckets.bucket(32), 7);
+        assert_eq!(buckets.bucket(127), 7);
+        assert_eq!(buckets.bucket(u64::MAX), 8);
+
+        let buckets = CountBuckets::with_lower_bounds(vec![2, 10, 100]);
+        assert_eq!(buckets.bucket(1), 0);
+        assert_eq!(buckets.bucket(2), 1);
+        assert_eq!(buckets.bucket(99), 2);
+        assert_eq!(buckets.bucket(100), 3);
+    }
+
+    #[test]
+    fn test_one_input_per_counter_and_bucket() {
+        let mut pool = UniqueValuesPool::<u64>::new("buckets", 4);
+        assert!(!pool.process(PoolStorageIndex::mock(0), &[(0, 1), (1, 4)], 2.0).is_empty());
+        // same buckets, more complex: rejected
+        assert!(pool.process(PoolStorageIndex::mock(1), &[(0, 1), (1, 4)], 3.0).is_empty());
+        // same buckets, simpler: replaces the first input
+        let deltas = pool.process(PoolStorageIndex::mock(2), &[(0, 1), (1, 4)], 1.0);
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].remove, vec![PoolStorageIndex::mock(0)]);
+        // new bucket for counter 1
+        assert!(!pool.process(PoolStorageIndex::mock(3), &[(1, 5)], 4.0).is_empty());
+        assert_eq!(pool.stats().size, 2);
+    }
+}
@@ -4,10 +4,12 @@ Types implementing the [Sensor](crate::Sensor) and [Pool](crate::Pool) traits.
 
 mod allocations_sensor;
 mod and_sensor_and_pool;
+mod count_buckets_sensor;
 mod map_sensor;
 mod maximise_each_counter_pool;
 mod maximise_observation_pool;
 mod most_n_diverse_pool;
+mod ngram_and_context_sensors;
 mod noop_sensor;
 mod simplest_to_activate_counter_pool;
 mod static_value_sensor;
@@ -20,6 +22,8 @@ pub use allocations_sensor::{AllocationSensor, CountingAllocator};
 #[doc(inline)]
 pub use and_sensor_and_pool::{AndPool, AndSensor, AndSensorAndPool, DifferentObservations, SameObservations};
 #[doc(inline)]
+pub use count_buckets_sensor::{CountBuckets, CountBucketsSensor};
+#[doc(inline)]
 pub use map_sensor::MapSensor;
 #[doc(inline)]
 pub use map_sensor::WrapperSensor;
@@ -30,6 +34,8 @@ pub use maximise_observation_pool::MaximiseObservationPool;
 #[doc(inline)]
 pub use most_n_diverse_pool::MostNDiversePool;
 #[doc(inline)]
+pub use ngram_and_context_sensors::{ContextSensor, NGramSensor};
+#[doc(inline)]
 pub use noop_sensor::NoopSensor;
 #[doc(inline)]
 pub use simplest_to_activate_counter_pool::SimplestToActivateCounterPool;
@@ -49,6 +55,10 @@ pub use unit_pool::UnitPool;
 
 #[doc(inline)]
 pub use crate::code_coverage_sensor::CodeCoverageSensor;
+#[doc(inline)]
+pub use crate::sanitizer_report::SanitizerReport;
+use std::hash::Hash;
+
 use crate::{Pool, Sensor};
 
 /// A trait for convenience methods automatically implemented for all types that conform to Pool.
@@ -100,6 +110,69 @@ pub trait SensorExt: Sensor {
     {
         MapSensor::new(self, map_f)
     }
+
+    /// Replaces the value of each counter of the sensor by the number of the bucket it falls into.
+    ///
+    /// The resulting sensor is best paired with a [`UniqueValuesPool`], which keeps one test case
+    /// for each bucket of each counter. See [`CountBucketsSensor`] for more details.
+    /// ```no_run
+    /// use fuzzcheck::SensorExt;
+    /// use fuzzcheck::sensors_and_pools::{CodeCoverageSensor, CountBuckets, UniqueValuesPool};
+    ///
+    /// let sensor = CodeCoverageSensor::observing_only_files_from_current_dir();
+    /// let nbr_counters = sensor.count_instrumented;
+    /// let sensor = sensor.count_buckets(CountBuckets::afl());
+    /// let pool = UniqueValuesPool::<u64>::new("cov_hits_buckets", nbr_counters);
+    /// ```
+    #[no_coverage]
+    fn count_buckets(self, buckets: CountBuckets) -> CountBucketsSensor<Self>
+    where
+        Self: Sized,
+        for<'a> &'a Self::Observations: IntoIterator<Item = &'a (usize, u64)>,
+    {
+        CountBucketsSensor::new(self, buckets)
+    }
+
+    /// Observes the sequences of `n` consecutive counters activated by the sensor.
+    ///
+    /// Each sequence is hashed into one of `size` counters. The resulting sensor can be
+    /// paired with any pool compatible with the code coverage sensor, as long as the pool is
+    /// created with a number of counters equal to `size`. See [`NGramSensor`] for more details.
+    /// ```no_run
+    /// use fuzzcheck::SensorExt;
+    /// use fuzzcheck::sensors_and_pools::{CodeCoverageSensor, SimplestToActivateCounterPool};
+    ///
+    /// let sensor = CodeCoverageSensor::observing_only_files_from_current_dir();
+    /// let size = sensor.count_instrumented * 4;
+    /// let sensor = sensor.ngrams(3, size);
+    /// let pool = SimplestToActivateCounterPool::new("simplest_3grams", size);
+    /// ```
+    #[no_coverage]
+    fn ngrams(self, n: usize, size: usize) -> NGramSensor<Self>
+    where
+        Self: Sized,
+        for<'a> &'a Self::Observations: IntoIterator<Item = &'a (usize, u64)>,
+    {
+        NGramSensor::new(self, n, size)
+    }
+
+    /// Combines each counter of the sensor with the context given by the observations of
+    /// the `context` sensor.
+    ///
+    /// Each pair of counter and context is hashed into one of `size` counters. The resulting
+    /// sensor can be paired with any pool compatible with the code coverage sensor, as long as
+    /// the pool is created with a number of counters equal to `size`. See [`ContextSensor`] for
+    /// more details.
+    #[no_coverage]
+    fn with_context<C>(self, context: C, size: usize) -> ContextSensor<Self, C>
+    where
+        Self: Sized,
+        C: Sensor,
+        C::Observations: Hash,
+        for<'a> &'a Self::Observations: IntoIterator<Item = &'a (usize, u64)>,
+    {
+        ContextSensor::new(self, context, size)
+    }
 }
 impl<T> SensorExt for T where T: Sensor {}
 
@@ -0,0 +1,361 @@
+use std::hash::{Hash, Hasher};
+
+use ahash::{AHashMap, AHasher};
+
+use crate::sensors_and_pools::WrapperSensor;
+use crate::{SaveToStatsFolder, Sensor};
+
+/// Fold a list of `(index, count)` observations with possibly colliding indices into a sorted list without
+/// duplicate indices, keeping the highest count for each index.
+#[no_coverage]
+fn collect_hashed_observations(map: &mut AHashMap<usize, u64>) -> Vec<(usize, u64)> {
+    let mut observations = map.drain().collect::<Vec<_>>();
+    observations.sort_unstable_by_key(
+        #[no_coverage]
+        |x| x.0,
+    );
+    observations
+}
+
+#[no_coverage]
+fn insert_hashed_observation(map: &mut AHashMap<usize, u64>, index: usize, count: u64) {
+    let previous = map.entry(index).or_insert(count);
+    if *previous < count {
+        *previous = count;
+    }
+}
+
+/// The result of [`sensor.ngrams(..)`](crate::SensorExt::ngrams)
+///
+/// A sensor adapter whose observations are the sequences of `n` consecutive counters activated by the
+/// wrapped sensor. Each sequence is hashed into one of `size` new counters, and the count associated
+/// with it is the smallest count of the counters in the sequence.
+///
+/// The observations of the wrapped sensor must be a list of `(counter_index, count)`, such as the observations
+/// of the [`CodeCoverageSensor`](crate::sensors_and_pools::CodeCoverageSensor). The observations of the
+/// `NGramSensor` have the same shape, so it can be paired with any pool compatible with the
+/// code coverage sensor, as long as the pool is created with a number of counters equal to `size`.
+///
+/// Note that the code coverage counters do not record the order in which the code regions were executed.
+/// With the `CodeCoverageSensor`, the n-grams are formed from neighbouring *active* counters in the order
+/// of their indices. Two test cases which each activate counters that were already seen, but in a new
+/// combination (for example, taking a new combination of branches in a sequence of `if` statements), will
+/// produce new n-grams. If the wrapped sensor records its observations in execution order instead, for
+/// example through a [`StaticValueSensor`](crate::sensors_and_pools::StaticValueSensor) updated with each
+/// transition of a parser’s state machine, then the n-grams are the ordered transitions of the program.
+///
+/// ```no_run
+/// use fuzzcheck::SensorExt;
+/// use fuzzcheck::sensors_and_pools::{CodeCoverageSensor, SimplestToActivateCounterPool};
+///
+/// let sensor = CodeCoverageSensor::observing_only_files_from_current_dir();
+/// let size = sensor.count_instrumented * 4;
+/// let sensor = sensor.ngrams(2, size);
+/// let pool = SimplestToActivateCounterPool::new("simplest_2grams", size);
+/// ```
+pub struct NGramSensor<S>
+where
+    S: Sensor,
+{
+    sensor: S,
+    n: usize,
+    size: usize,
+    hashed: AHashMap<usize, u64>,
+}
+
+impl<S> NGramSensor<S>
+where
+    S: Sensor,
+{
+    /// Create a new `NGramSensor`
+    ///
+    /// ## Arguments
+    /// - `sensor` is the wrapped sensor
+    /// - `n` is the number of consecutive counters in each n-gram. It must be greater than 0.
+    /// - `size` is the number of counters of the new sensor. It must be greater than 0.
+    #[no_coverage]
+    pub fn new(sensor: S, n: usize, size: usize) -> Self {
+        assert!(n > 0, "the n-grams of an NGramSensor must contain at least one counter");
+        assert!(size > 0, "an NGramSensor must have at least one counter");
+        Self {
+            sensor,
+            n,
+            size,
+            hashed: AHashMap::new(),
+        }
+    }
+    /// The number of counters of the sensor, which is the `size` argument given to
+    /// [`NGramSensor::new`]
+    #[no_coverage]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+impl<S> SaveToStatsFolder for NGramSensor<S>
+where
+    S: Sensor,
+{
+    #[no_coverage]
+    fn save_to_stats_folder(&self) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+        self.sensor.save_to_stats_folder()
+    }
+}
+impl<S> Sensor for NGramSensor<S>
+where
+    S: Sensor,
+    for<'a> &'a S::Observations: IntoIterator<Item = &'a (usize, u64)>,
+{
+    type Observations = Vec<(usize, u64)>;
+
+    #[no_coverage]
+    fn start_recording(&mut self) {
+        self.sensor.start_recording();
+    }
+
+    #[no_coverage]
+    fn stop_recording(&mut self) {
+        self.sensor.stop_recording();
+    }
+
+    #[no_coverage]
+    fn get_observations(&mut self) -> Self::Observations {
+        let observations = self.sensor.get_observations();
+        let observations = observations.into_iter().copied().collect::<Vec<_>>();
+        // if there are fewer active counters than `n`, then the only n-gram is the whole list
+        let window_size = std::cmp::min(self.n, observations.len());
+        if window_size == 0 {
+            return vec![];
+        }
+        for window in observations.windows(window_size) {
+            let mut hasher = AHasher::default();
+            let mut count = u64::MAX;
+            for &(index, counter) in window {
+                index.hash(&mut hasher);
+                count = std::cmp::min(count, counter);
+            }
+            let index = (hasher.finish() % self.size as u64) as usize;
+            insert_hashed_observation(&mut self.hashed, index, count);
+        }
+        collect_hashed_observations(&mut self.hashed)
+    }
+}
+impl<S> WrapperSensor for NGramSensor<S>
+where
+    S: Sensor,
+    for<'a> &'a S::Observations: IntoIterator<Item = &'a (usize, u64)>,
+{
+    type Wrapped = S;
+    #[no_coverage]
+    fn wrapped(&self) -> &S {
+        &self.sensor
+    }
+}
+
+/// The result of [`sensor.with_context(..)`](crate::SensorExt::with_context)
+///
+/// A sensor adapter which combines each counter of the wrapped sensor with a context given by a second sensor.
+/// Each pair `(counter_index, context)` is hashed into one of `size` new counters. This way, a code region
+/// reached in a new context is seen as new coverage by the pool, even if the region had already been reached
+/// in other contexts.
+///
+/// The observations of the wrapped sensor must be a list of `(counter_index, count)`, such as the observations
+/// of the [`CodeCoverageSensor`](crate::sensors_and_pools::CodeCoverageSensor). The context sensor can have
+/// any observations that implement [`Hash`]. Typically, it is a
+/// [`StaticValueSensor`](crate::sensors_and_pools::StaticValueSensor) observing a hash of the call stack or of
+/// the state of the program, which the test function keeps up to date.
+///
+/// The observations of the `ContextSensor` have the same shape as the observations of the wrapped sensor,
+/// so it can be paired with any pool compatible with the code coverage sensor, as long as the pool is created
+/// with a number of counters equal to `size`.
+///
+/// ```no_run
+/// use fuzzcheck::SensorExt;
+/// use fuzzcheck::sensors_and_pools::{CodeCoverageSensor, MaximiseEachCounterPool, StaticValueSensor};
+///
+/// // a hash of the parser’s state, updated by the test function
+/// static mut PARSER_STATE: u64 = 0;
+///
+/// let sensor = CodeCoverageSensor::observing_only_files_from_current_dir();
+/// let size = sensor.count_instrumented * 8;
+/// let context = unsafe { StaticValueSensor::new(&mut PARSER_STATE, 0) };
+/// let sensor = sensor.with_context(context, size);
+/// let pool = MaximiseEachCounterPool::new("max_each_cov_hits_in_context", size);
+/// ```
+pub struct ContextSensor<S, C>
+where
+    S: Sensor,
+    C: Sensor,
+{
+    sensor: S,
+    context: C,
+    size: usize,
+    hashed: AHashMap<usize, u64>,
+}
+
+impl<S, C> ContextSensor<S, C>
+where
+    S: Sensor,
+    C: Sensor,
+{
+    /// Create a new `ContextSensor`
+    ///
+    /// ## Arguments
+    /// - `sensor` is the wrapped sensor
+    /// - `context` is the sensor whose observations describe the context of the test case
+    /// - `size` is the number of counters of the new sensor. It must be greater than 0.
+    #[no_coverage]
+    pub fn new(sensor: S, context: C, size: usize) -> Self {
+        assert!(size > 0, "a ContextSensor must have at least one counter");
+        Self {
+            sensor,
+            context,
+            size,
+            hashed: AHashMap::new(),
+        }
+    }
+    /// The number of counters of the sensor, which is the `size` argument given to
+    /// [`ContextSensor::new`]
+    #[no_coverage]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+impl<S, C> SaveToStatsFolder for ContextSensor<S, C>
+where
+    S: Sensor,
+    C: Sensor,
+{
+    #[no_coverage]
+    fn save_to_stats_folder(&self) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+        let mut x = self.sensor.save_to_stats_folder();
+        x.extend(self.context.save_to_stats_folder());
+        x
+    }
+}
+impl<S, C> Sensor for ContextSensor<S, C>
+where
+    S: Sensor,
+    C: Sensor,
+    for<'a> &'a S::Observations: IntoIterator<Item = &'a (usize, u64)>,
+    C::Observations: Hash,
+{
+    type Observations = Vec<(usize, u64)>;
+
+    #[no_coverage]
+    fn start_recording(&mut self) {
+        self.context.start_recording();
+        self.sensor.start_recording();
+    }
+
+    #[no_coverage]
+    fn stop_recording(&mut self) {
+        self.sensor.stop_recording();
+        self.context.stop_recording();
+    }
+
+    #[no_coverage]
+    fn get_observations(&mut self) -> Self::Observations {
+        let context = self.context.get_observations();
+        let mut context_hasher = AHasher::default();
+        context.hash(&mut context_hasher);
+        let context = context_hasher.finish();
+
+        let observations = self.sensor.get_observations();
+        for &(index, count) in observations.into_iter() {
+            let mut hasher = AHasher::default();
+            (index, context).hash(&mut hasher);
+            let index = (hasher.finish() % self.size as u64) as usize;
+            insert_hashed_observation(&mut self.hashed, index, count);
+        }
+        collect_hashed_observations(&mut self.hashed)
+    }
+}
+impl<S, C> WrapperSensor for ContextSensor<S, C>
+where
+    S: Sensor,
+    C: Sensor,
+    for<'a> &'a S::Observations: IntoIterator<Item = &'a (usize, u64)>,
+    C::Observations: Hash,
+{
+    type Wrapped = S;
+    #[no_coverage]
+    fn wrapped(&self) -> &S {
+        &self.sensor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ContextSensor, NGramSensor};
+    use crate::sensors_and_pools::SimplestToActivateCounterPool;
+    use crate::traits::CompatibleWithObservations;
+    use crate::{PoolStorageIndex, SaveToStatsFolder, Sensor};
+
+    struct MockSensor<T: Clone + 'static> {
+        observations: T,
+    }
+    impl<T: Clone + 'static> SaveToStatsFolder for MockSensor<T> {
+        fn save_to_stats_folder(&self) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+            vec![]
+        }
+    }
+    impl<T: Clone + 'static> Sensor for MockSensor<T> {
+        type Observations = T;
+        fn start_recording(&mut self) {}
+        fn stop_recording(&mut self) {}
+        fn get_observations(&mut self) -> T {
+            self.observations.clone()
+        }
+    }
+
+    #[test]
+    fn test_ngram_sensor() {
+        let mut sensor = NGramSensor::new(MockSensor { observations: vec![(1, 2), (3, 1)] }, 2, 64);
+        let a = sensor.get_observations();
+        assert_eq!(a.len(), 1);
+        assert_eq!(a[0].1, 1);
+
+        sensor.sensor.observations = vec![(1, 2), (4, 5)];
+        let b = sensor.get_observations();
+        assert_eq!(b.len(), 1);
+        assert_eq!(b[0].1, 2);
+
+        sensor.sensor.observations = vec![(1, 2), (3, 1), (4, 5)];
+        let c = sensor.get_observations();
+        assert!(!c.is_empty() && c.len() <= 2);
+        assert!(c.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(c.iter().all(|&(index, _)| index < 64));
+
+        sensor.sensor.observations = vec![];
+        assert!(sensor.get_observations().is_empty());
+
+        let mut pool = SimplestToActivateCounterPool::new("ngrams", sensor.size());
+        sensor.sensor.observations = vec![(1, 2), (3, 1), (4, 5), (7, 1), (8, 9)];
+        let observations = sensor.get_observations();
+        let deltas = pool.process(PoolStorageIndex::mock(0), &observations, 1.0);
+        assert!(!deltas.is_empty());
+    }
+
+    #[test]
+    fn test_context_sensor() {
+        let mut sensor = ContextSensor::new(
+            MockSensor {
+                observations: vec![(0, 1), (1, 7), (2, 3)],
+            },
+            MockSensor { observations: 0u64 },
+            1024,
+        );
+        let a = sensor.get_observations();
+        assert!(a.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(a.iter().all(|&(index, _)| index < 1024));
+        assert_eq!(a, sensor.get_observations());
+
+        sensor.context.observations = 1;
+        let b = sensor.get_observations();
+        assert_ne!(a, b);
+
+        let mut pool = SimplestToActivateCounterPool::new("context", sensor.size());
+        assert!(!pool.process(PoolStorageIndex::mock(0), &a, 1.0).is_empty());
+        assert!(!pool.process(PoolStorageIndex::mock(1), &b, 1.0).is_empty());
+    }
+}
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 
 use nu_ansi_term::Color;
 
+use crate::sanitizer_report::SanitizerReport;
 use crate::traits::{CompatibleWithObservations, CorpusDelta, Pool, SaveToStatsFolder, Sensor, Stats};
 use crate::{CSVField, PoolStorageIndex, ToCSV};
 
@@ -13,10 +14,13 @@ pub(crate) static mut TEST_FAILURE: Option<TestFailure> = None;
 /// A type describing a test failure.
 ///
 /// It is uniquely identifiable through `self.id` and displayable through `self.display`.
+/// If the failure was detected by a sanitizer, `self.sanitizer_report` contains the kind of
+/// error and the top frame of the stack trace given in the sanitizer’s report.
 #[derive(Debug, Clone)]
 pub struct TestFailure {
     pub display: String,
     pub id: u64,
+    pub sanitizer_report: Option<SanitizerReport>,
 }
 
 /// A sensor that records test failures.
@@ -50,6 +50,14 @@ where
 }
 
 /// A pool that stores an input for each different value of each sensor counter
+///
+/// It is [compatible with](crate::CompatibleWithObservations) any sensor whose
+/// [observations](crate::Sensor::Observations) are given by an iterator of `(usize, T)`, where
+/// the first element is the index of the counter and must be smaller than the `size` given to
+/// [`UniqueValuesPool::new`].
+///
+/// Paired with a [`CountBucketsSensor`](crate::sensors_and_pools::CountBucketsSensor), it keeps the
+/// simplest test case reaching each bucket of each counter.
 pub struct UniqueValuesPool<T>
 where
     T: Hash + Eq + Clone,
@@ -188,7 +196,7 @@ where
         let cplx = complexity;
         let input = input_id;
         let input = Input {
-            best_for_values: AHashSet::new(), // fill in later! with new_observations.into_iter().collect(),
+            best_for_values: new_observations.iter().copied().collect(),
             data: input,
             score,
             number_times_chosen: 1,
@@ -215,21 +223,16 @@ where
                 self.best_input_for_value[*counter].insert(*id, input_key);
             }
         }
+        let mut removed_idxs = vec![];
         for &removed_key in &removed_keys {
+            removed_idxs.push(self.inputs[removed_key].data);
             self.inputs.remove(removed_key);
         }
-        let removed_keys = removed_keys
-            .into_iter()
-            .map(
-                #[no_coverage]
-                |k| self.inputs[k].data,
-            )
-            .collect();
         self.update_stats();
         vec![CorpusDelta {
             path: Path::new(&self.name).to_path_buf(),
             add: true,
-            remove: removed_keys,
+            remove: removed_idxs,
         }]
     }
 }
@@ -0,0 +1,130 @@
+use std::marker::PhantomData;
+
+/// The first bytes of every file written by the [`BincodeSerializer`]
+const MAGIC: &[u8; 4] = b"FZCB";
+/// The version of the encoding, incremented whenever the header or the bincode configuration changes
+const FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 8;
+
+/**
+A serializer that uses `serde` and `bincode` to serialize the test inputs
+(of arbitrary type `T: Serializable + for<'e> Deserializable<'e>`) to a compact
+binary file.
+
+It is a good replacement for the [`SerdeSerializer`](crate::SerdeSerializer) when the
+test inputs are large, because the corpus files are smaller and faster to decode.
+However, they are not human-readable.
+
+Each file starts with a header made of:
+1. the magic bytes `FZCB`
+2. the version of the format, as one byte
+3. a fingerprint of the tag given to [`BincodeSerializer::new`], as 8 little-endian bytes
+
+Files whose header does not match the current format version and tag are rejected.
+The tag describes the encoding of the serialized type, and should be changed whenever that
+encoding changes, such that a corpus is not silently decoded into different values after the
+fuzz test was changed. The tag is chosen by the user instead of being derived from the type,
+so that renaming or moving the type, or updating the compiler, does not invalidate the corpus.
+*/
+#[doc(cfg(feature = "bincode_serializer"))]
+pub struct BincodeSerializer<S> {
+    tag: &'static str,
+    phantom: PhantomData<S>,
+}
+
+impl<S> BincodeSerializer<S> {
+    /// Create a bincode serializer writing the given tag in the header of each file. For example:
+    /// ```
+    /// use fuzzcheck::BincodeSerializer;
+    ///
+    /// // increment the version when the encoding of the serialized type changes
+    /// let ser = BincodeSerializer::<Vec<(u8, String)>>::new("packets-v1");
+    /// ```
+    #[no_coverage]
+    pub fn new(tag: &'static str) -> Self {
+        Self {
+            tag,
+            phantom: PhantomData,
+        }
+    }
+
+    /// A fingerprint of the tag, computed with the FNV-1a hash function.
+    ///
+    /// Unlike the hasher of the standard library, FNV-1a is fully specified, so the fingerprint
+    /// only depends on the tag.
+    #[no_coverage]
+    fn tag_fingerprint(&self) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+        for b in self.tag.bytes() {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash
+    }
+}
+
+impl<S> crate::traits::Serializer for BincodeSerializer<S>
+where
+    S: serde::Serialize + for<'e> serde::Deserialize<'e>,
+{
+    type Value = S;
+
+    #[no_coverage]
+    fn extension(&self) -> &str {
+        "bin"
+    }
+    #[no_coverage]
+    fn from_data(&self, data: &[u8]) -> Option<S> {
+        if data.len() < HEADER_LEN || &data[..MAGIC.len()] != MAGIC || data[MAGIC.len()] != FORMAT_VERSION {
+            return None;
+        }
+        let fingerprint = u64::from_le_bytes(data[MAGIC.len() + 1..HEADER_LEN].try_into().unwrap());
+        if fingerprint != self.tag_fingerprint() {
+            return None;
+        }
+        bincode::deserialize(&data[HEADER_LEN..]).ok()
+    }
+    #[no_coverage]
+    fn to_data(&self, value: &Self::Value) -> Vec<u8> {
+        let mut data = Vec::with_capacity(HEADER_LEN);
+        data.extend_from_slice(MAGIC);
+        data.push(FORMAT_VERSION);
+        data.extend_from_slice(&self.tag_fingerprint().to_le_bytes());
+        bincode::serialize_into(&mut data, value).unwrap();
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BincodeSerializer, FORMAT_VERSION, HEADER_LEN};
+    use crate::Serializer;
+
+    #[test]
+    fn test_bincode_serializer_round_trip() {
+        let serializer = BincodeSerializer::<Vec<(u8, String)>>::new("test");
+        let value = vec![(1, "hello".to_owned()), (255, "".to_owned())];
+        let data = serializer.to_data(&value);
+        assert_eq!(&data[..5], b"FZCB\x01");
+        assert_eq!(serializer.from_data(&data), Some(value));
+    }
+
+    #[test]
+    fn test_bincode_serializer_checks_header() {
+        let serializer = BincodeSerializer::<u32>::new("test-v1");
+        let data = serializer.to_data(&7);
+        assert_eq!(data.len(), HEADER_LEN + 4);
+        assert_eq!(&data[5..HEADER_LEN], &0x769c_79b6_2dd2_e4a1_u64.to_le_bytes());
+
+        // the header only depends on the tag, not on the name of the type
+        assert_eq!(BincodeSerializer::<i32>::new("test-v1").from_data(&data), Some(7));
+        assert_eq!(BincodeSerializer::<u32>::new("test-v2").from_data(&data), None);
+
+        let mut other_version = data.clone();
+        other_version[4] = FORMAT_VERSION + 1;
+        assert_eq!(serializer.from_data(&other_version), None);
+
+        assert_eq!(serializer.from_data(&data[..3]), None);
+        assert_eq!(serializer.from_data(&7u32.to_le_bytes()), None);
+    }
+}
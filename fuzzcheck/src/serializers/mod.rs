@@ -1,21 +1,29 @@
 //! Types implementing the [Serializer] trait.
 //!
-//! There are currently three implementations:
+//! There are currently four implementations:
 //!
 //! * SerdeSerializer uses the `serde` and `serde_json` crate to serialize
 //! the test inputs (of arbitrary Serializable type) to a `.json` file.
 //!
+//! * BincodeSerializer uses the `serde` and `bincode` crate to serialize
+//! the test inputs (of arbitrary Serializable type) to a compact `.bin` file.
+//! It is only available with the `bincode_serializer` feature.
+//!
 //! * [ByteSerializer] encodes and decodes values of type `Vec<u8>` by simply
 //! copy/pasting the bytes from/to the files. The extension is customizable.
 //!
 //! * [StringSerializer] encodes and decodes values of any type implementing
 //! `FromStr` and `ToString` into utf-8 encoded text files.
 
+#[cfg(feature = "bincode_serializer")]
+mod bincode_serializer;
 #[cfg(feature = "serde_json_serializer")]
 mod serde_serializer;
 use std::marker::PhantomData;
 use std::str::FromStr;
 
+#[cfg(feature = "bincode_serializer")]
+pub use bincode_serializer::BincodeSerializer;
 #[cfg(feature = "serde_json_serializer")]
 pub use serde_serializer::SerdeSerializer;
 
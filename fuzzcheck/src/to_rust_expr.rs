@@ -0,0 +1,305 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::{Range, RangeInclusive};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/**
+A type whose values can be written as Rust expressions.
+
+It is used by `cargo fuzzcheck --command reproduce --emit-test` to write a regression test
+that constructs a failing test case. The returned expression must evaluate to a value
+equal to `self` when it is compiled in a module where the type is accessible.
+
+It can be derived for structs and enums whose fields implement `ToRustExpr`:
+```
+# #![feature(no_coverage)]
+use fuzzcheck::ToRustExpr;
+
+#[derive(ToRustExpr)]
+enum Expr {
+    Lit(u8),
+    Add { lhs: Box<Expr>, rhs: Box<Expr> },
+}
+let expr = Expr::Add { lhs: Box::new(Expr::Lit(1)), rhs: Box::new(Expr::Lit(2)) };
+assert_eq!(
+    expr.to_rust_expr(),
+    "rust_out::Expr::Add { lhs: Box::new(rust_out::Expr::Lit(1u8)), rhs: Box::new(rust_out::Expr::Lit(2u8)) }"
+);
+```
+The derived implementation refers to the type through the path of the module where
+it is defined, so it does not work for types whose fields are private or which are only
+accessible through a re-export. Implement the trait manually for these types instead.
+*/
+pub trait ToRustExpr {
+    fn to_rust_expr(&self) -> String;
+}
+
+impl ToRustExpr for () {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        "()".to_owned()
+    }
+}
+
+impl ToRustExpr for bool {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ToRustExpr for char {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl ToRustExpr for String {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("{:?}.to_owned()", self)
+    }
+}
+
+macro_rules! impl_to_rust_expr_for_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl ToRustExpr for $ty {
+                #[no_coverage]
+                fn to_rust_expr(&self) -> String {
+                    format!("{}{}", self, stringify!($ty))
+                }
+            }
+        )*
+    };
+}
+impl_to_rust_expr_for_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_to_rust_expr_for_float {
+    ($($ty:ident),*) => {
+        $(
+            impl ToRustExpr for $ty {
+                #[no_coverage]
+                fn to_rust_expr(&self) -> String {
+                    if self.is_nan() {
+                        concat!(stringify!($ty), "::NAN").to_owned()
+                    } else if *self == $ty::INFINITY {
+                        concat!(stringify!($ty), "::INFINITY").to_owned()
+                    } else if *self == $ty::NEG_INFINITY {
+                        concat!(stringify!($ty), "::NEG_INFINITY").to_owned()
+                    } else {
+                        // the Debug representation is the shortest one that round-trips, and contains a `.` or an exponent
+                        format!("{:?}{}", self, stringify!($ty))
+                    }
+                }
+            }
+        )*
+    };
+}
+impl_to_rust_expr_for_float!(f32, f64);
+
+#[no_coverage]
+fn join<'a, T: ToRustExpr + 'a>(values: impl IntoIterator<Item = &'a T>) -> String {
+    values
+        .into_iter()
+        .map(
+            #[no_coverage]
+            |x| x.to_rust_expr(),
+        )
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl<T: ToRustExpr> ToRustExpr for Option<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        match self {
+            Some(x) => format!("Some({})", x.to_rust_expr()),
+            None => "None".to_owned(),
+        }
+    }
+}
+
+impl<T: ToRustExpr, E: ToRustExpr> ToRustExpr for Result<T, E> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        match self {
+            Ok(x) => format!("Ok({})", x.to_rust_expr()),
+            Err(e) => format!("Err({})", e.to_rust_expr()),
+        }
+    }
+}
+
+impl<T: ToRustExpr> ToRustExpr for Box<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("Box::new({})", self.as_ref().to_rust_expr())
+    }
+}
+
+impl<T: ToRustExpr> ToRustExpr for Rc<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("std::rc::Rc::new({})", self.as_ref().to_rust_expr())
+    }
+}
+
+impl<T: ToRustExpr> ToRustExpr for Arc<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("std::sync::Arc::new({})", self.as_ref().to_rust_expr())
+    }
+}
+
+impl<T> ToRustExpr for PhantomData<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        "std::marker::PhantomData".to_owned()
+    }
+}
+
+impl<T: ToRustExpr> ToRustExpr for Vec<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("vec![{}]", join(self))
+    }
+}
+
+impl<T: ToRustExpr, const N: usize> ToRustExpr for [T; N] {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("[{}]", join(self))
+    }
+}
+
+impl<T: ToRustExpr> ToRustExpr for VecDeque<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("std::collections::VecDeque::from(vec![{}])", join(self))
+    }
+}
+
+impl<T: ToRustExpr> ToRustExpr for BTreeSet<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("std::collections::BTreeSet::from([{}])", join(self))
+    }
+}
+
+impl<T: ToRustExpr + Hash + Eq> ToRustExpr for HashSet<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("std::collections::HashSet::from([{}])", join(self))
+    }
+}
+
+impl<K: ToRustExpr, V: ToRustExpr> ToRustExpr for BTreeMap<K, V> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        let entries = self
+            .iter()
+            .map(
+                #[no_coverage]
+                |(k, v)| format!("({}, {})", k.to_rust_expr(), v.to_rust_expr()),
+            )
+            .collect::<Vec<_>>();
+        format!("std::collections::BTreeMap::from([{}])", entries.join(", "))
+    }
+}
+
+impl<K: ToRustExpr + Hash + Eq, V: ToRustExpr> ToRustExpr for HashMap<K, V> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        let entries = self
+            .iter()
+            .map(
+                #[no_coverage]
+                |(k, v)| format!("({}, {})", k.to_rust_expr(), v.to_rust_expr()),
+            )
+            .collect::<Vec<_>>();
+        format!("std::collections::HashMap::from([{}])", entries.join(", "))
+    }
+}
+
+impl<T: ToRustExpr> ToRustExpr for Range<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("({}..{})", self.start.to_rust_expr(), self.end.to_rust_expr())
+    }
+}
+
+impl<T: ToRustExpr> ToRustExpr for RangeInclusive<T> {
+    #[no_coverage]
+    fn to_rust_expr(&self) -> String {
+        format!("({}..={})", self.start().to_rust_expr(), self.end().to_rust_expr())
+    }
+}
+
+macro_rules! impl_to_rust_expr_for_tuple {
+    ($($T:ident $idx:tt),+) => {
+        impl<$($T: ToRustExpr),+> ToRustExpr for ($($T,)+) {
+            #[no_coverage]
+            fn to_rust_expr(&self) -> String {
+                let elements = [$(self.$idx.to_rust_expr()),+];
+                if elements.len() == 1 {
+                    format!("({},)", elements[0])
+                } else {
+                    format!("({})", elements.join(", "))
+                }
+            }
+        }
+    };
+}
+impl_to_rust_expr_for_tuple!(T0 0);
+impl_to_rust_expr_for_tuple!(T0 0, T1 1);
+impl_to_rust_expr_for_tuple!(T0 0, T1 1, T2 2);
+impl_to_rust_expr_for_tuple!(T0 0, T1 1, T2 2, T3 3);
+impl_to_rust_expr_for_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4);
+impl_to_rust_expr_for_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+impl_to_rust_expr_for_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+impl_to_rust_expr_for_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+impl_to_rust_expr_for_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8);
+impl_to_rust_expr_for_tuple!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, VecDeque};
+
+    use super::ToRustExpr;
+
+    #[test]
+    fn test_primitive_exprs() {
+        assert_eq!(true.to_rust_expr(), "true");
+        assert_eq!(12u8.to_rust_expr(), "12u8");
+        assert_eq!(i8::MIN.to_rust_expr(), "-128i8");
+        assert_eq!(1.5f32.to_rust_expr(), "1.5f32");
+        assert_eq!(2.0f64.to_rust_expr(), "2.0f64");
+        assert_eq!(1e-10f64.to_rust_expr(), "1e-10f64");
+        assert_eq!(f64::NAN.to_rust_expr(), "f64::NAN");
+        assert_eq!(f32::NEG_INFINITY.to_rust_expr(), "f32::NEG_INFINITY");
+        assert_eq!('\''.to_rust_expr(), r"'\''");
+        assert_eq!("a\"b\n".to_owned().to_rust_expr(), r#""a\"b\n".to_owned()"#);
+    }
+
+    #[test]
+    fn test_compound_exprs() {
+        assert_eq!(vec![Some(1u16), None].to_rust_expr(), "vec![Some(1u16), None]");
+        assert_eq!((7u8,).to_rust_expr(), "(7u8,)");
+        assert_eq!((7u8, false).to_rust_expr(), "(7u8, false)");
+        assert_eq!(
+            [Box::new(()), Box::new(())].to_rust_expr(),
+            "[Box::new(()), Box::new(())]"
+        );
+        assert_eq!(
+            VecDeque::from(vec![0..2usize, 3..3]).to_rust_expr(),
+            "std::collections::VecDeque::from(vec![(0usize..2usize), (3usize..3usize)])"
+        );
+        assert_eq!(
+            BTreeMap::from([(1u8, 'a')]).to_rust_expr(),
+            "std::collections::BTreeMap::from([(1u8, 'a')])"
+        );
+    }
+}
@@ -189,8 +189,19 @@ This should never happen, and is probably a bug in fuzzcheck. Sorry :("#
                 println!("{}", Color::Yellow.paint("DONE"));
                 return;
             }
-            FuzzerEvent::DidReadCorpus => {
+            FuzzerEvent::DidReadCorpus {
+                nbr_unparsable,
+                nbr_invalid,
+            } => {
                 println!("{}", Color::Yellow.paint("FINISHED READING CORPUS"));
+                if nbr_unparsable + nbr_invalid > 0 {
+                    println!(
+                        "{} files of the input corpus were ignored: {} could not be deserialized, {} were rejected by the mutator",
+                        nbr_unparsable + nbr_invalid,
+                        nbr_unparsable,
+                        nbr_invalid
+                    );
+                }
                 return;
             }
             FuzzerEvent::CaughtSignal(signal) => println!("\n================ SIGNAL {} ================", signal),
@@ -239,7 +250,7 @@ This should never happen, and is probably a bug in fuzzcheck. Sorry :("#
     }
 
     #[no_coverage]
-    pub fn read_input_corpus(&self) -> Result<Vec<Vec<u8>>> {
+    pub fn read_input_corpus(&self) -> Result<Vec<(PathBuf, Vec<u8>)>> {
         if self.settings.corpus_in.is_none() {
             return Result::Ok(vec![]);
         }
@@ -249,7 +260,7 @@ This should never happen, and is probably a bug in fuzzcheck. Sorry :("#
         Ok(values)
     }
     #[no_coverage]
-    fn read_input_corpus_rec(&self, corpus: &Path, values: &mut Vec<Vec<u8>>) -> Result<()> {
+    fn read_input_corpus_rec(&self, corpus: &Path, values: &mut Vec<(PathBuf, Vec<u8>)>) -> Result<()> {
         if !corpus.exists() {
             return Ok(());
         }
@@ -265,13 +276,55 @@ This should never happen, and is probably a bug in fuzzcheck. Sorry :("#
             if path.is_dir() {
                 self.read_input_corpus_rec(&path, values)?;
             } else {
-                let data = fs::read(path)?;
-                values.push(data);
+                let data = fs::read(&path)?;
+                values.push((path, data));
             }
         }
         Ok(())
     }
 
+    /// Move a file of the input corpus to the given folder, so that it is not read again
+    ///
+    /// The file keeps its path relative to the input corpus folder, such that files with the same name in
+    /// different subfolders of the corpus do not overwrite each other. An existing file is never overwritten.
+    ///
+    /// Returns the new path of the file.
+    #[no_coverage]
+    pub fn quarantine_input(&self, file: &Path, folder: &Path) -> Result<PathBuf> {
+        let relative_path = self
+            .settings
+            .corpus_in
+            .as_ref()
+            .and_then(
+                #[no_coverage]
+                |corpus| file.strip_prefix(corpus).ok(),
+            )
+            .or_else(
+                #[no_coverage]
+                || file.file_name().map(Path::new),
+            )
+            .ok_or_else(
+                #[no_coverage]
+                || io::Error::new(io::ErrorKind::InvalidInput, "The input path has no file name."),
+            )?;
+        let destination = folder.join(relative_path);
+        if destination.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("The file {} already exists.", destination.display()),
+            ));
+        }
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if fs::rename(file, &destination).is_err() {
+            // the folder may be on a different file system
+            fs::copy(file, &destination)?;
+            fs::remove_file(file)?;
+        }
+        Ok(destination)
+    }
+
     #[no_coverage]
     pub fn read_input_file(&self, file: &Path) -> Result<Vec<u8>> {
         let data = fs::read(file)?;
@@ -280,12 +333,47 @@ This should never happen, and is probably a bug in fuzzcheck. Sorry :("#
 
     #[no_coverage]
     pub fn save_artifact(&mut self, content: Vec<u8>, cplx: f64, extension: &str) -> Result<()> {
-        let artifacts_folder = self.settings.artifacts_folder.as_ref();
-        if artifacts_folder.is_none() {
-            return Ok(());
+        let artifacts_folder = self.settings.artifacts_folder.clone();
+        if let Some(artifacts_folder) = artifacts_folder {
+            self.save_artifact_in_folder(&artifacts_folder, content, cplx, extension)
+        } else {
+            Ok(())
         }
-        let artifacts_folder = artifacts_folder.unwrap().as_path();
+    }
 
+    /// Save an artifact in a subfolder of the artifacts folder named after the identifier of the test failure
+    ///
+    /// When minifying or reading an input, the artifact is saved at the root of the artifacts folder instead,
+    /// where `cargo-fuzzcheck` expects to find it.
+    #[no_coverage]
+    pub fn save_artifact_for_failure(
+        &mut self,
+        content: Vec<u8>,
+        cplx: f64,
+        extension: &str,
+        failure_id: u64,
+    ) -> Result<()> {
+        let artifacts_folder = self.settings.artifacts_folder.clone();
+        if let Some(artifacts_folder) = artifacts_folder {
+            let artifacts_folder = if let FuzzerCommand::Fuzz = self.settings.command {
+                artifacts_folder.join(format!("{:x}", failure_id))
+            } else {
+                artifacts_folder
+            };
+            self.save_artifact_in_folder(&artifacts_folder, content, cplx, extension)
+        } else {
+            Ok(())
+        }
+    }
+
+    #[no_coverage]
+    fn save_artifact_in_folder(
+        &mut self,
+        artifacts_folder: &Path,
+        content: Vec<u8>,
+        cplx: f64,
+        extension: &str,
+    ) -> Result<()> {
         if !artifacts_folder.is_dir() {
             std::fs::create_dir_all(artifacts_folder)?;
         }
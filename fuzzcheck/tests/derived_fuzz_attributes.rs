@@ -0,0 +1,84 @@
+#![cfg(feature = "regex_grammar")]
+#![allow(unused_attributes)]
+#![feature(no_coverage)]
+use fuzzcheck::mutators::testing_utilities::test_mutator;
+use fuzzcheck::{make_mutator, DefaultMutator, Mutator};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+struct Request {
+    #[fuzz(range = 1 ..= 3)]
+    version: u8,
+    #[fuzz(regex = "/[a-z]{1,8}(/[a-z]{1,8})*")]
+    path: String,
+    #[fuzz(len = 1..8)]
+    headers: Vec<u8>,
+    #[fuzz(value = true)]
+    keep_alive: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, DefaultMutator)]
+struct Point {
+    #[fuzz(range = -1.0 .. 1.0)]
+    x: f64,
+    #[fuzz(range = 'a' ..= 'z')]
+    label: char,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Shape {
+    Circle(u16),
+    Polygon { sides: Vec<(i8, i8)>, name: String },
+}
+make_mutator! {
+    name: ShapeMutator,
+    default: true,
+    type:
+        enum Shape {
+            Circle(#[fuzz(range = 1 ..= 10)] u16),
+            Polygon {
+                #[fuzz(len = 3..=6)]
+                sides: Vec<(i8, i8)>,
+                #[fuzz(len = ..=4)]
+                name: String
+            },
+        }
+}
+
+#[test]
+fn test_fuzz_attributes_struct() {
+    let mutator = Request::default_mutator();
+    for _ in 0..1000 {
+        let (x, _) = mutator.random_arbitrary(1000.0);
+        assert!((1..=3).contains(&x.version));
+        assert!(x.path.starts_with('/') && !x.path.ends_with('/'), "{}", x.path);
+        assert!((1..8).contains(&x.headers.len()));
+        assert!(x.keep_alive);
+    }
+    test_mutator(mutator, 1000., 1000., false, true, 50, 50);
+}
+
+#[test]
+fn test_fuzz_attributes_float_and_char() {
+    let mutator = Point::default_mutator();
+    for _ in 0..1000 {
+        let (x, _) = mutator.random_arbitrary(1000.0);
+        assert!((-1.0..1.0).contains(&x.x));
+        assert!(x.label.is_ascii_lowercase());
+    }
+}
+
+#[test]
+fn test_fuzz_attributes_enum() {
+    let mutator = Shape::default_mutator();
+    for _ in 0..1000 {
+        let (x, _) = mutator.random_arbitrary(1000.0);
+        match x {
+            Shape::Circle(r) => assert!((1..=10).contains(&r)),
+            Shape::Polygon { sides, name } => {
+                assert!((3..=6).contains(&sides.len()));
+                assert!(name.chars().count() <= 4);
+            }
+        }
+    }
+    test_mutator(mutator, 1000., 1000., false, true, 50, 50);
+}
@@ -0,0 +1,115 @@
+#![allow(unused_attributes)]
+#![feature(no_coverage)]
+
+use fuzzcheck::mutators::testing_utilities::test_mutator;
+use fuzzcheck::{DefaultMutator, Mutator};
+
+fn repair_span(span: &mut Span) {
+    if span.start > span.end {
+        std::mem::swap(&mut span.start, &mut span.end);
+    }
+    span.len = span.items.len();
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+#[fuzz(invariant = repair_span)]
+struct Span {
+    start: u16,
+    end: u16,
+    len: usize,
+    items: Vec<u8>,
+}
+
+fn repair_shape(shape: &mut Shape) {
+    if let Shape::Rectangle { width, height } = shape {
+        if width < height {
+            std::mem::swap(width, height);
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+#[fuzz(invariant = repair_shape)]
+enum Shape {
+    Square(u8),
+    Rectangle { width: u8, height: u8 },
+}
+
+fn repair_direction(direction: &mut Direction) {
+    if *direction == Direction::Up {
+        *direction = Direction::Down;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+#[fuzz(invariant = repair_direction)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[test]
+fn test_derived_struct_with_invariant() {
+    let mutator = Span::default_mutator();
+    for _ in 0..1000 {
+        let (x, _) = mutator.random_arbitrary(1000.);
+        assert!(x.start <= x.end);
+        assert_eq!(x.len, x.items.len());
+    }
+    let (mut x, _) = mutator.random_arbitrary(1000.);
+    let mut cache = mutator.validate_value(&x).unwrap();
+    let mut step = mutator.default_mutation_step(&x, &cache);
+    for _ in 0..1000 {
+        let original = x.clone();
+        if let Some((token, cplx)) = mutator.ordered_mutate(
+            &mut x,
+            &mut cache,
+            &mut step,
+            &fuzzcheck::subvalue_provider::EmptySubValueProvider,
+            1000.,
+        ) {
+            assert!(x.start <= x.end);
+            assert_eq!(x.len, x.items.len());
+            assert_eq!(cplx, mutator.complexity(&x, &mutator.validate_value(&x).unwrap()));
+            mutator.unmutate(&mut x, &mut cache, token);
+            assert_eq!(x, original);
+        } else {
+            break;
+        }
+    }
+    // values violating the invariant, e.g. from the corpus, are rejected
+    let invalid = Span {
+        start: 2,
+        end: 1,
+        len: 0,
+        items: vec![],
+    };
+    assert!(mutator.validate_value(&invalid).is_none());
+    assert!(!mutator.is_valid(&invalid));
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+}
+
+#[test]
+fn test_derived_enum_with_invariant() {
+    let mutator = Shape::default_mutator();
+    for _ in 0..1000 {
+        let (x, _) = mutator.random_arbitrary(1000.);
+        if let Shape::Rectangle { width, height } = x {
+            assert!(width >= height);
+        }
+    }
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+}
+
+#[test]
+fn test_derived_basic_enum_with_invariant() {
+    let mutator = Direction::default_mutator();
+    for _ in 0..100 {
+        let (x, _) = mutator.random_arbitrary(10.);
+        assert_ne!(x, Direction::Up);
+    }
+    assert!(mutator.validate_value(&Direction::Up).is_none());
+    test_mutator(mutator, 10., 10., false, true, 100, 100);
+}
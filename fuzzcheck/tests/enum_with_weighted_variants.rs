@@ -0,0 +1,88 @@
+#![allow(unused_attributes)]
+#![feature(no_coverage)]
+
+use fuzzcheck::mutators::testing_utilities::test_mutator;
+use fuzzcheck::{DefaultMutator, Mutator};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+enum Command {
+    Read(u8),
+    Write(u8, bool),
+    #[fuzz(weight = 20)]
+    Shutdown {
+        force: bool,
+    },
+    #[ignore_variant]
+    Unused(u8),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+enum Level {
+    Low,
+    #[fuzz(weight = 0.1)]
+    Medium,
+    High,
+}
+
+#[test]
+fn test_derived_enum_with_weighted_variants() {
+    let mutator = Command::default_mutator();
+    let mut nbr_shutdowns = 0;
+    for _ in 0..1000 {
+        let (v, _) = mutator.random_arbitrary(1000.);
+        assert!(!matches!(v, Command::Unused(_)));
+        if matches!(v, Command::Shutdown { .. }) {
+            nbr_shutdowns += 1;
+        }
+    }
+    // the expected number of shutdowns is 1000 * 20 / 22
+    assert!(nbr_shutdowns > 800, "{nbr_shutdowns}");
+
+    // a random mutation rarely replaces the value with one of another variant, chosen according to the weights
+    let mut value = Command::Read(0);
+    let mut cache = mutator.validate_value(&value).unwrap();
+    let (mut nbr_writes, mut nbr_shutdowns) = (0, 0);
+    for _ in 0..100_000 {
+        let (token, _) = mutator.random_mutate(&mut value, &mut cache, 1000.);
+        match value {
+            Command::Read(_) => {}
+            Command::Write(..) => nbr_writes += 1,
+            Command::Shutdown { .. } => nbr_shutdowns += 1,
+            Command::Unused(_) => panic!(),
+        }
+        mutator.unmutate(&mut value, &mut cache, token);
+    }
+    // the expected number of shutdowns is 20 times the number of writes
+    assert!(nbr_shutdowns > 5 * nbr_writes, "{nbr_shutdowns} {nbr_writes}");
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+}
+
+#[test]
+fn test_derived_basic_enum_with_weighted_variants() {
+    let mutator = Level::default_mutator();
+    let mut nbr_medium = 0;
+    for _ in 0..1000 {
+        let (v, _) = mutator.random_arbitrary(1000.);
+        if v == Level::Medium {
+            nbr_medium += 1;
+        }
+    }
+    // the expected number of medium levels is 1000 * 0.1 / 2.1
+    assert!(nbr_medium < 150, "{nbr_medium}");
+
+    let mut value = Level::Low;
+    let mut cache = mutator.validate_value(&value).unwrap();
+    let (mut nbr_medium, mut nbr_high) = (0, 0);
+    for _ in 0..1000 {
+        let (token, _) = mutator.random_mutate(&mut value, &mut cache, 1000.);
+        match value {
+            Level::Low => {}
+            Level::Medium => nbr_medium += 1,
+            Level::High => nbr_high += 1,
+        }
+        mutator.unmutate(&mut value, &mut cache, token);
+    }
+    // the expected number of high levels is 10 times the number of medium levels
+    assert!(nbr_high > 3 * nbr_medium, "{nbr_high} {nbr_medium}");
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+}
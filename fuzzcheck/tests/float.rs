@@ -0,0 +1,24 @@
+#![allow(unused_attributes)]
+#![feature(no_coverage)]
+#![cfg(feature = "serde_json_serializer")]
+use fuzzcheck::sensors_and_pools::{MaximiseObservationPool, NoopSensor};
+use fuzzcheck::{Arguments, DefaultMutator, ReasonForStopping};
+
+#[derive(Clone, Debug, PartialEq, DefaultMutator, serde::Serialize, serde::Deserialize)]
+struct Measurement {
+    id: u8,
+    value: f64,
+    weights: Vec<f32>,
+}
+
+#[test]
+fn test_derived_struct_with_float_fields() {
+    let result = fuzzcheck::fuzz_test(|m: &Measurement| m.value.is_finite() && m.weights.iter().all(|w| *w < 1e30))
+        .default_mutator()
+        .serde_serializer()
+        .sensor_and_pool(NoopSensor, MaximiseObservationPool::<()>::new("noop"))
+        .arguments(Arguments::for_internal_documentation_test())
+        .stop_after_iterations(10_000)
+        .launch();
+    assert!(matches!(result.reason_for_stopping, ReasonForStopping::TestFailure(_)));
+}
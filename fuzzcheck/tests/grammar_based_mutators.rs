@@ -6,7 +6,8 @@ use std::rc::{Rc, Weak};
 
 use fuzzcheck::mutators::grammar::*;
 use fuzzcheck::mutators::testing_utilities::test_mutator;
-// use fuzzcheck::{DefaultMutator, Mutator};
+use fuzzcheck::subvalue_provider::{CrossoverSubValueProvider, Generation, SubValueProviderId};
+use fuzzcheck::{Mutator, Serializer};
 
 #[no_coverage]
 fn text() -> Rc<Grammar> {
@@ -182,3 +183,180 @@ fn test_grammar_based_ast_mutator() {
     let mutator = grammar_based_ast_mutator(markdown());
     test_mutator(mutator, 500., 500., false, true, 60, 100);
 }
+
+#[test]
+fn test_grammar_parser_roundtrip() {
+    let mutator = grammar_based_ast_mutator(markdown());
+    let parser = GrammarParser::new(markdown());
+    for _ in 0..100 {
+        let (ast, _) = mutator.random_arbitrary(100.0);
+        let string = ast.to_string();
+        let parsed = parser.parse(&string).unwrap();
+        assert_eq!(parsed.to_string(), string);
+        assert!(mutator.validate_value(&parsed).is_some(), "{:?}", parsed);
+    }
+}
+
+#[test]
+fn test_grammar_parser_long_repetition_with_upper_bound() {
+    // each item matches one or two characters, and the bound is too large for the items to be chained
+    let grammar = concatenation([
+        repetition(
+            alternation([literal('a'), concatenation([literal('a'), literal('a')])]),
+            0..=40,
+        ),
+        literal('b'),
+    ]);
+    let mutator = grammar_based_ast_mutator(grammar.clone());
+    let parser = GrammarParser::new(grammar);
+    for nbr_a in [0, 1, 40, 79, 80] {
+        let string = format!("{}b", "a".repeat(nbr_a));
+        let parsed = parser.parse(&string).unwrap();
+        assert_eq!(parsed.to_string(), string);
+        assert!(mutator.validate_value(&parsed).is_some(), "{}", string);
+    }
+    assert!(parser.parse(&format!("{}b", "a".repeat(81))).is_none());
+}
+
+#[test]
+fn test_grammar_based_string_mutator() {
+    let grammar = || concatenation([rule("year", repetition(literal_range('0'..='9'), 4..5)), literal('-')]);
+    let mutator = grammar_based_string_mutator(grammar());
+    let mut value = "2021-".to_owned();
+    let mut cache = mutator.validate_value(&value).unwrap();
+    for _ in 0..100 {
+        let (token, _) = mutator.random_mutate(&mut value, &mut cache, 100.0);
+        assert!(mutator.validate_value(&value).is_some(), "{}", value);
+        mutator.unmutate(&mut value, &mut cache, token);
+        assert_eq!(value, "2021-");
+    }
+    assert!(mutator.validate_value(&"20211-".to_owned()).is_none());
+
+    let serializer = GrammarSerializer::new(grammar(), "txt");
+    let (string, ast) = serializer.from_data(b"1999-").unwrap();
+    assert_eq!(string, ast.to_string());
+    assert_eq!(serializer.to_data(&(string, ast)), b"1999-");
+    assert!(serializer.from_data(b"1999").is_none());
+}
+
+#[test]
+fn test_regex_anchors_word_boundaries_and_errors() {
+    let mutator = grammar_based_ast_mutator(regex(r"^\bab?\b$|")).with_string();
+    for _ in 0..100 {
+        let ((string, _), _) = mutator.random_arbitrary(10.0);
+        assert!(["", "a", "ab"].contains(&string.as_str()), "{}", string);
+    }
+    let error = try_regex("a(b").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 2));
+    assert!(try_regex(r"\p{NotAProperty}").is_err());
+}
+
+#[test]
+fn test_regex_matching_only_the_empty_string() {
+    for regex_str in ["", "^", "^$", r"\b", "()"] {
+        let mutator = grammar_based_ast_mutator(regex(regex_str)).with_string();
+        for _ in 0..10 {
+            let ((string, _), _) = mutator.random_arbitrary(10.0);
+            assert_eq!(string, "", "{}", regex_str);
+        }
+    }
+}
+
+#[test]
+fn test_bytes_regex() {
+    let mutator = grammar_based_ast_mutator(bytes_regex(r"\xFF[\x00-\x01](?u:é)")).with_bytes();
+    for _ in 0..100 {
+        let ((bytes, _), _) = mutator.random_arbitrary(10.0);
+        assert_eq!(bytes.len(), 4);
+        assert_eq!(bytes[0], 0xFF);
+        assert!(bytes[1] <= 1);
+        assert_eq!(&bytes[2..], "é".as_bytes());
+    }
+    assert!(try_bytes_regex("(?u:[é-ê]").is_err());
+}
+
+#[test]
+fn test_weighted_alternation_and_length_distribution() {
+    let mutator = grammar_based_ast_mutator(weighted_alternation([(literal('a'), 9.0), (literal('b'), 1.0)]));
+    let nbr_a = (0..1000)
+        .filter(|_| mutator.random_arbitrary(10.0).0.to_string() == "a")
+        .count();
+    assert!(nbr_a > 750, "{}", nbr_a);
+
+    let mutator = grammar_based_ast_mutator(repetition_with_distribution(
+        literal('a'),
+        0..,
+        LengthDistribution::Geometric(0.5),
+    ));
+    let total_len = (0..1000)
+        .map(|_| mutator.random_arbitrary(1000.0).0.to_string().len())
+        .sum::<usize>();
+    assert!(total_len < 3000, "{}", total_len);
+
+    let mutator = grammar_based_ast_mutator(repetition_with_distribution(
+        literal('a'),
+        0..=5,
+        LengthDistribution::Weights(vec![0.0, 0.0, 1.0]),
+    ));
+    let nbr_len_2 = (0..1000)
+        .filter(|_| mutator.random_arbitrary(100.0).0.to_string() == "aa")
+        .count();
+    assert!(nbr_len_2 > 900, "{}", nbr_len_2);
+}
+
+#[no_coverage]
+fn date_and_pin(year: &str, pin: &str) -> AST {
+    let digits = |s: &str| AST::Sequence(s.chars().map(AST::Token).collect());
+    AST::Sequence(vec![
+        AST::Rule {
+            rule: "year".to_owned(),
+            ast: Box::new(digits(year)),
+        },
+        AST::Token('-'),
+        AST::Rule {
+            rule: "pin".to_owned(),
+            ast: Box::new(digits(pin)),
+        },
+    ])
+}
+
+#[test]
+fn test_crossover_only_splices_subtrees_of_the_same_rule() {
+    let digits = || repetition(literal_range('0'..='9'), 8..9);
+    let mutator = grammar_based_ast_mutator(concatenation([
+        rule("year", digits()),
+        literal('-'),
+        rule("pin", digits()),
+    ]));
+
+    let other = date_and_pin("11111111", "22222222");
+    let other_cache = mutator.validate_value(&other).unwrap();
+    let provider = CrossoverSubValueProvider::new(
+        SubValueProviderId {
+            idx: 0,
+            generation: Generation(0),
+        },
+        &other,
+        &other_cache,
+        &mutator,
+    );
+
+    let mut value = date_and_pin("55555555", "66666666");
+    let mut cache = mutator.validate_value(&value).unwrap();
+    let mut step = mutator.default_mutation_step(&value, &cache);
+    let mut nbr_splices = 0;
+    for _ in 0..2000 {
+        let (token, _) = match mutator.ordered_mutate(&mut value, &mut cache, &mut step, &provider, 1000.0) {
+            Some(x) => x,
+            None => break,
+        };
+        let s = value.to_string();
+        let (year, pin) = s.split_once('-').unwrap();
+        assert!(year != "22222222" && pin != "11111111", "{}", s);
+        if year == "11111111" || pin == "22222222" {
+            nbr_splices += 1;
+        }
+        mutator.unmutate(&mut value, &mut cache, token);
+    }
+    assert!(nbr_splices > 0);
+}
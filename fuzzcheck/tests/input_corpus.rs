@@ -0,0 +1,82 @@
+#![cfg(feature = "serde_json_serializer")]
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use fuzzcheck::mutators::integer_within_range::U8WithinRangeMutator;
+use fuzzcheck::sensors_and_pools::{MaximiseObservationPool, NoopSensor};
+use fuzzcheck::Arguments;
+
+/// The tests of this file launch the fuzzer, which cannot run twice at the same time in the same process
+static FUZZER_LOCK: Mutex<()> = Mutex::new(());
+
+/// A temporary folder, removed when it is dropped, even if the test panics
+struct TempFolder(PathBuf);
+
+impl Drop for TempFolder {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Create an input corpus with one valid file, one file rejected by the mutator, and one
+/// file that cannot be deserialized
+fn make_corpus(name: &str) -> TempFolder {
+    let folder = std::env::temp_dir().join(format!("fuzzcheck-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&folder);
+    let corpus = folder.join("in");
+    fs::create_dir_all(&corpus).unwrap();
+    fs::write(corpus.join("valid.json"), "50").unwrap();
+    fs::write(corpus.join("out_of_range.json"), "200").unwrap();
+    fs::write(corpus.join("unparsable.json"), "[1, 2").unwrap();
+    TempFolder(folder)
+}
+
+fn fuzz_corpus(args: Arguments) {
+    let _guard = FUZZER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = fuzzcheck::fuzz_test(|_: &u8| true)
+        .mutator(U8WithinRangeMutator::new(0..=100))
+        .serde_serializer()
+        .sensor_and_pool(NoopSensor, MaximiseObservationPool::<()>::new("noop"))
+        .arguments(args)
+        .stop_after_iterations(10)
+        .launch();
+}
+
+#[test]
+fn test_quarantine_invalid_corpus_files() {
+    let temp_folder = make_corpus("quarantine");
+    let folder = &temp_folder.0;
+    // an invalid file with the same name as another one, in a subfolder
+    fs::create_dir_all(folder.join("in/nested")).unwrap();
+    fs::write(folder.join("in/nested/out_of_range.json"), "150").unwrap();
+    let mut args = Arguments::for_internal_documentation_test();
+    args.corpus_in = Some(folder.join("in"));
+    args.quarantine_invalid = Some(folder.join("invalid"));
+    fuzz_corpus(args);
+
+    assert!(folder.join("in/valid.json").is_file());
+    assert!(!folder.join("in/out_of_range.json").exists());
+    assert!(!folder.join("in/unparsable.json").exists());
+    assert!(!folder.join("in/nested/out_of_range.json").exists());
+    assert_eq!(
+        fs::read_to_string(folder.join("invalid/out_of_range.json")).unwrap(),
+        "200"
+    );
+    assert_eq!(
+        fs::read_to_string(folder.join("invalid/nested/out_of_range.json")).unwrap(),
+        "150"
+    );
+    assert!(folder.join("invalid/unparsable.json").is_file());
+    assert!(!folder.join("invalid/valid.json").exists());
+}
+
+#[test]
+#[should_panic(expected = "2 files of the input corpus are invalid")]
+fn test_strict_corpus() {
+    let temp_folder = make_corpus("strict");
+    let mut args = Arguments::for_internal_documentation_test();
+    args.corpus_in = Some(temp_folder.0.join("in"));
+    args.strict_corpus = true;
+    fuzz_corpus(args);
+}
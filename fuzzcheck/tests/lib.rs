@@ -1,6 +1,10 @@
 #![feature(no_coverage)]
 // #![feature(trivial_bounds)]
 #![feature(bench_black_box)]
+// The tests launching the fuzzer (float, input_corpus, multiple_arguments, seed_inputs, and
+// stateful_fuzz_test) are not part of this binary. The fuzzer uses process-wide state, such as
+// the panic hook, the signal handlers, and the test failure statics, so these tests are compiled
+// as separate test binaries instead of running in parallel with each other here.
 mod alternation_char_mutators;
 mod char_mutators;
 mod const_generics;
@@ -0,0 +1,43 @@
+#![cfg(feature = "serde_json_serializer")]
+use fuzzcheck::builder::FuzzTestArguments2;
+use fuzzcheck::sensors_and_pools::{MaximiseObservationPool, NoopSensor};
+use fuzzcheck::{Arguments, ReasonForStopping, SerdeSerializer, Serializer};
+
+fn both_set(xs: &[u8], flag: &bool) -> bool {
+    !(*flag && xs.len() > 2)
+}
+
+#[test]
+fn test_fuzz_test_with_multiple_arguments() {
+    let result = fuzzcheck::fuzz_test(both_set)
+        .default_mutator()
+        .serde_serializer()
+        .sensor_and_pool(NoopSensor, MaximiseObservationPool::<()>::new("noop"))
+        .arguments(Arguments::for_internal_documentation_test())
+        .stop_after_iterations(100_000)
+        .launch();
+
+    assert!(result.found_test_failure);
+    match result.reason_for_stopping {
+        ReasonForStopping::TestFailure(FuzzTestArguments2 { arg0, arg1 }) => {
+            assert!(arg1);
+            assert!(arg0.len() > 2);
+        }
+        _ => panic!("the fuzzer should have found a test failure"),
+    }
+}
+
+#[test]
+fn test_multiple_arguments_are_serialized_as_a_record() {
+    let serializer = SerdeSerializer::<FuzzTestArguments2<String, bool>>::default();
+    let args = FuzzTestArguments2 {
+        arg0: "hello".to_owned(),
+        arg1: true,
+    };
+    let data = serializer.to_data(&args);
+    assert_eq!(
+        String::from_utf8(data.clone()).unwrap(),
+        r#"{"arg0":"hello","arg1":true}"#
+    );
+    assert_eq!(serializer.from_data(&data), Some(args));
+}
@@ -0,0 +1,58 @@
+#![allow(unused_attributes)]
+#![feature(no_coverage)]
+
+use fuzzcheck::mutators::benchmark::MutatorBenchmark;
+use fuzzcheck::DefaultMutator;
+
+#[derive(Clone, Debug, DefaultMutator)]
+struct Packet {
+    id: u16,
+    payload: Vec<u8>,
+    flags: Option<bool>,
+}
+
+#[test]
+fn test_mutator_benchmark() {
+    let mutator = Packet::default_mutator();
+    let report = MutatorBenchmark::new(256.).with_iterations(50, 20).run(&mutator);
+
+    assert!(report.ordered_arbitrary.nbr_values <= 50);
+    assert_eq!(report.random_arbitrary.nbr_values, 50);
+    assert_eq!(report.random_mutate.nbr_values, 50 * 20);
+    assert!(report.ordered_mutate.nbr_values <= 50 * 20);
+    for stats in [
+        &report.ordered_arbitrary,
+        &report.random_arbitrary,
+        &report.ordered_mutate,
+        &report.random_mutate,
+    ] {
+        assert!(stats.nbr_distinct_values <= stats.nbr_values);
+        assert!(stats.complexities.windows(2).all(|w| w[0] <= w[1]));
+        assert!(stats.complexity_quantile(0.5) <= stats.complexity_quantile(0.9));
+    }
+    assert!(report.random_mutate.diversity() > 0.1);
+
+    let table = report.to_string();
+    let rows = table
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), 5);
+    assert_eq!(
+        rows[0].join(" "),
+        "operation values ns/op distinct min cplx median cplx p90 cplx max cplx"
+    );
+    for (row, (name, stats)) in rows[1..].iter().zip([
+        ("ordered_arbitrary", &report.ordered_arbitrary),
+        ("random_arbitrary", &report.random_arbitrary),
+        ("ordered_mutate", &report.ordered_mutate),
+        ("random_mutate", &report.random_mutate),
+    ]) {
+        assert_eq!(row[0], name);
+        assert_eq!(row[1], stats.nbr_values.to_string());
+        assert_eq!(row[2], format!("{:.1}", stats.nanos_per_operation()));
+        assert_eq!(row[3], format!("{:.2}%", stats.diversity() * 100.0));
+        assert_eq!(row[4], format!("{:.2}", stats.complexity_quantile(0.0)));
+        assert_eq!(row[7], format!("{:.2}", stats.complexity_quantile(1.0)));
+    }
+}
@@ -0,0 +1,123 @@
+#![allow(unused_attributes)]
+#![feature(no_coverage)]
+
+use std::any::Any;
+
+use fuzzcheck::mutators::conformance::{ConformanceTest, ConformanceViolation, Origin};
+use fuzzcheck::mutators::integer_within_range::U8WithinRangeMutator;
+use fuzzcheck::{DefaultMutator, Mutator};
+
+#[derive(Clone, Debug, PartialEq, DefaultMutator)]
+enum Instruction {
+    Push(u8),
+    Pop,
+    Jump { offset: i16, conditional: bool },
+}
+
+#[test]
+fn test_conformance_of_default_mutators() {
+    fastrand::seed(0);
+    ConformanceTest::new(256.)
+        .run(&<Vec<Instruction>>::default_mutator())
+        .assert_ok();
+    ConformanceTest::new(256.)
+        .run(&<(u16, Option<bool>)>::default_mutator())
+        .assert_ok();
+    ConformanceTest::new(256.).run(&String::default_mutator()).assert_ok();
+    ConformanceTest::new(256.)
+        .run(&U8WithinRangeMutator::new(3..=10))
+        .assert_ok();
+}
+
+/// A mutator that forgets to revert its mutations
+struct ForgetfulMutator;
+
+impl Mutator<u8> for ForgetfulMutator {
+    type Cache = ();
+    type MutationStep = ();
+    type ArbitraryStep = ();
+    type UnmutateToken = ();
+
+    #[no_coverage]
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {}
+    #[no_coverage]
+    fn is_valid(&self, _value: &u8) -> bool {
+        true
+    }
+    #[no_coverage]
+    fn validate_value(&self, _value: &u8) -> Option<Self::Cache> {
+        Some(())
+    }
+    #[no_coverage]
+    fn default_mutation_step(&self, _value: &u8, _cache: &Self::Cache) -> Self::MutationStep {}
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        8.
+    }
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        8.
+    }
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        8.
+    }
+    #[no_coverage]
+    fn complexity(&self, _value: &u8, _cache: &Self::Cache) -> f64 {
+        8.
+    }
+    #[no_coverage]
+    fn ordered_arbitrary(&self, _step: &mut Self::ArbitraryStep, _max_cplx: f64) -> Option<(u8, f64)> {
+        Some((0, 8.))
+    }
+    #[no_coverage]
+    fn random_arbitrary(&self, _max_cplx: f64) -> (u8, f64) {
+        (0, 8.)
+    }
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut u8,
+        _cache: &mut Self::Cache,
+        _step: &mut Self::MutationStep,
+        _subvalue_provider: &dyn fuzzcheck::SubValueProvider,
+        _max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        *value = value.wrapping_add(1);
+        Some(((), 8.))
+    }
+    #[no_coverage]
+    fn random_mutate(&self, value: &mut u8, _cache: &mut Self::Cache, _max_cplx: f64) -> (Self::UnmutateToken, f64) {
+        *value = value.wrapping_add(1);
+        ((), 8.)
+    }
+    #[no_coverage]
+    fn unmutate(&self, _value: &mut u8, _cache: &mut Self::Cache, _t: Self::UnmutateToken) {}
+    #[no_coverage]
+    fn visit_subvalues<'a>(&self, _value: &'a u8, _cache: &'a Self::Cache, _visit: &mut dyn FnMut(&'a dyn Any, f64)) {}
+}
+
+#[test]
+fn test_conformance_violations_are_reported() {
+    let report = ConformanceTest::new(256.)
+        .with_iterations(2, 1)
+        .check_duplicates()
+        .run(&ForgetfulMutator);
+    assert!(!report.is_ok());
+    assert!(report
+        .violations
+        .contains(&ConformanceViolation::DuplicateArbitrary { value: "0".to_owned() }));
+    assert!(report.violations.contains(&ConformanceViolation::UnmutateMismatch {
+        origin: Origin::RandomMutate,
+        original: "0".to_owned(),
+        mutated: "1".to_owned(),
+        unmutated: "1".to_owned(),
+    }));
+    assert!(report.to_string().contains("unmutate did not revert ordered_mutate"));
+
+    let report = ConformanceTest::new(256.).with_iterations(2, 1).run(&ForgetfulMutator);
+    assert!(!report
+        .violations
+        .iter()
+        .any(|violation| matches!(violation, ConformanceViolation::DuplicateArbitrary { .. })));
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "serde_json_serializer")]
+use std::sync::Mutex;
+
+use fuzzcheck::mutators::integer_within_range::U8WithinRangeMutator;
+use fuzzcheck::sensors_and_pools::{MaximiseObservationPool, NoopSensor};
+use fuzzcheck::{Arguments, DefaultMutator, ReasonForStopping};
+
+/// The tests of this file launch the fuzzer, which cannot run twice at the same time in the same process
+static FUZZER_LOCK: Mutex<()> = Mutex::new(());
+
+#[test]
+fn test_seed_inputs_are_tested_first() {
+    let _guard = FUZZER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let result = fuzzcheck::fuzz_test(|xs: &[u8]| xs != b"a very specific seed")
+        .mutator(Vec::<u8>::default_mutator())
+        .serializer(fuzzcheck::ByteSerializer::new("bytes"))
+        .sensor_and_pool(NoopSensor, MaximiseObservationPool::<()>::new("noop"))
+        .arguments(Arguments::for_internal_documentation_test())
+        .stop_after_iterations(10)
+        .seed_inputs([b"a very specific seed".to_vec()])
+        .launch();
+
+    match result.reason_for_stopping {
+        ReasonForStopping::TestFailure(xs) => assert_eq!(xs, b"a very specific seed"),
+        _ => panic!("the seed input should have been tested"),
+    }
+}
+
+#[test]
+#[should_panic(expected = "The seed input #1 is rejected by the mutator: 200")]
+fn test_invalid_seed_input() {
+    let _guard = FUZZER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = fuzzcheck::fuzz_test(|_: &u8| true)
+        .mutator(U8WithinRangeMutator::new(0..=100))
+        .serde_serializer()
+        .sensor_and_pool(NoopSensor, MaximiseObservationPool::<()>::new("noop"))
+        .arguments(Arguments::for_internal_documentation_test())
+        .stop_after_iterations(10)
+        .seed_inputs([50, 200])
+        .launch();
+}
+
+#[test]
+#[should_panic(expected = "The seed input #0 is more complex than the maximum input complexity (17.00 > 8.00)")]
+fn test_too_complex_seed_input() {
+    let _guard = FUZZER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut args = Arguments::for_internal_documentation_test();
+    args.max_input_cplx = 8.0;
+    let _ = fuzzcheck::fuzz_test(|_: &[u8]| true)
+        .mutator(Vec::<u8>::default_mutator())
+        .serializer(fuzzcheck::ByteSerializer::new("bytes"))
+        .sensor_and_pool(NoopSensor, MaximiseObservationPool::<()>::new("noop"))
+        .arguments(args)
+        .stop_after_iterations(10)
+        .seed_inputs([b"ab".to_vec()])
+        .launch();
+}
@@ -0,0 +1,39 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use fuzzcheck::sensors_and_pools::{MaximiseObservationPool, NoopSensor};
+use fuzzcheck::{Arguments, DefaultMutator};
+
+static NBR_SETUPS: AtomicUsize = AtomicUsize::new(0);
+static NBR_RESETS: AtomicUsize = AtomicUsize::new(0);
+
+struct State {
+    inputs: Vec<Vec<u8>>,
+}
+
+fn setup() -> State {
+    NBR_SETUPS.fetch_add(1, Ordering::SeqCst);
+    State { inputs: vec![] }
+}
+
+#[test]
+fn test_fuzz_test_with_state() {
+    let result = fuzzcheck::fuzz_test_with_state(setup, |state: &mut State, x: &[u8]| {
+        state.inputs.push(x.to_vec());
+        // the state is reset before each test case
+        state.inputs.len() == 1
+    })
+    .reset_state(|state: &mut State| {
+        NBR_RESETS.fetch_add(1, Ordering::SeqCst);
+        state.inputs.clear();
+    })
+    .mutator(Vec::<u8>::default_mutator())
+    .serializer(fuzzcheck::ByteSerializer::new("bytes"))
+    .sensor_and_pool(NoopSensor, MaximiseObservationPool::<()>::new("noop"))
+    .arguments(Arguments::for_internal_documentation_test())
+    .stop_after_iterations(100)
+    .launch();
+
+    assert!(!result.found_test_failure);
+    assert_eq!(NBR_SETUPS.load(Ordering::SeqCst), 1);
+    assert!(NBR_RESETS.load(Ordering::SeqCst) >= 100);
+}
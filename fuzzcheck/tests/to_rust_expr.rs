@@ -0,0 +1,93 @@
+#![allow(unused_attributes)]
+#![feature(no_coverage)]
+
+use fuzzcheck::builder::FuzzTestArguments2;
+use fuzzcheck::ToRustExpr;
+
+#[derive(Clone, Debug, PartialEq, ToRustExpr)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, ToRustExpr)]
+pub struct Wrapper<T>(pub T, pub bool);
+
+#[derive(Clone, Debug, PartialEq, ToRustExpr)]
+pub struct Unit;
+
+#[derive(Clone, Debug, PartialEq, ToRustExpr)]
+pub enum Shape {
+    Empty,
+    Line(Point, Point),
+    Polygon { points: Vec<Point>, closed: bool },
+}
+
+#[test]
+fn test_derived_to_rust_expr() {
+    // the paths start with the module where the type is defined
+    let m = module_path!();
+    assert_eq!(Unit.to_rust_expr(), format!("{}::Unit", m));
+    assert_eq!(
+        Wrapper(Some('a'), true).to_rust_expr(),
+        format!("{}::Wrapper(Some('a'), true)", m)
+    );
+    assert_eq!(Shape::Empty.to_rust_expr(), format!("{}::Shape::Empty", m));
+    assert_eq!(
+        Shape::Line(Point { x: 0, y: -1 }, Point { x: 2, y: 3 }).to_rust_expr(),
+        format!(
+            "{m}::Shape::Line({m}::Point {{ x: 0i32, y: -1i32 }}, {m}::Point {{ x: 2i32, y: 3i32 }})",
+            m = m
+        )
+    );
+    assert_eq!(
+        Shape::Polygon {
+            points: vec![],
+            closed: false
+        }
+        .to_rust_expr(),
+        format!("{}::Shape::Polygon {{ points: vec![], closed: false }}", m)
+    );
+}
+
+#[test]
+fn test_to_rust_expr_of_multiple_arguments() {
+    let arguments = FuzzTestArguments2 {
+        arg0: "ab".to_owned(),
+        arg1: 3u8,
+    };
+    assert_eq!(
+        arguments.to_rust_expr(),
+        r#"fuzzcheck::builder::FuzzTestArguments2 { arg0: "ab".to_owned(), arg1: 3u8 }"#
+    );
+}
+
+fn contains_origin(points: &[Point]) -> bool {
+    points.contains(&Point { x: 0, y: 0 })
+}
+
+fn compare(a: &str, b: &u8) -> Result<(), String> {
+    if a.len() == *b as usize {
+        Ok(())
+    } else {
+        Err(format!("{} != {}", a.len(), b))
+    }
+}
+
+#[test]
+fn test_reproduce() {
+    fuzzcheck::reproduce(contains_origin, vec![Point { x: 0i32, y: 0i32 }]);
+    fuzzcheck::reproduce(
+        compare,
+        fuzzcheck::builder::FuzzTestArguments2 {
+            arg0: "ab".to_owned(),
+            arg1: 2u8,
+        },
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_reproduce_failure() {
+    fuzzcheck::reproduce(contains_origin, vec![Point { x: 1, y: 0 }]);
+}
@@ -1,5 +1,6 @@
 use fuzzcheck::mutators::integer::U8Mutator;
 use fuzzcheck::mutators::vector::VecMutator;
+use fuzzcheck::Mutator;
 #[test]
 fn test_vector_mutator() {
     // let m = VecMutator::new(U8Mutator::default(), 0..=10);
@@ -18,6 +19,20 @@ fn test_vector_mutator() {
     fuzzcheck::mutators::testing_utilities::test_mutator(m, 500.0, 500.0, false, true, 100, 150);
 }
 
+#[test]
+fn test_vector_ordered_arbitrary_produces_empty_vector_once() {
+    let m = VecMutator::new(U8Mutator::default(), 0..=10, true);
+    let mut step = m.default_arbitrary_step();
+    let values = std::iter::from_fn(|| m.ordered_arbitrary(&mut step, 100.0))
+        .take(1000)
+        .collect::<Vec<_>>();
+    assert_eq!(values.iter().filter(|(v, _)| v.is_empty()).count(), 1);
+
+    let mut step = m.default_arbitrary_step();
+    assert_eq!(m.ordered_arbitrary(&mut step, 1.0), Some((vec![], 1.0)));
+    assert_eq!(m.ordered_arbitrary(&mut step, 1.0), None);
+}
+
 // #[test]
 // fn test_vector_explore() {
 //     // let m = VecMutator::new(VecMutator::new(U8Mutator::default(), 0..=5), 0..=5);
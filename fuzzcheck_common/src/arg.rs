@@ -23,9 +23,18 @@ pub const STOP_AFTER_FIRST_FAILURE_FLAG: &str = "stop-after-first-failure";
 
 pub const DETECT_INFINITE_LOOP_FLAG: &str = "detect-infinite-loop";
 
+pub const SANITIZER_FLAG: &str = "sanitizer";
+pub const LEAK_CHECK_INTERVAL_FLAG: &str = "leak-check-interval";
+
+pub const STRICT_CORPUS_FLAG: &str = "strict-corpus";
+pub const QUARANTINE_INVALID_FLAG: &str = "quarantine-invalid";
+
+pub const EMIT_TEST_FLAG: &str = "emit-test";
+
 pub const COMMAND_FUZZ: &str = "fuzz";
 pub const COMMAND_MINIFY_INPUT: &str = "minify";
 pub const COMMAND_READ: &str = "read";
+pub const COMMAND_REPRODUCE: &str = "reproduce";
 
 #[derive(Clone)]
 pub struct DefaultArguments {
@@ -44,6 +53,7 @@ pub enum FuzzerCommand {
     Fuzz,
     Read { input_file: PathBuf },
     MinifyInput { input_file: PathBuf },
+    Reproduce { input_file: PathBuf, emit_test: bool },
 }
 impl Default for FuzzerCommand {
     fn default() -> Self {
@@ -51,6 +61,58 @@ impl Default for FuzzerCommand {
     }
 }
 
+/// A sanitizer that the fuzz test can be compiled with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sanitizer {
+    Address,
+    Leak,
+    Memory,
+    Thread,
+}
+impl Sanitizer {
+    /// The name of the sanitizer, as given to `-Zsanitizer` and `--sanitizer`
+    #[no_coverage]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sanitizer::Address => "address",
+            Sanitizer::Leak => "leak",
+            Sanitizer::Memory => "memory",
+            Sanitizer::Thread => "thread",
+        }
+    }
+    /// Parse the name of a sanitizer, as given to `--sanitizer`
+    #[no_coverage]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "address" => Some(Sanitizer::Address),
+            "leak" => Some(Sanitizer::Leak),
+            "memory" => Some(Sanitizer::Memory),
+            "thread" => Some(Sanitizer::Thread),
+            _ => None,
+        }
+    }
+    /// The name of the environment variable used to pass options to the sanitizer’s runtime
+    #[no_coverage]
+    pub fn options_env_var(&self) -> &'static str {
+        match self {
+            Sanitizer::Address => "ASAN_OPTIONS",
+            Sanitizer::Leak => "LSAN_OPTIONS",
+            Sanitizer::Memory => "MSAN_OPTIONS",
+            Sanitizer::Thread => "TSAN_OPTIONS",
+        }
+    }
+    /// The name of the sanitizer as it appears in its reports, e.g. `AddressSanitizer`
+    #[no_coverage]
+    pub fn report_name(&self) -> &'static str {
+        match self {
+            Sanitizer::Address => "AddressSanitizer",
+            Sanitizer::Leak => "LeakSanitizer",
+            Sanitizer::Memory => "MemorySanitizer",
+            Sanitizer::Thread => "ThreadSanitizer",
+        }
+    }
+}
+
 /// Various arguments given to the fuzzer, typically provided by the `cargo fuzzcheck` command line tool.
 #[derive(Debug, Clone)]
 pub struct Arguments {
@@ -64,6 +126,15 @@ pub struct Arguments {
     pub corpus_out: Option<PathBuf>,
     pub artifacts_folder: Option<PathBuf>,
     pub stats_folder: Option<PathBuf>,
+    pub sanitizer: Option<Sanitizer>,
+    /// Number of test cases between two leak checks, when the fuzz test is compiled with LeakSanitizer
+    ///
+    /// By default, leaks are only reported when the process exits
+    pub leak_check_interval: Option<usize>,
+    /// Fail instead of ignoring the files of the input corpus that cannot be deserialized or are rejected by the mutator
+    pub strict_corpus: bool,
+    /// Folder where the invalid files of the input corpus are moved to
+    pub quarantine_invalid: Option<PathBuf>,
 }
 impl Arguments {
     pub fn for_internal_documentation_test() -> Self {
@@ -78,6 +149,10 @@ impl Arguments {
             corpus_out: None,
             artifacts_folder: None,
             stats_folder: None,
+            sanitizer: None,
+            leak_check_interval: None,
+            strict_corpus: false,
+            quarantine_invalid: None,
         }
     }
 }
@@ -93,10 +168,10 @@ pub fn options_parser() -> Options {
         "",
         COMMAND_FLAG,
         &format!(
-            "the action to be performed (default: fuzz). --{} is required when using `{}`",
-            INPUT_FILE_FLAG, COMMAND_MINIFY_INPUT
+            "the action to be performed (default: fuzz). --{} is required when using `{}` or `{}`",
+            INPUT_FILE_FLAG, COMMAND_MINIFY_INPUT, COMMAND_REPRODUCE
         ),
-        &format!("<{} | {}>", COMMAND_FUZZ, COMMAND_MINIFY_INPUT),
+        &format!("<{} | {} | {}>", COMMAND_FUZZ, COMMAND_MINIFY_INPUT, COMMAND_REPRODUCE),
     );
     options.optopt(
         "",
@@ -128,6 +203,17 @@ pub fn options_parser() -> Options {
         )
         .as_str(),
     );
+    options.optflag(
+        "",
+        STRICT_CORPUS_FLAG,
+        "fail if a file of the input corpus cannot be deserialized or is rejected by the mutator",
+    );
+    options.optopt(
+        "",
+        QUARANTINE_INVALID_FLAG,
+        "folder where the invalid files of the input corpus are moved to",
+        "PATH",
+    );
     options.optopt("", OUT_CORPUS_FLAG, "folder for the output corpus", "PATH");
     options.optflag(
         "",
@@ -155,6 +241,30 @@ pub fn options_parser() -> Options {
         format!("do not save statistics, overrides --{stats}", stats = STATS_FLAG).as_str(),
     );
     options.optopt("", INPUT_FILE_FLAG, "file containing a test case", "PATH");
+    options.optflag(
+        "",
+        EMIT_TEST_FLAG,
+        &format!(
+            "when using `{}`, write a regression test for the test case next to the fuzz test",
+            COMMAND_REPRODUCE
+        ),
+    );
+    options.optopt(
+        "",
+        SANITIZER_FLAG,
+        "compile the fuzz test with the given sanitizer and report its findings as test failures",
+        "<address | leak | memory | thread>",
+    );
+    options.optopt(
+        "",
+        LEAK_CHECK_INTERVAL_FLAG,
+        &format!(
+            "when using --{} leak, check for memory leaks every N test cases instead of only at exit. \
+            The test case that is saved is the last one that ran before the check",
+            SANITIZER_FLAG
+        ),
+        "N",
+    );
     options.optopt(
         "",
         MAX_INPUT_CPLX_FLAG,
@@ -196,12 +306,16 @@ impl Arguments {
 
         let command = command.as_str();
 
-        if !matches!(command, COMMAND_FUZZ | COMMAND_READ | COMMAND_MINIFY_INPUT) {
+        if !matches!(
+            command,
+            COMMAND_FUZZ | COMMAND_READ | COMMAND_MINIFY_INPUT | COMMAND_REPRODUCE
+        ) {
             return Err(ArgumentsError::Validation(format!(
-                r#"The command {c} is not supported. It can either be ‘{fuzz}’ or ‘{minify}’."#,
+                r#"The command {c} is not supported. It can either be ‘{fuzz}’, ‘{minify}’, or ‘{reproduce}’."#,
                 c = &matches.free[0],
                 fuzz = COMMAND_FUZZ,
                 minify = COMMAND_MINIFY_INPUT,
+                reproduce = COMMAND_REPRODUCE,
             )));
         }
 
@@ -229,6 +343,13 @@ impl Arguments {
             None
         };
 
+        let strict_corpus = matches.opt_present(STRICT_CORPUS_FLAG);
+
+        let quarantine_invalid: Option<PathBuf> = matches.opt_str(QUARANTINE_INVALID_FLAG).and_then(
+            #[no_coverage]
+            |x| x.parse::<PathBuf>().ok(),
+        );
+
         let corpus_out: Option<PathBuf> = matches.opt_str(OUT_CORPUS_FLAG).and_then(
             #[no_coverage]
             |x| x.parse::<PathBuf>().ok(),
@@ -267,6 +388,32 @@ impl Arguments {
             |x| x.parse::<PathBuf>().ok(),
         );
 
+        let sanitizer = if let Some(sanitizer) = matches.opt_str(SANITIZER_FLAG) {
+            if let Some(sanitizer) = Sanitizer::from_name(&sanitizer) {
+                Some(sanitizer)
+            } else {
+                return Err(ArgumentsError::Validation(format!(
+                    "The sanitizer {} is not supported. It can be ‘address’, ‘leak’, ‘memory’, or ‘thread’.",
+                    sanitizer
+                )));
+            }
+        } else {
+            None
+        };
+        let leak_check_interval = if let Some(interval) = matches.opt_str(LEAK_CHECK_INTERVAL_FLAG) {
+            match interval.parse::<usize>() {
+                Ok(interval) if interval > 0 => Some(interval),
+                _ => {
+                    return Err(ArgumentsError::Validation(format!(
+                        "The value of --{} must be a positive integer, not {}.",
+                        LEAK_CHECK_INTERVAL_FLAG, interval
+                    )));
+                }
+            }
+        } else {
+            None
+        };
+
         // verify all the right options are here
 
         let command = match command {
@@ -295,6 +442,19 @@ impl Arguments {
                 );
                 FuzzerCommand::MinifyInput { input_file }
             }
+            COMMAND_REPRODUCE => {
+                let input_file = input_file.unwrap_or_else(
+                    #[no_coverage]
+                    || {
+                        panic!(
+                            "An input file must be provided when reproducing a test failure. Use --{}",
+                            INPUT_FILE_FLAG
+                        )
+                    },
+                );
+                let emit_test = matches.opt_present(EMIT_TEST_FLAG);
+                FuzzerCommand::Reproduce { input_file, emit_test }
+            }
             _ => unreachable!(),
         };
 
@@ -336,6 +496,10 @@ impl Arguments {
             corpus_out,
             artifacts_folder,
             stats_folder,
+            sanitizer,
+            leak_check_interval,
+            strict_corpus,
+            quarantine_invalid,
         })
     }
 }
@@ -383,17 +547,43 @@ cargo-fuzzcheck tests::fuzzit --{max_cplx} 4000 --{out_corpus} fuzz_results/out/
     and write the output corpus (i.e. the folder of most interesting test cases) 
     to fuzz_results/out/.
 
+cargo-fuzzcheck tests::fuzz --{sanitizer} address
+    Fuzz "tests::fuzz" with AddressSanitizer enabled. Memory errors detected by
+    the sanitizer are saved as artifacts in a folder named after the error.
+
+cargo-fuzzcheck tests::fuzz --{sanitizer} leak --{leak_check_interval} 1000
+    Fuzz "tests::fuzz" with LeakSanitizer enabled, checking for memory leaks
+    every 1000 test cases.
+
+cargo-fuzzcheck tests::fuzz --{in_corpus} fuzz_results/in/ --{quarantine_invalid} fuzz_results/invalid/
+    Fuzz "tests::fuzz", starting from the test cases in fuzz_results/in/. The files
+    that cannot be deserialized or that are rejected by the mutator are moved to
+    fuzz_results/invalid/. Use --{strict_corpus} to stop the fuzzer instead.
+
 cargo-fuzzcheck tests::fuzz --command {minify} --{input_file} "artifacts/crash.json"
     Using the fuzz test located at "tests::fuzz_test", minify the test input defined 
     in the file "artifacts/crash.json". It will put minified inputs in the folder 
     artifacts/crash.minified/ and name them {{complexity}}-{{hash}}.json. 
     For example, artifacts/crash.minified/4213--8cd7777109b57b8c.json
     is a minified input of complexity 42.13.
+
+cargo-fuzzcheck tests::fuzz --command {reproduce} --{input_file} "artifacts/crash.minified/4213--8cd7777109b57b8c.json" --{emit_test}
+    Run the fuzz test "tests::fuzz" on the given test case and write a regression
+    test constructing it, in the file fuzzcheck_regressions.rs of the module containing
+    the fuzz test. The type of the test case must implement `ToRustExpr`, and the tested
+    function must be a named function rather than a closure.
 "#,
         minify = COMMAND_MINIFY_INPUT,
+        reproduce = COMMAND_REPRODUCE,
+        emit_test = EMIT_TEST_FLAG,
         input_file = INPUT_FILE_FLAG,
         max_cplx = MAX_INPUT_CPLX_FLAG,
         out_corpus = OUT_CORPUS_FLAG,
+        sanitizer = SANITIZER_FLAG,
+        leak_check_interval = LEAK_CHECK_INTERVAL_FLAG,
+        in_corpus = IN_CORPUS_FLAG,
+        quarantine_invalid = QUARANTINE_INVALID_FLAG,
+        strict_corpus = STRICT_CORPUS_FLAG,
     )
     .as_str();
     help
@@ -18,7 +18,7 @@ pub enum FuzzerEvent {
     Pulse,
     Done,
     Replace(usize, usize),
-    DidReadCorpus,
+    DidReadCorpus { nbr_unparsable: usize, nbr_invalid: usize },
     CaughtSignal(i32),
     TestFailure,
     None,
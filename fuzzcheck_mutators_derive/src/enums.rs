@@ -1,4 +1,4 @@
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenStream};
 use syn::{DataEnum, Generics, Visibility};
 
 use crate::structs_and_enums::{CreateWrapperMutatorParams, FieldMutator, FieldMutatorKind};
@@ -47,7 +47,7 @@ pub(crate) fn impl_default_mutator_for_enum(
                         }
                         let mut mutator = None;
                         for attribute in field.attrs.iter() {
-                            match super::read_field_default_mutator_attribute(attribute) {
+                            match super::read_field_default_mutator_attribute(attribute, &field.ty) {
                                 Ok(Some(field_mutator_attribute)) => {
                                     mutator = Some((field_mutator_attribute.ty, field_mutator_attribute.equal));
                                 }
@@ -80,6 +80,8 @@ pub(crate) fn impl_default_mutator_for_enum(
         })
         .collect::<Vec<_>>();
 
+    let with_weights = variant_weights_stream(tb, enu);
+
     let TupleNMutator = cm.TupleNMutator.as_ref();
     let EnumSingleVariant = ident!(&enum_ident "SingleVariant");
 
@@ -113,6 +115,38 @@ pub(crate) fn impl_default_mutator_for_enum(
         ">"
     );
 
+    let (InnerMutator, inner_mutator_init) = crate::structs_and_enums::repair_inner_mutator(
+        tb,
+        &cm,
+        enum_ident,
+        generics,
+        settings,
+        InnerMutator,
+        ts!(
+            cm.AlternationMutator "::new(vec!["
+                join_ts!(enu.variants.iter().enumerate().filter(|(_, variant)| {
+                            variant.attrs.iter().all(|attr| {
+                                !super::has_ignore_variant_attribute(attr)
+                            })
+                        }), (i, variant),
+                EnumSingleVariant "::" variant.ident "("
+                if variant.fields.is_empty() {
+                    TupleNMutator(0)
+                } else {
+                        ts!(
+                            TupleNMutator(variant.fields.len()) "::new("
+                                join_ts!(variant.fields.iter().enumerate(), (idx, field),
+                                    ident!("mutator_" enu.variants[i].ident "_" access_field(field, idx))
+                                , separator: ",")
+                            ")"
+                       )
+                }
+                ")"
+                , separator: ",")
+            "], " format!("{:.2}", size_to_cplxity(enu.variants.len())) ")" with_weights
+        ),
+    );
+
     let params = CreateWrapperMutatorParams {
         cm: &cm,
         visibility: vis,
@@ -129,27 +163,7 @@ pub(crate) fn impl_default_mutator_for_enum(
                 ident!("mutator_" enu.variants[field_mutator.i].ident "_" access_field(&field_mutator.field, field_mutator.j.unwrap())) ":" field_mutator.mutator_stream(&cm)
             , separator: ",") ") -> Self {
                 Self {
-                    mutator: " cm.AlternationMutator "::new(vec!["
-                        join_ts!(enu.variants.iter().enumerate().filter(|(_, variant)| {
-                                    variant.attrs.iter().all(|attr| {
-                                        !super::has_ignore_variant_attribute(attr)
-                                    })
-                                }), (i, variant),
-                        EnumSingleVariant "::" variant.ident "("
-                        if variant.fields.is_empty() {
-                            TupleNMutator(0)
-                        } else {
-                                ts!(
-                                    TupleNMutator(variant.fields.len()) "::new("
-                                        join_ts!(variant.fields.iter().enumerate(), (idx, field),
-                                            ident!("mutator_" enu.variants[i].ident "_" access_field(field, idx))
-                                        , separator: ",")
-                                    ")"
-                               )
-                        }
-                        ")"
-                        , separator: ",")
-                    "], " format!("{:.2}", size_to_cplxity(enu.variants.len())) ")
+                    mutator: " inner_mutator_init "
                 }
             }"
         ),
@@ -159,6 +173,19 @@ pub(crate) fn impl_default_mutator_for_enum(
     extend_ts!(tb, crate::structs_and_enums::make_mutator_type_and_impl(params))
 }
 
+/// The `.with_weights(..)` call setting the weights of the variants given by their `#[fuzz(weight = N)]` attributes
+fn variant_weights_stream(tb: &mut TokenBuilder, enu: &DataEnum) -> TokenStream {
+    if let Some(weights) = super::read_variant_weights(tb, enu) {
+        ts!(
+            ".with_weights(vec!["
+                join_ts!(weights.iter(), weight, format!("{weight:?}"), separator: ",")
+            "])"
+        )
+    } else {
+        ts!()
+    }
+}
+
 #[allow(non_snake_case)]
 pub(crate) fn impl_basic_enum_structure(tb: &mut TokenBuilder, enum_ident: &Ident, enu: &DataEnum) {
     assert!(!enu.variants.is_empty() && enu.variants.iter().all(|variant| variant.fields.is_empty()));
@@ -213,7 +240,13 @@ pub(crate) fn impl_basic_enum_structure(tb: &mut TokenBuilder, enum_ident: &Iden
 }
 
 #[allow(non_snake_case)]
-pub(crate) fn impl_default_mutator_for_basic_enum(tb: &mut TokenBuilder, enum_ident: &Ident, enu: &DataEnum) {
+pub(crate) fn impl_default_mutator_for_basic_enum(
+    tb: &mut TokenBuilder,
+    enum_ident: &Ident,
+    generics: &Generics,
+    enu: &DataEnum,
+    settings: &MakeMutatorSettings,
+) {
     assert!(!enu.variants.is_empty() && enu.variants.iter().all(|variant| variant.fields.is_empty()));
 
     let cm = Common::new(0);
@@ -231,12 +264,24 @@ pub(crate) fn impl_default_mutator_for_basic_enum(tb: &mut TokenBuilder, enum_id
         })
         .count();
 
+    let with_weights = variant_weights_stream(tb, enu);
+
+    let (Mutator, mutator_init) = crate::structs_and_enums::repair_inner_mutator(
+        tb,
+        &cm,
+        enum_ident,
+        generics,
+        settings,
+        BasicEnumMutator.clone(),
+        ts!(BasicEnumMutator "::new::<" enum_ident ">(" q!(count_non_ignored) ")" with_weights),
+    );
+
     extend_ts!(tb,
         "impl" cm.DefaultMutator "for " enum_ident " {
-            type Mutator = " BasicEnumMutator ";
+            type Mutator = " Mutator ";
             #[no_coverage]
             fn default_mutator() -> Self::Mutator {
-                Self::Mutator::new::<" enum_ident ">(" q!(count_non_ignored) ")
+                " mutator_init "
             }
         }"
     )
@@ -6,12 +6,13 @@
 use proc_macro2::{Ident, Literal, TokenStream, TokenTree};
 use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
-use syn::{parenthesized, parse2, parse_macro_input, token, Attribute, DeriveInput, Error, LitBool, Token};
+use syn::{parenthesized, parse2, parse_macro_input, token, Attribute, DeriveInput, Error, LitBool, LitStr, Token};
 use token_builder::{extend_ts, ident, ts, TokenBuilder};
 
 mod enums;
 mod single_variant;
 mod structs_and_enums;
+mod to_rust_expr;
 
 mod token_builder;
 
@@ -81,7 +82,7 @@ pub fn make_mutator(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     derive_default_mutator_(settings).into()
 }
 
-#[proc_macro_derive(DefaultMutator, attributes(field_mutator, ignore_variant))]
+#[proc_macro_derive(DefaultMutator, attributes(field_mutator, fuzz, ignore_variant))]
 pub fn derive_default_mutator(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let settings = MakeMutatorSettings {
         name: None,
@@ -92,6 +93,17 @@ pub fn derive_default_mutator(item: proc_macro::TokenStream) -> proc_macro::Toke
     derive_default_mutator_(settings).into()
 }
 
+/// Implement `ToRustExpr` for the type, such that the value can be written as a Rust expression.
+///
+/// The generated expressions refer to the type by its module path, e.g. `my_crate::ast::Expr::Lit(..)`.
+#[proc_macro_derive(ToRustExpr)]
+pub fn derive_to_rust_expr(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let item = parse_macro_input!(item as DeriveInput);
+    let mut tb = TokenBuilder::default();
+    to_rust_expr::impl_to_rust_expr(&mut tb, &item);
+    tb.finish().into()
+}
+
 #[doc(hidden)]
 #[proc_macro]
 pub fn make_single_variant_mutator(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -127,7 +139,7 @@ fn derive_default_mutator_(settings: MakeMutatorSettings) -> proc_macro2::TokenS
             } else if !e.variants.is_empty() {
                 // no associated data anywhere
                 enums::impl_basic_enum_structure(&mut tb, &item.ident, &e);
-                enums::impl_default_mutator_for_basic_enum(&mut tb, &item.ident, &e);
+                enums::impl_default_mutator_for_basic_enum(&mut tb, &item.ident, &item.generics, &e, &settings);
             } else {
                 extend_ts!(
                     &mut tb,
@@ -379,17 +391,229 @@ impl syn::parse::Parse for FieldMutatorAttribute {
     }
 }
 
-fn read_field_default_mutator_attribute(attribute: &Attribute) -> Result<Option<FieldMutatorAttribute>, syn::Error> {
+/// A shorthand for a common `#[field_mutator]` attribute, such as `#[fuzz(range = 0 ..= 10)]`
+enum FuzzAttribute {
+    /// The default mutator of the integer, float, or `char` within the range
+    Range(TokenStream),
+    /// The default mutator of the `Vec`, `VecDeque`, or `String` with a length within the range
+    Len(TokenStream),
+    /// The grammar-based mutator of the strings matched by the regular expression
+    Regex(LitStr),
+    /// A mutator that always produces the value of the expression
+    Value(TokenStream),
+}
+impl syn::parse::Parse for FuzzAttribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _ = parenthesized!(content in input);
+        let input = content;
+
+        let ident = input.call(Ident::parse_any)?;
+        let _ = input.parse::<Token![=]>()?;
+        if input.is_empty() {
+            return Err(syn::Error::new(
+                input.span(),
+                format!("Expected a value after `{ident} =`"),
+            ));
+        }
+        match ident.to_string().as_str() {
+            "range" => Ok(Self::Range(input.parse()?)),
+            "len" => Ok(Self::Len(input.parse()?)),
+            "regex" => {
+                let regex = input.parse::<LitStr>()?;
+                check_regex(&regex)?;
+                Ok(Self::Regex(regex))
+            }
+            "value" => Ok(Self::Value(input.parse()?)),
+            x => Err(syn::Error::new(
+                ident.span(),
+                format!("{x} is not a valid fuzz attribute, expected one of `range`, `len`, `regex`, or `value`"),
+            )),
+        }
+    }
+}
+
+/// Parse the regular expression of a `#[fuzz(regex = ..)]` attribute the same way as
+/// `fuzzcheck::mutators::grammar::regex`, such that an invalid regular expression is a compile error
+/// pointing to the literal instead of a panic at runtime
+fn check_regex(regex: &LitStr) -> syn::Result<()> {
+    regex_syntax::Parser::new().parse(&regex.value()).map_err(|error| {
+        let message = match &error {
+            regex_syntax::Error::Parse(e) => e.kind().to_string(),
+            regex_syntax::Error::Translate(e) => e.kind().to_string(),
+            _ => error.to_string(),
+        };
+        syn::Error::new(regex.span(), format!("invalid regular expression: {message}"))
+    })?;
+    Ok(())
+}
+impl FuzzAttribute {
+    /// The equivalent `#[field_mutator]` attribute for a field of type `field_ty`
+    fn into_field_mutator_attribute(self, field_ty: &syn::Type) -> FieldMutatorAttribute {
+        let mutators = ts!("fuzzcheck::mutators");
+        let (ty, equal) = match self {
+            FuzzAttribute::Range(range) => {
+                let trait_ = ts!("<" q!(field_ty) "as" mutators "::DefaultMutatorWithinRange>");
+                (
+                    ts!(trait_ "::Mutator"),
+                    ts!(trait_ "::default_mutator_within_range(" range ")"),
+                )
+            }
+            FuzzAttribute::Len(len_range) => {
+                let trait_ = ts!("<" q!(field_ty) "as" mutators "::DefaultMutatorWithLength>");
+                (
+                    ts!(trait_ "::Mutator"),
+                    ts!(trait_ "::default_mutator_with_length(" len_range ")"),
+                )
+            }
+            FuzzAttribute::Regex(regex) => (
+                ts!(mutators "::grammar::GrammarStringMutator"),
+                ts!(mutators "::grammar::grammar_based_string_mutator(" mutators "::grammar::regex(" q!(regex) "))"),
+            ),
+            FuzzAttribute::Value(value) => (
+                ts!(mutators "::unit::UnitMutator<" q!(field_ty) ">"),
+                ts!(mutators "::unit::UnitMutator::new(" value ", 0.0)"),
+            ),
+        };
+        FieldMutatorAttribute {
+            ty: parse2(ty).unwrap(),
+            equal: Some(equal),
+        }
+    }
+}
+
+/// Read the `#[field_mutator]` or `#[fuzz]` attribute of a field of type `field_ty`
+fn read_field_default_mutator_attribute(
+    attribute: &Attribute,
+    field_ty: &syn::Type,
+) -> Result<Option<FieldMutatorAttribute>, syn::Error> {
     if let Some(ident) = attribute.path.get_ident() {
-        if ident != "field_mutator" {
-            return Ok(None);
+        if ident == "field_mutator" {
+            parse2::<FieldMutatorAttribute>(attribute.tokens.clone()).map(Some)
+        } else if ident == "fuzz" {
+            parse2::<FuzzAttribute>(attribute.tokens.clone())
+                .map(|attribute| Some(attribute.into_field_mutator_attribute(field_ty)))
+        } else {
+            Ok(None)
         }
-        parse2::<FieldMutatorAttribute>(attribute.tokens.clone()).map(Some)
     } else {
         Ok(None)
     }
 }
 
+/// The function repairing the values of a type, given by `#[fuzz(invariant = repair_fn)]`
+struct InvariantAttribute {
+    repair: TokenStream,
+}
+impl syn::parse::Parse for InvariantAttribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _ = parenthesized!(content in input);
+        let input = content;
+
+        let ident = input.call(Ident::parse_any)?;
+        if ident != "invariant" {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!("{ident} is not a valid fuzz attribute for a type, expected `invariant`"),
+            ));
+        }
+        let _ = input.parse::<Token![=]>()?;
+        if input.is_empty() {
+            return Err(syn::Error::new(
+                input.span(),
+                "Expected the path to a repair function after `invariant =`",
+            ));
+        }
+        Ok(Self { repair: input.parse()? })
+    }
+}
+
+/// Read the `#[fuzz(invariant = repair_fn)]` attribute of the type, and return the path to the repair function
+fn read_invariant_attribute(tb: &mut TokenBuilder, attrs: &[Attribute]) -> Option<TokenStream> {
+    let mut repair = None;
+    for attribute in attrs.iter() {
+        if attribute.path.is_ident("fuzz") {
+            match parse2::<InvariantAttribute>(attribute.tokens.clone()) {
+                Ok(attribute) => repair = Some(attribute.repair),
+                Err(e) => tb.stream(e.to_compile_error()),
+            }
+        }
+    }
+    repair
+}
+
+/// The weight of an enum variant, given by `#[fuzz(weight = N)]`
+struct VariantWeightAttribute {
+    weight: f64,
+}
+impl syn::parse::Parse for VariantWeightAttribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _ = parenthesized!(content in input);
+        let input = content;
+
+        let ident = input.call(Ident::parse_any)?;
+        if ident != "weight" {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!("{ident} is not a valid fuzz attribute for an enum variant, expected `weight`"),
+            ));
+        }
+        let _ = input.parse::<Token![=]>()?;
+        let lit = input.parse::<syn::Lit>()?;
+        let weight = match &lit {
+            syn::Lit::Int(x) => x.base10_parse::<u64>()? as f64,
+            syn::Lit::Float(x) => x.base10_parse::<f64>()?,
+            _ => {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "Expected a number as the weight of the variant",
+                ))
+            }
+        };
+        if !(weight.is_finite() && weight > 0.0) {
+            return Err(syn::Error::new(
+                lit.span(),
+                "The weight of a variant must be positive, use #[ignore_variant] to never generate it",
+            ));
+        }
+        Ok(Self { weight })
+    }
+}
+
+/// Read the `#[fuzz(weight = N)]` attributes of each non-ignored variant of the enum.
+///
+/// Returns `None` if no variant has a weight, otherwise the variants without the attribute have a weight of 1.
+fn read_variant_weights(tb: &mut TokenBuilder, enu: &syn::DataEnum) -> Option<Vec<f64>> {
+    let mut any_weight = false;
+    let weights = enu
+        .variants
+        .iter()
+        .filter(|variant| variant.attrs.iter().all(|attr| !has_ignore_variant_attribute(attr)))
+        .map(|variant| {
+            let mut weight = 1.0;
+            for attribute in variant.attrs.iter() {
+                if attribute.path.is_ident("fuzz") {
+                    match parse2::<VariantWeightAttribute>(attribute.tokens.clone()) {
+                        Ok(attribute) => {
+                            any_weight = true;
+                            weight = attribute.weight;
+                        }
+                        Err(e) => tb.stream(e.to_compile_error()),
+                    }
+                }
+            }
+            weight
+        })
+        .collect::<Vec<_>>();
+    if any_weight {
+        Some(weights)
+    } else {
+        None
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use syn::{parse2, DeriveInput};
@@ -3,7 +3,7 @@ use syn::punctuated::Punctuated;
 use syn::token::Where;
 use syn::{parse2, Field, Generics, Visibility, WhereClause};
 
-use crate::token_builder::{ident, join_ts, ts};
+use crate::token_builder::{ident, join_ts, ts, TokenBuilder};
 use crate::{q, Common, MakeMutatorSettings};
 
 // This file hosts the common code for generating default mutators for enums and structs
@@ -60,6 +60,31 @@ pub(crate) struct CreateWrapperMutatorParams<'a> {
     pub(crate) settings: &'a MakeMutatorSettings,
 }
 
+/// Wrap the inner mutator in a `RepairMutator` if the type has a `#[fuzz(invariant = repair_fn)]` attribute.
+///
+/// Returns the type of the wrapped mutator and the expression initialising it from `inner_mutator_init`.
+#[allow(non_snake_case)]
+pub(crate) fn repair_inner_mutator(
+    tb: &mut TokenBuilder,
+    cm: &Common,
+    type_ident: &Ident,
+    type_generics: &Generics,
+    settings: &MakeMutatorSettings,
+    InnerMutator: TokenStream,
+    inner_mutator_init: TokenStream,
+) -> (TokenStream, TokenStream) {
+    if let Some(repair) = crate::read_invariant_attribute(tb, &settings.ty.attrs) {
+        let RepairMutator = ts!(cm.mutators "::repair::RepairMutator");
+        let (_, generic_args, _) = type_generics.split_for_impl();
+        (
+            ts!(RepairMutator "<" InnerMutator ", fn(&mut " type_ident q!(generic_args) ")>"),
+            ts!(RepairMutator "{ mutator:" inner_mutator_init ", repair:" repair "}"),
+        )
+    } else {
+        (InnerMutator, inner_mutator_init)
+    }
+}
+
 #[allow(non_snake_case)]
 pub(crate) fn make_mutator_type_and_impl(params: CreateWrapperMutatorParams) -> TokenStream {
     let CreateWrapperMutatorParams {
@@ -0,0 +1,111 @@
+use proc_macro2::{Ident, TokenStream};
+use syn::{parse2, DeriveInput, Fields, Generics, WhereClause};
+
+use crate::q;
+use crate::token_builder::{access_field, extend_ts, join_ts, safe_field_ident, ts, TokenBuilder};
+
+/// Returns the format string and the arguments that build the expression of a struct or variant
+/// named `path` (relative to the module of the type) with the given fields.
+///
+/// The fields are given by the expressions that borrow them, e.g. `&self.x` or `_0`.
+fn format_fields(path: &str, fields: &Fields, access: impl Fn(&syn::Field, usize) -> TokenStream) -> TokenStream {
+    let (open, close) = match fields {
+        Fields::Named(_) => (" {{ ", " }}"),
+        Fields::Unnamed(_) => ("(", ")"),
+        Fields::Unit => ("", ""),
+    };
+    let mut format_string = format!("{{}}::{}", path);
+    if !matches!(fields, Fields::Unit) {
+        format_string.push_str(open);
+        for (i, field) in fields.iter().enumerate() {
+            if i != 0 {
+                format_string.push_str(", ");
+            }
+            if let Some(ident) = &field.ident {
+                format_string.push_str(&format!("{}: ", ident));
+            }
+            format_string.push_str("{}");
+        }
+        format_string.push_str(close);
+    }
+    ts!(
+        "::std::format!(" q!(format_string) ", ::std::module_path!()"
+            join_ts!(fields.iter().enumerate(), (i, field),
+                ", fuzzcheck::ToRustExpr::to_rust_expr(" access(field, i) ")"
+            )
+        ")"
+    )
+}
+
+/// Returns the pattern binding the fields of a variant, e.g. `{ x, y }` or `(_0, _1)`
+fn pattern(fields: &Fields) -> TokenStream {
+    match fields {
+        Fields::Named(_) => {
+            ts!("{" join_ts!(fields.iter().enumerate(), (i, field), safe_field_ident(field, i), separator: ",") "}")
+        }
+        Fields::Unnamed(_) => {
+            ts!("(" join_ts!(fields.iter().enumerate(), (i, field), safe_field_ident(field, i), separator: ",") ")")
+        }
+        Fields::Unit => ts!(),
+    }
+}
+
+fn impl_header(tb: &mut TokenBuilder, ident: &Ident, generics: &Generics) {
+    let mut new_generics = generics.clone();
+    if new_generics.where_clause.is_none() {
+        new_generics.where_clause = Some(WhereClause {
+            where_token: <_>::default(),
+            predicates: <_>::default(),
+        });
+    }
+    for tp in generics.type_params() {
+        let where_clause = new_generics.where_clause.as_mut().unwrap();
+        where_clause
+            .predicates
+            .push(parse2(ts!(tp.ident.clone() ": fuzzcheck::ToRustExpr")).unwrap());
+    }
+    let generics_split = new_generics.split_for_impl();
+    extend_ts!(tb,
+        "impl" q!(generics_split.0) "fuzzcheck::ToRustExpr for" ident q!(generics_split.1) q!(generics_split.2)
+    );
+}
+
+pub(crate) fn impl_to_rust_expr(tb: &mut TokenBuilder, item: &DeriveInput) {
+    let ident = &item.ident;
+    let body = match &item.data {
+        syn::Data::Struct(s) => format_fields(
+            &ident.to_string(),
+            &s.fields,
+            |field, i| ts!("&self." access_field(field, i)),
+        ),
+        syn::Data::Enum(e) => {
+            if e.variants.is_empty() {
+                ts!("match *self {}")
+            } else {
+                ts!("match self {"
+                    join_ts!(&e.variants, variant,
+                        "Self::" variant.ident pattern(&variant.fields) "=>"
+                            format_fields(&format!("{}::{}", ident, variant.ident), &variant.fields, |field, i| {
+                                ts!(safe_field_ident(field, i))
+                            })
+                    , separator: ",")
+                "}")
+            }
+        }
+        syn::Data::Union(_) => {
+            extend_ts!(tb,
+                "compile_error!(" q!("Unions are not supported by fuzzcheck’s procedural macros.") ");"
+            );
+            return;
+        }
+    };
+    impl_header(tb, ident, &item.generics);
+    extend_ts!(tb,
+        "{
+            #[no_coverage]
+            fn to_rust_expr(&self) -> ::std::string::String {"
+                body
+            "}
+        }"
+    );
+}
@@ -174,7 +174,7 @@ pub(crate) fn impl_default_mutator_for_struct(
         .map(|(i, field)| {
             let mut mutator = None;
             for attribute in field.attrs.iter() {
-                match super::read_field_default_mutator_attribute(attribute) {
+                match super::read_field_default_mutator_attribute(attribute, &field.ty) {
                     Ok(Some(field_mutator_attribute)) => {
                         mutator = Some((field_mutator_attribute.ty, field_mutator_attribute.equal));
                     }
@@ -217,7 +217,23 @@ pub(crate) fn impl_default_mutator_for_struct(
         ">"
     );
 
-    use crate::structs_and_enums::{make_mutator_type_and_impl, CreateWrapperMutatorParams};
+    use crate::structs_and_enums::{make_mutator_type_and_impl, repair_inner_mutator, CreateWrapperMutatorParams};
+
+    let (InnerMutator, inner_mutator_init) = repair_inner_mutator(
+        tb,
+        &cm,
+        struct_ident,
+        generics,
+        settings,
+        TupleMutatorWrapper,
+        ts!(
+            cm.TupleMutatorWrapper "::new(" TupleNMutator "::new("
+                join_ts!(struc.fields.iter().enumerate(), (idx, field),
+                    ident!("mutator_" access_field(field, idx))
+                , separator: ",")
+            "))"
+        ),
+    );
 
     let params = CreateWrapperMutatorParams {
         cm: &cm,
@@ -225,7 +241,7 @@ pub(crate) fn impl_default_mutator_for_struct(
         type_ident: struct_ident,
         type_generics: generics,
         field_mutators: &field_mutators,
-        InnerMutator: &TupleMutatorWrapper,
+        InnerMutator: &InnerMutator,
         new_impl: &ts!(
             "
             #[no_coverage]
@@ -235,11 +251,7 @@ pub(crate) fn impl_default_mutator_for_struct(
             , separator: ",")
             ") -> Self {
             Self {
-                mutator : " cm.TupleMutatorWrapper "::new(" TupleNMutator "::new("
-                    join_ts!(struc.fields.iter().enumerate(), (idx, field),
-                        ident!("mutator_" access_field(field, idx))
-                    , separator: ",")
-                    "))
+                mutator : " inner_mutator_init "
             }
             }"
         ),